@@ -9,17 +9,15 @@ RUST_BACKTRACE=1 RUST_LOG=trace cargo run -p pulsar-demo-tokio --bin full 127.0.
 use std::{error, str, time::Duration};
 
 use chrono::{Duration as ChronoDuration, Utc};
-use futures_util::future::try_join_all;
+use futures_util::{future::try_join_all, StreamExt as _};
 use log::{debug, error, info};
 use tokio::{net::TcpStream, task::spawn, time::sleep};
 
 use pulsar_client::{
     spec::{
-        types::{
-            AckValidationError, CompressionType, MessageProperties, ProtocolVersion, SubscribeType,
-        },
-        AckCommand, ConnectCommand, FlowCommand, MessageCommandPayload, ProducerCommand,
-        RedeliverUnacknowledgedMessagesCommand, SendCommand, SubscribeCommand,
+        types::{CompressionType, MessageProperties, ProtocolVersion, SubscribeType},
+        AckCommand, ConnectCommand, ProducerCommand, RedeliverUnacknowledgedMessagesCommand,
+        SendCommand, SubscribeCommand,
     },
     tokio_io::{client::AsyncClient, connection::AsyncConnection},
 };
@@ -41,13 +39,12 @@ async fn run() -> Result<(), Box<dyn error::Error>> {
     debug!("{:?}", sess);
 
     spawn(async move {
-        // TODO, remove loop
-
         loop {
             match handler.handle().await {
                 Ok(_) => {}
                 Err((err, _channel_storage)) => {
                     error!("{:?}", err);
+                    break;
                 }
             }
         }
@@ -55,12 +52,16 @@ async fn run() -> Result<(), Box<dyn error::Error>> {
 
     //
     let mut consumers = vec![];
-    for _ in 0..2 {
-        let subscribe_command = SubscribeCommand::new(
+    for i in 0..2 {
+        let mut subscribe_command = SubscribeCommand::new(
             "persistent://public/default/my-topic",
             "first-subscription",
             SubscribeType::Shared,
         );
+        // Only the first consumer receives.
+        if i == 0 {
+            subscribe_command.set_receiver_queue_size(100);
+        }
         let consumer = sess.raw_create_consumer(subscribe_command).await?;
         debug!("{:?}", consumer);
         consumers.push(consumer);
@@ -78,98 +79,39 @@ async fn run() -> Result<(), Box<dyn error::Error>> {
 
     //
     spawn(async move {
-        let consumer = consumer1;
-        let flow_command = FlowCommand::new(100);
-        consumer.raw_flow(flow_command).await.unwrap();
-
-        while let Ok(message_command) = consumer.get_message().await {
-            if let Some(message_command) = message_command {
-                let message_id = message_command.get_message_id().unwrap();
-
-                if message_command.get_is_checksum_mismatch() == Some(true) {
-                    error!("{:?} {:?}", consumer.get_consumer_id(), message_id);
-
-                    let ack_command =
-                        AckCommand::individual(&[message_id], AckValidationError::ChecksumMismatch);
-                    consumer.raw_ack(ack_command).await.unwrap();
-                    continue;
-                }
-
-                match message_command.get_payload() {
-                    MessageCommandPayload::Single(Ok(bytes)) => {
-                        if fastrand::u8(1..10) < 4 {
-                            info!(
-                                "{:?} {:?} {:?} {:?}",
-                                consumer.get_consumer_id(),
-                                message_command.get_message_metadata().get_properties(),
-                                str::from_utf8(bytes),
-                                (message_id.get_ledger_id(), message_id.get_entry_id())
-                            );
-
-                            let ack_command =
-                                AckCommand::individual(&[message_id.to_owned()], None);
-                            consumer.raw_ack(ack_command).await.unwrap();
-                        } else {
-                            info!(
-                                "{:?} redeliver {:?} {:?} {:?}",
-                                consumer.get_consumer_id(),
-                                message_command.get_message_metadata().get_properties(),
-                                str::from_utf8(bytes),
-                                (message_id.get_ledger_id(), message_id.get_entry_id())
-                            );
-                            let redeliver_unacknowledged_messages_command =
-                                RedeliverUnacknowledgedMessagesCommand::new(&[message_id]);
-                            consumer
-                                .raw_redeliver_unacknowledged_messages(
-                                    redeliver_unacknowledged_messages_command,
-                                )
-                                .await
-                                .unwrap();
-                        }
-                    }
-                    MessageCommandPayload::Single(Err(err)) => {
-                        error!(
-                            "{:?} {:?} {:?}",
-                            consumer.get_consumer_id(),
-                            message_id,
-                            err
-                        );
-
-                        let ack_command =
-                            AckCommand::individual(&[message_id], AckValidationError::from(err));
-                        consumer.raw_ack(ack_command).await.unwrap();
-                    }
-                    MessageCommandPayload::Batch(Ok(msgs)) => {
-                        info!(
-                            "{:?} {:?} {:?}",
-                            consumer.get_consumer_id(),
-                            msgs.iter()
-                                .map(|(single_message_metadata, bytes)| (
-                                    single_message_metadata.get_properties(),
-                                    str::from_utf8(bytes)
-                                ))
-                                .collect::<Vec<_>>(),
-                            (message_id.get_ledger_id(), message_id.get_entry_id())
-                        );
-
-                        let ack_command = AckCommand::individual(&[message_id], None);
-                        consumer.raw_ack(ack_command).await.unwrap();
-                    }
-                    MessageCommandPayload::Batch(Err(err)) => {
-                        error!(
-                            "{:?} {:?} {:?}",
-                            consumer.get_consumer_id(),
-                            message_id,
-                            err
-                        );
-
-                        let ack_command =
-                            AckCommand::individual(&[message_id], AckValidationError::from(err));
-                        consumer.raw_ack(ack_command).await.unwrap();
-                    }
-                }
+        let mut consumer = consumer1;
+
+        while let Some(message) = consumer.next().await {
+            let message = message.unwrap();
+            let message_id = message.get_message_id().to_owned();
+
+            if fastrand::u8(1..10) < 4 {
+                info!(
+                    "{:?} {:?} {:?} {:?}",
+                    consumer.get_consumer_id(),
+                    message.get_properties(),
+                    str::from_utf8(message.get_payload()),
+                    (message_id.get_ledger_id(), message_id.get_entry_id())
+                );
+
+                let ack_command = AckCommand::individual(&[message_id], None);
+                consumer.raw_ack(ack_command).await.unwrap();
             } else {
-                sleep(Duration::from_secs(1)).await;
+                info!(
+                    "{:?} redeliver {:?} {:?} {:?}",
+                    consumer.get_consumer_id(),
+                    message.get_properties(),
+                    str::from_utf8(message.get_payload()),
+                    (message_id.get_ledger_id(), message_id.get_entry_id())
+                );
+                let redeliver_unacknowledged_messages_command =
+                    RedeliverUnacknowledgedMessagesCommand::new(&[message_id]);
+                consumer
+                    .raw_redeliver_unacknowledged_messages(
+                        redeliver_unacknowledged_messages_command,
+                    )
+                    .await
+                    .unwrap();
             }
         }
     });
@@ -0,0 +1,81 @@
+/*
+openssl req -x509 -newkey rsa:2048 -nodes -days 365 -subj "/CN=localhost" \
+    -addext "subjectAltName=DNS:localhost" -keyout /tmp/broker.key.pem -out /tmp/broker.cert.pem
+
+conf/standalone.conf
+brokerServicePortTls=6651
+tlsCertificateFilePath=/tmp/broker.cert.pem
+tlsKeyFilePath=/tmp/broker.key.pem
+
+bin/pulsar standalone
+
+RUST_BACKTRACE=1 RUST_LOG=trace cargo run -p pulsar-demo-tokio --features tls --bin tls pulsar+ssl://127.0.0.1:6651 /tmp/broker.cert.pem localhost
+*/
+
+use std::{env, error, fs};
+
+use log::{debug, error};
+use tokio::task::spawn;
+
+use pulsar_client::{
+    spec::{
+        broker_service_url::Url, types::ProtocolVersion, ConnectCommand, ProducerCommand,
+        SendCommand,
+    },
+    tls::{TlsConnector, TlsConnectorConfig},
+    tokio_io::{client::AsyncClient, connection::AsyncConnection},
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn error::Error>> {
+    run().await
+}
+
+async fn run() -> Result<(), Box<dyn error::Error>> {
+    env_logger::init();
+
+    let url: Url = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "pulsar+ssl://127.0.0.1:6651".to_owned())
+        .parse()?;
+    let ca_certs_path = env::args()
+        .nth(2)
+        .unwrap_or_else(|| "/tmp/broker.cert.pem".to_owned());
+    let server_name = env::args().nth(3).unwrap_or_else(|| "localhost".to_owned());
+
+    let mut tls_config = TlsConnectorConfig::default();
+    tls_config
+        .set_ca_certs_pem(&fs::read(ca_certs_path)?)
+        .set_server_name(&server_name);
+    let tls_connector = TlsConnector::new(&tls_config)?;
+
+    let stream = tls_connector.connect(&url).await?;
+    let conn = AsyncConnection::new(stream, None);
+
+    let mut connect_command = ConnectCommand::new("Pulsar-Client-Rust-v0.1.0");
+    connect_command.set_protocol_version(ProtocolVersion::V17);
+    let (sess, mut handler) = AsyncClient::new(conn).raw_connect(connect_command).await?;
+    debug!("{:?}", sess);
+
+    spawn(async move {
+        loop {
+            match handler.handle().await {
+                Ok(_) => {}
+                Err((err, _channel_storage)) => {
+                    error!("{:?}", err);
+                    break;
+                }
+            }
+        }
+    });
+
+    let producer_command = ProducerCommand::new("persistent://public/default/my-topic");
+    let producer = sess.raw_create_producer(producer_command).await?;
+    debug!("{:?}", producer);
+
+    let send_command = SendCommand::single(producer.next_sequence_id(), None, b"over tls", None);
+    let send_receipt_command = producer.raw_send(send_command).await?;
+    debug!("{:?}", send_receipt_command);
+
+    Ok(())
+}
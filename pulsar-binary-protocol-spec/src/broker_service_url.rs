@@ -2,7 +2,7 @@ use std::{str::FromStr, string::ToString};
 
 use thiserror::Error;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Url {
     pub scheme: Scheme,
     pub host: url::Host,
@@ -14,7 +14,7 @@ impl ToString for Url {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Scheme {
     Tcp,
     Tls,
@@ -61,12 +61,67 @@ impl FromStr for Url {
             .ok_or(ParseError::HostMissing)
             .map(|x| x.to_owned())?;
 
-        let port = url.port().unwrap_or_else(|| 6651);
+        let port = url.port().unwrap_or(match scheme {
+            Scheme::Tcp => 6650,
+            Scheme::Tls => 6651,
+        });
 
         Ok(Self { scheme, host, port })
     }
 }
 
+// One or more hosts, like pulsar://h1:6650,h2:6650,h3
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServiceUrl {
+    urls: Vec<Url>,
+}
+impl ServiceUrl {
+    pub fn new(urls: Vec<Url>) -> Option<Self> {
+        if urls.is_empty() {
+            None
+        } else {
+            Some(Self { urls })
+        }
+    }
+
+    pub fn get_urls(&self) -> &[Url] {
+        &self.urls[..]
+    }
+}
+impl ToString for ServiceUrl {
+    fn to_string(&self) -> String {
+        let hosts = self
+            .urls
+            .iter()
+            .map(|url| format!("{}:{}", url.host, url.port))
+            .collect::<Vec<_>>();
+        format!("{}://{}/", self.urls[0].scheme.to_string(), hosts.join(","))
+    }
+}
+
+impl FromStr for ServiceUrl {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = match s.find("://") {
+            Some(i) => (&s[..i], &s[i + 3..]),
+            None => ("pulsar", s),
+        };
+        let hosts = match rest.find('/') {
+            Some(i) => &rest[..i],
+            None => rest,
+        };
+
+        let urls = hosts
+            .split(',')
+            .filter(|host| !host.is_empty())
+            .map(|host| format!("{}://{}", scheme, host).parse())
+            .collect::<Result<Vec<Url>, _>>()?;
+
+        Self::new(urls).ok_or(ParseError::HostMissing)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,6 +148,37 @@ mod tests {
         assert_eq!(url.port, 6651);
         assert_eq!(url.to_string(), "pulsar://broker.example.com:6651/");
 
+        let url: Url = "pulsar://broker.example.com".parse()?;
+        assert_eq!(url.port, 6650);
+
+        let url: Url = "pulsar+ssl://broker.example.com".parse()?;
+        assert_eq!(url.port, 6651);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_service_url() -> Result<(), Box<dyn error::Error>> {
+        let service_url: ServiceUrl = "pulsar://h1:6650,h2:6660,h3/".parse()?;
+        let urls = service_url.get_urls();
+        assert_eq!(urls.len(), 3);
+        assert_eq!(urls[0].to_string(), "pulsar://h1:6650/");
+        assert_eq!(urls[1].to_string(), "pulsar://h2:6660/");
+        assert_eq!(urls[2].to_string(), "pulsar://h3:6650/");
+        assert_eq!(service_url.to_string(), "pulsar://h1:6650,h2:6660,h3:6650/");
+
+        let service_url: ServiceUrl = "pulsar+ssl://h1,h2".parse()?;
+        assert!(service_url
+            .get_urls()
+            .iter()
+            .all(|url| url.scheme == Scheme::Tls && url.port == 6651));
+
+        let service_url: ServiceUrl = "broker.example.com".parse()?;
+        assert_eq!(service_url.get_urls().len(), 1);
+
+        assert!("pulsar://".parse::<ServiceUrl>().is_err());
+        assert!("http://h1,h2".parse::<ServiceUrl>().is_err());
+
         Ok(())
     }
 }
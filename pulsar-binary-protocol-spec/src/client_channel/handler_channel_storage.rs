@@ -14,7 +14,7 @@ pub type SessionReceiver = AC_Receiver<SessionSendHandlerChannelMessage>;
 pub type ProducerReceiver = AC_Receiver<ProducerSendHandlerChannelMessage>;
 pub type ConsumerReceiver = AC_Receiver<ConsumerSendHandlerChannelMessage>;
 
-#[derive(Debug)]
+#[derive(Default, Debug)]
 pub struct HandlerChannelStorage(HashMap<HandlerChannelStorageKey, HandlerChannelStorageValue>);
 impl HandlerChannelStorage {
     pub fn new(session_receiver: SessionReceiver) -> Self {
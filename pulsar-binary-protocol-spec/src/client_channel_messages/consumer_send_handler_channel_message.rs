@@ -12,7 +12,7 @@ use crate::{
 
 use super::handler_reply_consumer_channel_message::{
     HandlerReplyConsumerAckChannelMessage, HandlerReplyConsumerFlowChannelMessage,
-    HandlerReplyConsumerGetMessageChannelMessage,
+    HandlerReplyConsumerGetMessageChannelMessage, HandlerReplyConsumerReceiveMessageChannelMessage,
     HandlerReplyConsumerRedeliverUnacknowledgedMessagesChannelMessage,
 };
 
@@ -22,6 +22,8 @@ pub enum ConsumerSendHandlerChannelMessage {
         FC_Sender<HandlerReplyConsumerFlowChannelMessage>,
     ),
     GetMessage(FC_Sender<HandlerReplyConsumerGetMessageChannelMessage>),
+    // Replied once a message is pushed, unlike GetMessage.
+    ReceiveMessage(FC_Sender<HandlerReplyConsumerReceiveMessageChannelMessage>),
     Ack(
         <ConsumerAckRespond as Respond>::Request,
         FC_Sender<HandlerReplyConsumerAckChannelMessage>,
@@ -46,6 +48,7 @@ impl ConsumerSendHandlerChannelMessage {
                 ConsumerSendHandlerChannelMessageGroup::Flow(command, s)
             }
             Self::GetMessage(s) => ConsumerSendHandlerChannelMessageGroup::GetMessage(s),
+            Self::ReceiveMessage(s) => ConsumerSendHandlerChannelMessageGroup::ReceiveMessage(s),
             Self::Ack(mut c, s) => {
                 c.set_consumer_id(consumer_id);
 
@@ -75,6 +78,7 @@ pub enum ConsumerSendHandlerChannelMessageGroup {
         FC_Sender<Result<(), <ConsumerFlowHalfRequest as HalfRequest>::Error>>,
     ),
     GetMessage(FC_Sender<Option<MessageCommand>>),
+    ReceiveMessage(FC_Sender<MessageCommand>),
     PendingRequest(RequestId, PendingRequestValue, Box<Command>),
     RedeliverUnacknowledgedMessages(
         Command,
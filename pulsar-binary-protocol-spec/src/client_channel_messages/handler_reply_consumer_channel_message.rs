@@ -9,6 +9,7 @@ use crate::{
 pub type HandlerReplyConsumerFlowChannelMessage =
     Result<(), <ConsumerFlowHalfRequest as HalfRequest>::Error>;
 pub type HandlerReplyConsumerGetMessageChannelMessage = Option<MessageCommand>;
+pub type HandlerReplyConsumerReceiveMessageChannelMessage = MessageCommand;
 pub type HandlerReplyConsumerAckChannelMessage =
     Result<<ConsumerAckRespond as Respond>::Response, <ConsumerAckRespond as Respond>::Error>;
 pub type HandlerReplyConsumerRedeliverUnacknowledgedMessagesChannelMessage =
@@ -17,6 +18,7 @@ pub type HandlerReplyConsumerRedeliverUnacknowledgedMessagesChannelMessage =
 pub enum HandlerReplyConsumerChannelMessage {
     ReplyFlow(HandlerReplyConsumerFlowChannelMessage),
     ReplyGetMessage(Box<HandlerReplyConsumerGetMessageChannelMessage>),
+    ReplyReceiveMessage(Box<HandlerReplyConsumerReceiveMessageChannelMessage>),
     ReplyAck(HandlerReplyConsumerAckChannelMessage),
     ReplyRedeliverUnacknowledgedMessages(
         HandlerReplyConsumerRedeliverUnacknowledgedMessagesChannelMessage,
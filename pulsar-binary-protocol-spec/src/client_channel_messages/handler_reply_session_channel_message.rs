@@ -1,6 +1,10 @@
 use crate::{
     client_channel::AC_Sender,
-    client_responds::{Respond, SessionCreateConsumerRespond, SessionCreateProducerRespond},
+    client_responds::{
+        Respond, SessionCreateConsumerRespond, SessionCreateProducerRespond,
+        SessionGetTopicsOfNamespaceRespond, SessionLookupTopicRespond,
+        SessionPartitionedTopicMetadataRespond,
+    },
 };
 
 use super::{ConsumerSendHandlerChannelMessage, ProducerSendHandlerChannelMessage};
@@ -23,7 +27,34 @@ pub type HandlerReplySessionCreateConsumerChannelMessage = Result<
     <SessionCreateConsumerRespond as Respond>::Error,
 >;
 
+pub type HandlerReplySessionLookupTopicChannelMessage = Result<
+    (
+        <SessionLookupTopicRespond as Respond>::Request,
+        <SessionLookupTopicRespond as Respond>::Response,
+    ),
+    <SessionLookupTopicRespond as Respond>::Error,
+>;
+
+pub type HandlerReplySessionPartitionedTopicMetadataChannelMessage = Result<
+    (
+        <SessionPartitionedTopicMetadataRespond as Respond>::Request,
+        <SessionPartitionedTopicMetadataRespond as Respond>::Response,
+    ),
+    <SessionPartitionedTopicMetadataRespond as Respond>::Error,
+>;
+
+pub type HandlerReplySessionGetTopicsOfNamespaceChannelMessage = Result<
+    (
+        <SessionGetTopicsOfNamespaceRespond as Respond>::Request,
+        <SessionGetTopicsOfNamespaceRespond as Respond>::Response,
+    ),
+    <SessionGetTopicsOfNamespaceRespond as Respond>::Error,
+>;
+
 pub enum HandlerReplySessionChannelMessage {
     ReplyCreateProducer(HandlerReplySessionCreateProducerChannelMessage),
     ReplyCreateConsumer(HandlerReplySessionCreateConsumerChannelMessage),
+    ReplyLookupTopic(HandlerReplySessionLookupTopicChannelMessage),
+    ReplyPartitionedTopicMetadata(HandlerReplySessionPartitionedTopicMetadataChannelMessage),
+    ReplyGetTopicsOfNamespace(HandlerReplySessionGetTopicsOfNamespaceChannelMessage),
 }
@@ -1,6 +1,6 @@
 use crate::{
     client_channel::FC_Sender,
-    client_handler::PendingSequenceValue,
+    client_handler::{PendingSequenceGuard, PendingSequenceValue},
     client_responds::{ProducerSendRespond, Respond},
     command::Command,
     types::{ProducerId, ProducerName, SequenceId},
@@ -12,6 +12,7 @@ pub enum ProducerSendHandlerChannelMessage {
     Send(
         <ProducerSendRespond as Respond>::Request,
         FC_Sender<HandlerReplyProducerSendChannelMessage>,
+        Option<PendingSequenceGuard>,
     ),
 }
 
@@ -22,15 +23,19 @@ impl ProducerSendHandlerChannelMessage {
         producer_name: ProducerName,
     ) -> ProducerSendHandlerChannelMessageGroup {
         match self {
-            Self::Send(mut c, s) => {
-                c.set_producer_id(producer_id);
+            Self::Send(mut c, s, guard) => {
+                c.set_producer_id(producer_id.to_owned());
                 c.set_producer_name(producer_name);
                 let command = Command::from(&c);
 
                 ProducerSendHandlerChannelMessageGroup::PendingSequence(
                     c.get_sequence_id(),
-                    s,
-                    command,
+                    PendingSequenceValue {
+                        producer_id,
+                        sender: s,
+                        command,
+                        guard,
+                    },
                 )
             }
         }
@@ -38,5 +43,5 @@ impl ProducerSendHandlerChannelMessage {
 }
 
 pub enum ProducerSendHandlerChannelMessageGroup {
-    PendingSequence(SequenceId, PendingSequenceValue, Command),
+    PendingSequence(SequenceId, PendingSequenceValue),
 }
@@ -1,7 +1,11 @@
 use crate::{
     client_channel::FC_Sender,
     client_handler::PendingRequestValue,
-    client_responds::{Respond, SessionCreateConsumerRespond, SessionCreateProducerRespond},
+    client_responds::{
+        Respond, SessionCreateConsumerRespond, SessionCreateProducerRespond,
+        SessionGetTopicsOfNamespaceRespond, SessionLookupTopicRespond,
+        SessionPartitionedTopicMetadataRespond,
+    },
     command::Command,
     types::{ConsumerIdBuilder, ProducerIdBuilder, RequestId, RequestIdBuilder},
 };
@@ -9,6 +13,9 @@ use crate::{
 use super::handler_reply_session_channel_message::{
     HandlerReplySessionCreateConsumerChannelMessage,
     HandlerReplySessionCreateProducerChannelMessage,
+    HandlerReplySessionGetTopicsOfNamespaceChannelMessage,
+    HandlerReplySessionLookupTopicChannelMessage,
+    HandlerReplySessionPartitionedTopicMetadataChannelMessage,
 };
 
 pub enum SessionSendHandlerChannelMessage {
@@ -20,6 +27,18 @@ pub enum SessionSendHandlerChannelMessage {
         <SessionCreateConsumerRespond as Respond>::Request,
         FC_Sender<HandlerReplySessionCreateConsumerChannelMessage>,
     ),
+    LookupTopic(
+        <SessionLookupTopicRespond as Respond>::Request,
+        FC_Sender<HandlerReplySessionLookupTopicChannelMessage>,
+    ),
+    PartitionedTopicMetadata(
+        <SessionPartitionedTopicMetadataRespond as Respond>::Request,
+        FC_Sender<HandlerReplySessionPartitionedTopicMetadataChannelMessage>,
+    ),
+    GetTopicsOfNamespace(
+        <SessionGetTopicsOfNamespaceRespond as Respond>::Request,
+        FC_Sender<HandlerReplySessionGetTopicsOfNamespaceChannelMessage>,
+    ),
 }
 
 impl SessionSendHandlerChannelMessage {
@@ -60,6 +79,45 @@ impl SessionSendHandlerChannelMessage {
                     command,
                 )
             }
+            Self::LookupTopic(mut c, s) => {
+                if c.get_request_id().is_require_set() {
+                    c.set_request_id(request_id_builder.next());
+                }
+                let command = Command::from(&c);
+                (
+                    (
+                        c.get_request_id(),
+                        PendingRequestValue::SessionLookupTopic(c, s),
+                    ),
+                    command,
+                )
+            }
+            Self::PartitionedTopicMetadata(mut c, s) => {
+                if c.get_request_id().is_require_set() {
+                    c.set_request_id(request_id_builder.next());
+                }
+                let command = Command::from(&c);
+                (
+                    (
+                        c.get_request_id(),
+                        PendingRequestValue::SessionPartitionedTopicMetadata(c, s),
+                    ),
+                    command,
+                )
+            }
+            Self::GetTopicsOfNamespace(mut c, s) => {
+                if c.get_request_id().is_require_set() {
+                    c.set_request_id(request_id_builder.next());
+                }
+                let command = Command::from(&c);
+                (
+                    (
+                        c.get_request_id(),
+                        PendingRequestValue::SessionGetTopicsOfNamespace(c, s),
+                    ),
+                    command,
+                )
+            }
         }
     }
 }
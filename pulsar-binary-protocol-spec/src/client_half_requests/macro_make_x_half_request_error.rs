@@ -12,6 +12,9 @@ macro_rules! make_x_half_request_error {
 
                 #[error("WriteError {0:?}")]
                 WriteError(IoError),
+
+                #[error("ConnectionLost")]
+                ConnectionLost,
             }
 
             impl From<WriteCommandError> for [<$name HalfRequestError>] {
@@ -28,11 +28,62 @@ pub(super) fn handle_error(
                         Err((c.get_error(), c.get_message()).into()),
                     ))),
                 ),
+                PendingRequestValue::SessionLookupTopic(lookup_topic_command, s) => Ok(
+                    HandlerHandleOutput::OnResponded(Box::new(OnResponded::SessionLookupTopic(
+                        lookup_topic_command,
+                        s,
+                        Err((c.get_error(), c.get_message()).into()),
+                    ))),
+                ),
+                PendingRequestValue::SessionPartitionedTopicMetadata(
+                    partitioned_topic_metadata_command,
+                    s,
+                ) => Ok(HandlerHandleOutput::OnResponded(Box::new(
+                    OnResponded::SessionPartitionedTopicMetadata(
+                        partitioned_topic_metadata_command,
+                        s,
+                        Err((c.get_error(), c.get_message()).into()),
+                    ),
+                ))),
+                PendingRequestValue::SessionGetTopicsOfNamespace(
+                    get_topics_of_namespace_command,
+                    s,
+                ) => Ok(HandlerHandleOutput::OnResponded(Box::new(
+                    OnResponded::SessionGetTopicsOfNamespace(
+                        get_topics_of_namespace_command,
+                        s,
+                        Err((c.get_error(), c.get_message()).into()),
+                    ),
+                ))),
                 PendingRequestValue::ConsumerAck(s) => {
                     Ok(HandlerHandleOutput::OnResponded(Box::new(
                         OnResponded::ConsumerAck(s, Err((c.get_error(), c.get_message()).into())),
                     )))
                 }
+                PendingRequestValue::ProducerReconnect(producer_command) => Ok(
+                    HandlerHandleOutput::OnResponded(Box::new(OnResponded::ProducerReconnect(
+                        producer_command,
+                        Err((c.get_error(), c.get_message()).into()),
+                    ))),
+                ),
+                PendingRequestValue::ConsumerReconnect(subscribe_command) => Ok(
+                    HandlerHandleOutput::OnResponded(Box::new(OnResponded::ConsumerReconnect(
+                        subscribe_command,
+                        Err((c.get_error(), c.get_message()).into()),
+                    ))),
+                ),
+                PendingRequestValue::ConsumerClose(close_consumer_command) => Ok(
+                    HandlerHandleOutput::OnResponded(Box::new(OnResponded::ConsumerClose(
+                        close_consumer_command,
+                        Err((c.get_error(), c.get_message()).into()),
+                    ))),
+                ),
+                PendingRequestValue::ProducerClose(close_producer_command) => Ok(
+                    HandlerHandleOutput::OnResponded(Box::new(OnResponded::ProducerClose(
+                        close_producer_command,
+                        Err((c.get_error(), c.get_message()).into()),
+                    ))),
+                ),
             }
         } else {
             Err(HandlerHandleError::PendingRequestNotFount(
@@ -0,0 +1,43 @@
+use crate::{
+    commands::GetTopicsOfNamespaceResponseCommand, protos::protobuf::pulsar_api::BaseCommand,
+};
+
+use super::{
+    HandlerHandleError, HandlerHandleOutput, OnResponded, PendingRequestValue, PendingRequests,
+};
+
+pub(super) fn handle_get_topics_of_namespace_response(
+    base_command: &BaseCommand,
+    pending_requests: &mut PendingRequests,
+) -> Result<HandlerHandleOutput, HandlerHandleError> {
+    if let Some(c) = base_command.getTopicsOfNamespaceResponse.as_ref() {
+        let c = GetTopicsOfNamespaceResponseCommand {
+            inner_command: c.to_owned(),
+        };
+        if let Some(pending_request) = pending_requests.remove(&c.get_request_id()) {
+            match pending_request {
+                PendingRequestValue::SessionGetTopicsOfNamespace(
+                    get_topics_of_namespace_command,
+                    s,
+                ) => Ok(HandlerHandleOutput::OnResponded(Box::new(
+                    OnResponded::SessionGetTopicsOfNamespace(
+                        get_topics_of_namespace_command,
+                        s,
+                        Ok(c),
+                    ),
+                ))),
+                _ => Err(HandlerHandleError::PendingRequestMismatch(
+                    base_command.to_owned(),
+                )),
+            }
+        } else {
+            Err(HandlerHandleError::PendingRequestNotFount(
+                base_command.to_owned(),
+            ))
+        }
+    } else {
+        Err(HandlerHandleError::BaseCommandInvalid(
+            base_command.to_owned(),
+        ))
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{
+    commands::PartitionedTopicMetadataResponseCommand, protos::protobuf::pulsar_api::BaseCommand,
+};
+
+use super::{
+    HandlerHandleError, HandlerHandleOutput, OnResponded, PendingRequestValue, PendingRequests,
+};
+
+pub(super) fn handle_partitioned_metadata_response(
+    base_command: &BaseCommand,
+    pending_requests: &mut PendingRequests,
+) -> Result<HandlerHandleOutput, HandlerHandleError> {
+    if let Some(c) = base_command.partitionMetadataResponse.as_ref() {
+        let c = PartitionedTopicMetadataResponseCommand {
+            inner_command: c.to_owned(),
+        };
+        if let Some(pending_request) = pending_requests.remove(&c.get_request_id()) {
+            match pending_request {
+                PendingRequestValue::SessionPartitionedTopicMetadata(
+                    partitioned_topic_metadata_command,
+                    s,
+                ) => {
+                    let res = if c.is_failed() {
+                        Err((c.get_error(), c.get_message()).into())
+                    } else {
+                        Ok(c)
+                    };
+                    Ok(HandlerHandleOutput::OnResponded(Box::new(
+                        OnResponded::SessionPartitionedTopicMetadata(
+                            partitioned_topic_metadata_command,
+                            s,
+                            res,
+                        ),
+                    )))
+                }
+                _ => Err(HandlerHandleError::PendingRequestMismatch(
+                    base_command.to_owned(),
+                )),
+            }
+        } else {
+            Err(HandlerHandleError::PendingRequestNotFount(
+                base_command.to_owned(),
+            ))
+        }
+    } else {
+        Err(HandlerHandleError::BaseCommandInvalid(
+            base_command.to_owned(),
+        ))
+    }
+}
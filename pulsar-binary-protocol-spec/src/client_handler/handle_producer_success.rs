@@ -19,6 +19,11 @@ pub(super) fn handle_producer_success(
                         OnResponded::SessionCreateProducer(producer_command, s, Ok(c)),
                     )))
                 }
+                PendingRequestValue::ProducerReconnect(producer_command) => {
+                    Ok(HandlerHandleOutput::OnResponded(Box::new(
+                        OnResponded::ProducerReconnect(producer_command, Ok(c)),
+                    )))
+                }
                 _ => Err(HandlerHandleError::PendingRequestMismatch(
                     base_command.to_owned(),
                 )),
@@ -1,6 +1,9 @@
 use crate::{commands::SendErrorCommand, protos::protobuf::pulsar_api::BaseCommand};
 
-use super::{HandlerHandleError, HandlerHandleOutput, OnResponded, PendingSequences};
+use super::{
+    pending_sequences::TakePendingSequenceError, HandlerHandleError, HandlerHandleOutput,
+    OnResponded, PendingSequences,
+};
 
 pub(super) fn handle_send_error(
     base_command: &BaseCommand,
@@ -10,17 +13,19 @@ pub(super) fn handle_send_error(
         let c = SendErrorCommand {
             inner_command: c.to_owned(),
         };
-        if let Some(pending_sequence) = pending_sequences.remove(&c.get_sequence_id()) {
-            Ok(HandlerHandleOutput::OnResponded(Box::new(
+        match pending_sequences.take(c.get_producer_id(), c.get_sequence_id()) {
+            Ok(pending_sequence) => Ok(HandlerHandleOutput::OnResponded(Box::new(
                 OnResponded::ProducerSend(
-                    pending_sequence,
+                    pending_sequence.sender,
                     Err((c.get_error(), c.get_message()).into()),
                 ),
-            )))
-        } else {
-            Err(HandlerHandleError::PendingSequenceNotFount(
-                base_command.to_owned(),
-            ))
+            ))),
+            Err(TakePendingSequenceError::NotFound) => Err(
+                HandlerHandleError::PendingSequenceNotFount(base_command.to_owned()),
+            ),
+            Err(TakePendingSequenceError::OutOfOrder) => Err(
+                HandlerHandleError::PendingSequenceOutOfOrder(base_command.to_owned()),
+            ),
         }
     } else {
         Err(HandlerHandleError::BaseCommandInvalid(
@@ -1,6 +1,9 @@
 use crate::{commands::SendReceiptCommand, protos::protobuf::pulsar_api::BaseCommand};
 
-use super::{HandlerHandleError, HandlerHandleOutput, OnResponded, PendingSequences};
+use super::{
+    pending_sequences::TakePendingSequenceError, HandlerHandleError, HandlerHandleOutput,
+    OnResponded, PendingSequences,
+};
 
 pub(super) fn handle_send_receipt(
     base_command: &BaseCommand,
@@ -10,14 +13,16 @@ pub(super) fn handle_send_receipt(
         let c = SendReceiptCommand {
             inner_command: c.to_owned(),
         };
-        if let Some(pending_sequence) = pending_sequences.remove(&c.get_sequence_id()) {
-            Ok(HandlerHandleOutput::OnResponded(Box::new(
-                OnResponded::ProducerSend(pending_sequence, Ok(c)),
-            )))
-        } else {
-            Err(HandlerHandleError::PendingSequenceNotFount(
-                base_command.to_owned(),
-            ))
+        match pending_sequences.take(c.get_producer_id(), c.get_sequence_id()) {
+            Ok(pending_sequence) => Ok(HandlerHandleOutput::OnResponded(Box::new(
+                OnResponded::ProducerSend(pending_sequence.sender, Ok(c)),
+            ))),
+            Err(TakePendingSequenceError::NotFound) => Err(
+                HandlerHandleError::PendingSequenceNotFount(base_command.to_owned()),
+            ),
+            Err(TakePendingSequenceError::OutOfOrder) => Err(
+                HandlerHandleError::PendingSequenceOutOfOrder(base_command.to_owned()),
+            ),
         }
     } else {
         Err(HandlerHandleError::BaseCommandInvalid(
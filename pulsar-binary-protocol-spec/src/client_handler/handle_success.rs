@@ -19,6 +19,21 @@ pub(super) fn handle_success(
                         OnResponded::SessionCreateConsumer(subscribe_command, s, Ok(c)),
                     )))
                 }
+                PendingRequestValue::ConsumerReconnect(subscribe_command) => {
+                    Ok(HandlerHandleOutput::OnResponded(Box::new(
+                        OnResponded::ConsumerReconnect(subscribe_command, Ok(c)),
+                    )))
+                }
+                PendingRequestValue::ConsumerClose(close_consumer_command) => {
+                    Ok(HandlerHandleOutput::OnResponded(Box::new(
+                        OnResponded::ConsumerClose(close_consumer_command, Ok(c)),
+                    )))
+                }
+                PendingRequestValue::ProducerClose(close_producer_command) => {
+                    Ok(HandlerHandleOutput::OnResponded(Box::new(
+                        OnResponded::ProducerClose(close_producer_command, Ok(c)),
+                    )))
+                }
                 _ => Err(HandlerHandleError::PendingRequestMismatch(
                     base_command.to_owned(),
                 )),
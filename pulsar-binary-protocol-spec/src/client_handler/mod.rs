@@ -10,7 +10,10 @@ use crate::{
 mod handle_ack_response;
 mod handle_connected;
 mod handle_error;
+mod handle_get_topics_of_namespace_response;
+mod handle_lookup_response;
 mod handle_message;
+mod handle_partitioned_metadata_response;
 mod handle_ping;
 mod handle_pong;
 mod handle_producer_success;
@@ -24,11 +27,14 @@ pub mod pending_messages;
 pub mod pending_requests;
 pub mod pending_sequences;
 
+#[cfg(test)]
+mod tests;
+
 pub use errors::{ReadCommandError, WriteCommandError};
 pub use on_responded::OnResponded;
 pub use pending_messages::PendingMessages;
 pub use pending_requests::{PendingRequestValue, PendingRequests};
-pub use pending_sequences::{PendingSequenceValue, PendingSequences};
+pub use pending_sequences::{PendingSequenceGuard, PendingSequenceValue, PendingSequences};
 
 #[derive(Debug)]
 pub enum HandlerHandleOutput {
@@ -53,6 +59,9 @@ pub enum HandlerHandleError {
 
     #[error("PendingSequenceNotFount {0:?}")]
     PendingSequenceNotFount(BaseCommand),
+    // Responded before an older sequence of the same producer.
+    #[error("PendingSequenceOutOfOrder {0:?}")]
+    PendingSequenceOutOfOrder(BaseCommand),
 
     #[error("Unsupported {0:?}")]
     Unsupported(BaseCommand),
@@ -73,6 +82,21 @@ pub fn handle(
             }
             Type::SUCCESS => handle_success::handle_success(&c.message, pending_requests),
             Type::ERROR => handle_error::handle_error(&c.message, pending_requests),
+            Type::LOOKUP_RESPONSE => {
+                handle_lookup_response::handle_lookup_response(&c.message, pending_requests)
+            }
+            Type::GET_TOPICS_OF_NAMESPACE_RESPONSE => {
+                handle_get_topics_of_namespace_response::handle_get_topics_of_namespace_response(
+                    &c.message,
+                    pending_requests,
+                )
+            }
+            Type::PARTITIONED_METADATA_RESPONSE => {
+                handle_partitioned_metadata_response::handle_partitioned_metadata_response(
+                    &c.message,
+                    pending_requests,
+                )
+            }
 
             //
             Type::SEND_RECEIPT => {
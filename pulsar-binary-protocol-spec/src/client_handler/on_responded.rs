@@ -6,11 +6,16 @@ use crate::{
         handler_reply_session_channel_message::{
             HandlerReplySessionCreateConsumerChannelMessage,
             HandlerReplySessionCreateProducerChannelMessage,
+            HandlerReplySessionGetTopicsOfNamespaceChannelMessage,
+            HandlerReplySessionLookupTopicChannelMessage,
+            HandlerReplySessionPartitionedTopicMetadataChannelMessage,
         },
     },
     client_responds::{
-        ConsumerAckRespond, ProducerSendRespond, Respond, SessionCreateConsumerRespond,
-        SessionCreateProducerRespond,
+        ConsumerAckRespond, ConsumerCloseRespond, ProducerCloseRespond, ProducerSendRespond,
+        Respond, SessionCreateConsumerRespond, SessionCreateProducerRespond,
+        SessionGetTopicsOfNamespaceRespond, SessionLookupTopicRespond,
+        SessionPartitionedTopicMetadataRespond,
     },
 };
 
@@ -32,6 +37,30 @@ pub enum OnResponded {
             <SessionCreateConsumerRespond as Respond>::Error,
         >,
     ),
+    SessionLookupTopic(
+        <SessionLookupTopicRespond as Respond>::Request,
+        FC_Sender<HandlerReplySessionLookupTopicChannelMessage>,
+        Result<
+            <SessionLookupTopicRespond as Respond>::Response,
+            <SessionLookupTopicRespond as Respond>::Error,
+        >,
+    ),
+    SessionPartitionedTopicMetadata(
+        <SessionPartitionedTopicMetadataRespond as Respond>::Request,
+        FC_Sender<HandlerReplySessionPartitionedTopicMetadataChannelMessage>,
+        Result<
+            <SessionPartitionedTopicMetadataRespond as Respond>::Response,
+            <SessionPartitionedTopicMetadataRespond as Respond>::Error,
+        >,
+    ),
+    SessionGetTopicsOfNamespace(
+        <SessionGetTopicsOfNamespaceRespond as Respond>::Request,
+        FC_Sender<HandlerReplySessionGetTopicsOfNamespaceChannelMessage>,
+        Result<
+            <SessionGetTopicsOfNamespaceRespond as Respond>::Response,
+            <SessionGetTopicsOfNamespaceRespond as Respond>::Error,
+        >,
+    ),
     ProducerSend(
         FC_Sender<HandlerReplyProducerSendChannelMessage>,
         Result<<ProducerSendRespond as Respond>::Response, <ProducerSendRespond as Respond>::Error>,
@@ -40,4 +69,32 @@ pub enum OnResponded {
         FC_Sender<HandlerReplyConsumerAckChannelMessage>,
         Result<<ConsumerAckRespond as Respond>::Response, <ConsumerAckRespond as Respond>::Error>,
     ),
+    ProducerReconnect(
+        <SessionCreateProducerRespond as Respond>::Request,
+        Result<
+            <SessionCreateProducerRespond as Respond>::Response,
+            <SessionCreateProducerRespond as Respond>::Error,
+        >,
+    ),
+    ConsumerReconnect(
+        <SessionCreateConsumerRespond as Respond>::Request,
+        Result<
+            <SessionCreateConsumerRespond as Respond>::Response,
+            <SessionCreateConsumerRespond as Respond>::Error,
+        >,
+    ),
+    ConsumerClose(
+        <ConsumerCloseRespond as Respond>::Request,
+        Result<
+            <ConsumerCloseRespond as Respond>::Response,
+            <ConsumerCloseRespond as Respond>::Error,
+        >,
+    ),
+    ProducerClose(
+        <ProducerCloseRespond as Respond>::Request,
+        Result<
+            <ProducerCloseRespond as Respond>::Response,
+            <ProducerCloseRespond as Respond>::Error,
+        >,
+    ),
 }
@@ -7,9 +7,16 @@ use crate::{
         handler_reply_session_channel_message::{
             HandlerReplySessionCreateConsumerChannelMessage,
             HandlerReplySessionCreateProducerChannelMessage,
+            HandlerReplySessionGetTopicsOfNamespaceChannelMessage,
+            HandlerReplySessionLookupTopicChannelMessage,
+            HandlerReplySessionPartitionedTopicMetadataChannelMessage,
         },
     },
-    client_responds::{Respond, SessionCreateConsumerRespond, SessionCreateProducerRespond},
+    client_responds::{
+        ConsumerCloseRespond, ProducerCloseRespond, Respond, SessionCreateConsumerRespond,
+        SessionCreateProducerRespond, SessionGetTopicsOfNamespaceRespond,
+        SessionLookupTopicRespond, SessionPartitionedTopicMetadataRespond,
+    },
     types::RequestId,
 };
 
@@ -24,5 +31,24 @@ pub enum PendingRequestValue {
         <SessionCreateConsumerRespond as Respond>::Request,
         FC_Sender<HandlerReplySessionCreateConsumerChannelMessage>,
     ),
+    SessionLookupTopic(
+        <SessionLookupTopicRespond as Respond>::Request,
+        FC_Sender<HandlerReplySessionLookupTopicChannelMessage>,
+    ),
+    SessionPartitionedTopicMetadata(
+        <SessionPartitionedTopicMetadataRespond as Respond>::Request,
+        FC_Sender<HandlerReplySessionPartitionedTopicMetadataChannelMessage>,
+    ),
+    SessionGetTopicsOfNamespace(
+        <SessionGetTopicsOfNamespaceRespond as Respond>::Request,
+        FC_Sender<HandlerReplySessionGetTopicsOfNamespaceChannelMessage>,
+    ),
     ConsumerAck(FC_Sender<HandlerReplyConsumerAckChannelMessage>),
+    // Re-registration after a reconnection, nobody waits for the respond.
+    ProducerReconnect(<SessionCreateProducerRespond as Respond>::Request),
+    ConsumerReconnect(<SessionCreateConsumerRespond as Respond>::Request),
+    // Sent once the consumer is dropped, nobody waits for the respond.
+    ConsumerClose(<ConsumerCloseRespond as Respond>::Request),
+    // Sent once the producer is dropped, nobody waits for the respond.
+    ProducerClose(<ProducerCloseRespond as Respond>::Request),
 }
@@ -1,11 +1,144 @@
-use std::collections::BTreeMap;
+use std::collections::{btree_map::IntoIter, BTreeMap, VecDeque};
 
 use crate::{
     client_channel::FC_Sender,
     client_channel_messages::handler_reply_producer_channel_message::HandlerReplyProducerSendChannelMessage,
-    types::SequenceId,
+    command::Command,
+    types::{ProducerId, SequenceId},
 };
 
-pub type PendingSequences = BTreeMap<SequenceId, PendingSequenceValue>;
+// Keyed by producer, every producer numbers its own sequences.
+#[derive(Default)]
+pub struct PendingSequences {
+    values: BTreeMap<(ProducerId, SequenceId), PendingSequenceValue>,
+    // In the order of sending, the broker responds in this order.
+    sequence_ids: BTreeMap<ProducerId, VecDeque<SequenceId>>,
+}
 
-pub type PendingSequenceValue = FC_Sender<HandlerReplyProducerSendChannelMessage>;
+pub struct PendingSequenceValue {
+    pub producer_id: ProducerId,
+    pub sender: FC_Sender<HandlerReplyProducerSendChannelMessage>,
+    // Kept for resending after a reconnection.
+    pub command: Command,
+    pub guard: Option<PendingSequenceGuard>,
+}
+
+// Dropped once the send is responded, failed or timed out, e.g. the permits of the
+// pending limits of the producer.
+pub type PendingSequenceGuard = Box<dyn Send + Sync>;
+
+pub(super) enum TakePendingSequenceError {
+    NotFound,
+    OutOfOrder,
+}
+
+impl PendingSequences {
+    // Should be called in the order of sending.
+    pub fn insert(&mut self, sequence_id: SequenceId, value: PendingSequenceValue) {
+        let producer_id = value.producer_id.to_owned();
+        if self
+            .values
+            .insert((producer_id.to_owned(), sequence_id.to_owned()), value)
+            .is_some()
+        {
+            // The sequence id was reused, the replaced one is never responded.
+            self.remove_sequence_id(&producer_id, &sequence_id);
+        }
+        self.sequence_ids
+            .entry(producer_id)
+            .or_default()
+            .push_back(sequence_id);
+    }
+
+    pub fn remove(
+        &mut self,
+        producer_id: &ProducerId,
+        sequence_id: &SequenceId,
+    ) -> Option<PendingSequenceValue> {
+        let value = self
+            .values
+            .remove(&(producer_id.to_owned(), sequence_id.to_owned()))?;
+        self.remove_sequence_id(producer_id, sequence_id);
+        Some(value)
+    }
+
+    // In the order of sending.
+    pub fn remove_producer(&mut self, producer_id: &ProducerId) -> Vec<PendingSequenceValue> {
+        self.sequence_ids
+            .remove(producer_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|sequence_id| self.values.remove(&(producer_id.to_owned(), sequence_id)))
+            .collect()
+    }
+
+    // In the order of sending.
+    pub fn get_producer(
+        &self,
+        producer_id: &ProducerId,
+    ) -> impl Iterator<Item = &PendingSequenceValue> + '_ {
+        let producer_id = producer_id.to_owned();
+        self.sequence_ids
+            .get(&producer_id)
+            .into_iter()
+            .flatten()
+            .filter_map(move |sequence_id| {
+                self.values
+                    .get(&(producer_id.to_owned(), sequence_id.to_owned()))
+            })
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &(ProducerId, SequenceId)> {
+        self.values.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    // The broker responds to the sends of one producer in order, so the respond is for the
+    // oldest pending sequence of the producer.
+    pub(super) fn take(
+        &mut self,
+        producer_id: ProducerId,
+        sequence_id: SequenceId,
+    ) -> Result<PendingSequenceValue, TakePendingSequenceError> {
+        let oldest_sequence_id = self
+            .sequence_ids
+            .get(&producer_id)
+            .and_then(|sequence_ids| sequence_ids.front());
+
+        if oldest_sequence_id == Some(&sequence_id) {
+            self.remove(&producer_id, &sequence_id)
+                .ok_or(TakePendingSequenceError::NotFound)
+        } else if self.values.contains_key(&(producer_id, sequence_id)) {
+            Err(TakePendingSequenceError::OutOfOrder)
+        } else {
+            Err(TakePendingSequenceError::NotFound)
+        }
+    }
+
+    fn remove_sequence_id(&mut self, producer_id: &ProducerId, sequence_id: &SequenceId) {
+        if let Some(sequence_ids) = self.sequence_ids.get_mut(producer_id) {
+            if let Some(i) = sequence_ids.iter().position(|s| s == sequence_id) {
+                sequence_ids.remove(i);
+            }
+            if sequence_ids.is_empty() {
+                self.sequence_ids.remove(producer_id);
+            }
+        }
+    }
+}
+
+impl IntoIterator for PendingSequences {
+    type Item = ((ProducerId, SequenceId), PendingSequenceValue);
+    type IntoIter = IntoIter<(ProducerId, SequenceId), PendingSequenceValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
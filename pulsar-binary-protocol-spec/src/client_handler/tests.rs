@@ -0,0 +1,317 @@
+/*
+cargo test --package pulsar-binary-protocol-spec --lib -- client_handler --nocapture
+*/
+
+use std::sync::Arc;
+
+#[cfg(feature = "with-asynchronous")]
+use futures_channel::oneshot::channel;
+#[cfg(not(feature = "with-asynchronous"))]
+use std::sync::mpsc::channel;
+
+use crate::{
+    command::{Command, CommandWithParsed, SimpleCommand},
+    commands::{GetTopicsOfNamespaceCommand, SendCommand},
+    frame::{FrameParseOutput, FrameParser, FrameRenderer},
+    protos::protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type, ServerError},
+    types::{ProducerId, RequestId, SequenceId, TopicsOfNamespaceMode},
+};
+
+use super::{
+    handle, HandlerHandleError, HandlerHandleOutput, OnResponded, PendingRequestValue,
+    PendingRequests, PendingSequenceValue, PendingSequences,
+};
+
+fn add_pending_sequence(
+    pending_sequences: &mut PendingSequences,
+    producer_id: u64,
+    sequence_id: u64,
+) {
+    let (sender, _) = channel();
+    let send_command = SendCommand::single(SequenceId::new(sequence_id), None, b"foo", None);
+
+    pending_sequences.insert(
+        SequenceId::new(sequence_id),
+        PendingSequenceValue {
+            producer_id: ProducerId::new(producer_id),
+            sender,
+            command: Command::from(&send_command),
+            guard: None,
+        },
+    );
+}
+
+fn send_receipt(producer_id: u64, sequence_id: u64) -> CommandWithParsed {
+    let mut message = BaseCommand::new();
+    message.set_field_type(Type::SEND_RECEIPT);
+    message.mut_send_receipt().set_producer_id(producer_id);
+    message.mut_send_receipt().set_sequence_id(sequence_id);
+
+    CommandWithParsed::Simple(SimpleCommand { message })
+}
+
+fn send_error(producer_id: u64, sequence_id: u64) -> CommandWithParsed {
+    let mut message = BaseCommand::new();
+    message.set_field_type(Type::SEND_ERROR);
+    message.mut_send_error().set_producer_id(producer_id);
+    message.mut_send_error().set_sequence_id(sequence_id);
+    message
+        .mut_send_error()
+        .set_error(ServerError::PersistenceError);
+    message.mut_send_error().set_message("foo".to_owned());
+
+    CommandWithParsed::Simple(SimpleCommand { message })
+}
+
+fn pending_keys(pending_sequences: &PendingSequences) -> Vec<(u64, u64)> {
+    pending_sequences
+        .keys()
+        .map(|(p, s)| (u64::from(p.to_owned()), u64::from(s.to_owned())))
+        .collect()
+}
+
+#[test]
+fn send_receipt_with_multiple_producers() {
+    let mut pending_requests = PendingRequests::default();
+    let mut pending_sequences = PendingSequences::default();
+    for producer_id in 1..=3 {
+        for sequence_id in 1..=2 {
+            add_pending_sequence(&mut pending_sequences, producer_id, sequence_id);
+        }
+    }
+
+    for (producer_id, sequence_id) in &[(2, 1), (1, 1), (2, 2), (3, 1)] {
+        match handle(
+            &send_receipt(*producer_id, *sequence_id),
+            &mut pending_requests,
+            &mut pending_sequences,
+        ) {
+            Ok(HandlerHandleOutput::OnResponded(on_responded)) => match *on_responded {
+                OnResponded::ProducerSend(_, Ok(c)) => {
+                    assert_eq!(u64::from(c.get_producer_id()), *producer_id);
+                    assert_eq!(u64::from(c.get_sequence_id()), *sequence_id);
+                }
+                on_responded => panic!("{:?}", on_responded),
+            },
+            ret => panic!("{:?}", ret),
+        }
+    }
+
+    assert_eq!(pending_keys(&pending_sequences), vec![(1, 2), (3, 2)]);
+}
+
+#[test]
+fn send_error_with_multiple_producers() {
+    let mut pending_requests = PendingRequests::default();
+    let mut pending_sequences = PendingSequences::default();
+    add_pending_sequence(&mut pending_sequences, 1, 1);
+    add_pending_sequence(&mut pending_sequences, 2, 1);
+
+    match handle(
+        &send_error(2, 1),
+        &mut pending_requests,
+        &mut pending_sequences,
+    ) {
+        Ok(HandlerHandleOutput::OnResponded(on_responded)) => match *on_responded {
+            OnResponded::ProducerSend(_, Err(_)) => {}
+            on_responded => panic!("{:?}", on_responded),
+        },
+        ret => panic!("{:?}", ret),
+    }
+
+    assert_eq!(pending_keys(&pending_sequences), vec![(1, 1)]);
+}
+
+#[test]
+fn send_receipt_drops_guard() {
+    let mut pending_requests = PendingRequests::default();
+    let mut pending_sequences = PendingSequences::default();
+    let guard = Arc::new(());
+    let (sender, _) = channel();
+    let send_command = SendCommand::single(SequenceId::new(1), None, b"foo", None);
+    pending_sequences.insert(
+        SequenceId::new(1),
+        PendingSequenceValue {
+            producer_id: ProducerId::new(1),
+            sender,
+            command: Command::from(&send_command),
+            guard: Some(Box::new(guard.to_owned())),
+        },
+    );
+    assert_eq!(Arc::strong_count(&guard), 2);
+
+    match handle(
+        &send_receipt(1, 1),
+        &mut pending_requests,
+        &mut pending_sequences,
+    ) {
+        Ok(HandlerHandleOutput::OnResponded(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+
+    assert_eq!(Arc::strong_count(&guard), 1);
+}
+
+#[test]
+fn send_receipt_not_found() {
+    let mut pending_requests = PendingRequests::default();
+    let mut pending_sequences = PendingSequences::default();
+    add_pending_sequence(&mut pending_sequences, 1, 2);
+
+    // Other producer.
+    match handle(
+        &send_receipt(2, 2),
+        &mut pending_requests,
+        &mut pending_sequences,
+    ) {
+        Err(HandlerHandleError::PendingSequenceNotFount(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+
+    // Already responded.
+    match handle(
+        &send_receipt(1, 1),
+        &mut pending_requests,
+        &mut pending_sequences,
+    ) {
+        Err(HandlerHandleError::PendingSequenceNotFount(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+
+    assert_eq!(pending_keys(&pending_sequences), vec![(1, 2)]);
+}
+
+#[test]
+fn send_receipt_out_of_order() {
+    let mut pending_requests = PendingRequests::default();
+    let mut pending_sequences = PendingSequences::default();
+    add_pending_sequence(&mut pending_sequences, 1, 1);
+    add_pending_sequence(&mut pending_sequences, 1, 2);
+    add_pending_sequence(&mut pending_sequences, 2, 1);
+
+    match handle(
+        &send_receipt(1, 2),
+        &mut pending_requests,
+        &mut pending_sequences,
+    ) {
+        Err(HandlerHandleError::PendingSequenceOutOfOrder(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+
+    assert_eq!(
+        pending_keys(&pending_sequences),
+        vec![(1, 1), (1, 2), (2, 1)]
+    );
+}
+
+#[test]
+fn send_receipt_in_sending_order() {
+    let mut pending_requests = PendingRequests::default();
+    let mut pending_sequences = PendingSequences::default();
+    // The sequence ids were taken concurrently, and sent in another order.
+    for sequence_id in &[2, 1, 3] {
+        add_pending_sequence(&mut pending_sequences, 1, *sequence_id);
+    }
+
+    for sequence_id in &[2, 1, 3] {
+        match handle(
+            &send_receipt(1, *sequence_id),
+            &mut pending_requests,
+            &mut pending_sequences,
+        ) {
+            Ok(HandlerHandleOutput::OnResponded(_)) => {}
+            ret => panic!("{:?}", ret),
+        }
+    }
+
+    assert!(pending_sequences.is_empty());
+}
+
+#[test]
+fn remove_producer() {
+    let mut pending_sequences = PendingSequences::default();
+    for sequence_id in &[2, 1, 3] {
+        add_pending_sequence(&mut pending_sequences, 1, *sequence_id);
+        add_pending_sequence(&mut pending_sequences, 2, *sequence_id);
+    }
+    assert!(pending_sequences
+        .remove(&ProducerId::new(2), &SequenceId::new(1))
+        .is_some());
+
+    let sequence_ids = pending_sequences
+        .get_producer(&ProducerId::new(2))
+        .map(|v| match &v.command {
+            Command::Payload(c) => c.message.get_send().get_sequence_id(),
+            Command::Simple(_) => panic!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(sequence_ids, vec![2, 3]);
+
+    assert_eq!(
+        pending_sequences.remove_producer(&ProducerId::new(1)).len(),
+        3
+    );
+    assert_eq!(pending_keys(&pending_sequences), vec![(2, 2), (2, 3)]);
+}
+
+#[test]
+fn get_topics_of_namespace_response() {
+    let mut pending_requests = PendingRequests::default();
+    let mut pending_sequences = PendingSequences::default();
+    let (sender, _) = channel();
+    pending_requests.insert(
+        RequestId::new(1),
+        PendingRequestValue::SessionGetTopicsOfNamespace(
+            GetTopicsOfNamespaceCommand::new("public/default", TopicsOfNamespaceMode::Persistent),
+            sender,
+        ),
+    );
+
+    let mut message = BaseCommand::new();
+    message.set_field_type(Type::GET_TOPICS_OF_NAMESPACE_RESPONSE);
+    message.mut_getTopicsOfNamespaceResponse().set_request_id(1);
+    message
+        .mut_getTopicsOfNamespaceResponse()
+        .mut_topics()
+        .push("persistent://public/default/a-partition-0".to_owned());
+    message
+        .mut_getTopicsOfNamespaceResponse()
+        .mut_topics()
+        .push("persistent://public/default/b".to_owned());
+
+    let mut buf = Vec::new();
+    FrameRenderer::new()
+        .render(Command::Simple(SimpleCommand { message }), &mut buf)
+        .unwrap();
+    let command = match FrameParser::new().parse(&buf[..]).unwrap() {
+        FrameParseOutput::Completed(_, command) => command,
+        _ => panic!(),
+    };
+
+    match handle(&command, &mut pending_requests, &mut pending_sequences) {
+        Ok(HandlerHandleOutput::OnResponded(on_responded)) => match *on_responded {
+            OnResponded::SessionGetTopicsOfNamespace(get_topics_of_namespace_command, _, Ok(c)) => {
+                assert_eq!(
+                    get_topics_of_namespace_command.get_namespace(),
+                    "public/default"
+                );
+                assert_eq!(
+                    c.get_topics(),
+                    &[
+                        "persistent://public/default/a-partition-0".to_owned(),
+                        "persistent://public/default/b".to_owned(),
+                    ]
+                );
+            }
+            ret => panic!("{:?}", ret),
+        },
+        ret => panic!("{:?}", ret),
+    }
+    assert!(pending_requests.is_empty());
+
+    // Already responded.
+    match handle(&command, &mut pending_requests, &mut pending_sequences) {
+        Err(HandlerHandleError::PendingRequestNotFount(_)) => {}
+        ret => panic!("{:?}", ret),
+    }
+}
@@ -0,0 +1,14 @@
+use crate::commands::{CloseConsumerCommand, SuccessCommand};
+
+use super::Respond;
+
+pub struct ConsumerCloseRespond {}
+impl Respond for ConsumerCloseRespond {
+    type Request = CloseConsumerCommand;
+    type Response = SuccessCommand;
+    type Error = ConsumerCloseRespondError;
+}
+
+make_x_respond_error!(
+    ConsumerClose;
+);
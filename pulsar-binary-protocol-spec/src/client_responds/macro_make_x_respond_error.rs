@@ -19,6 +19,12 @@ macro_rules! make_x_respond_error {
                 #[error("FrameParseError {0:?}")]
                 FrameParseError(#[from] FrameParseError),
 
+                #[error("ConnectionLost")]
+                ConnectionLost,
+
+                #[error("Timeout")]
+                Timeout,
+
                 $(
                     #[error("{server_error:?} {msg}")]
                     [<SE $variant>] { server_error: ServerError, msg: String },
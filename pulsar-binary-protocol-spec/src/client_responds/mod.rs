@@ -9,12 +9,19 @@ pub trait Respond {
 
 pub mod connect_respond;
 pub mod consumer_ack_respond;
+pub mod consumer_close_respond;
+pub mod producer_close_respond;
 pub mod producer_send_respond;
 pub mod session_create_consumer_respond;
 pub mod session_create_producer_respond;
+pub mod session_get_topics_of_namespace_respond;
+pub mod session_lookup_topic_respond;
+pub mod session_partitioned_topic_metadata_respond;
 
 pub use connect_respond::{ConnectRespond, ConnectRespondError};
 pub use consumer_ack_respond::{ConsumerAckRespond, ConsumerAckRespondError};
+pub use consumer_close_respond::{ConsumerCloseRespond, ConsumerCloseRespondError};
+pub use producer_close_respond::{ProducerCloseRespond, ProducerCloseRespondError};
 pub use producer_send_respond::{ProducerSendRespond, ProducerSendRespondError};
 pub use session_create_consumer_respond::{
     SessionCreateConsumerRespond, SessionCreateConsumerRespondError,
@@ -22,3 +29,10 @@ pub use session_create_consumer_respond::{
 pub use session_create_producer_respond::{
     SessionCreateProducerRespond, SessionCreateProducerRespondError,
 };
+pub use session_get_topics_of_namespace_respond::{
+    SessionGetTopicsOfNamespaceRespond, SessionGetTopicsOfNamespaceRespondError,
+};
+pub use session_lookup_topic_respond::{SessionLookupTopicRespond, SessionLookupTopicRespondError};
+pub use session_partitioned_topic_metadata_respond::{
+    SessionPartitionedTopicMetadataRespond, SessionPartitionedTopicMetadataRespondError,
+};
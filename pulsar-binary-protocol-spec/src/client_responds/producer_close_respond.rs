@@ -0,0 +1,14 @@
+use crate::commands::{CloseProducerCommand, SuccessCommand};
+
+use super::Respond;
+
+pub struct ProducerCloseRespond {}
+impl Respond for ProducerCloseRespond {
+    type Request = CloseProducerCommand;
+    type Response = SuccessCommand;
+    type Error = ProducerCloseRespondError;
+}
+
+make_x_respond_error!(
+    ProducerClose;
+);
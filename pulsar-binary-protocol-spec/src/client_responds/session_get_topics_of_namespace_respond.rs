@@ -0,0 +1,17 @@
+use crate::commands::{GetTopicsOfNamespaceCommand, GetTopicsOfNamespaceResponseCommand};
+
+use super::Respond;
+
+pub struct SessionGetTopicsOfNamespaceRespond {}
+impl Respond for SessionGetTopicsOfNamespaceRespond {
+    type Request = GetTopicsOfNamespaceCommand;
+    type Response = GetTopicsOfNamespaceResponseCommand;
+    type Error = SessionGetTopicsOfNamespaceRespondError;
+}
+
+make_x_respond_error!(
+    SessionGetTopicsOfNamespace;
+    ServiceNotReady "TODO",
+    MetadataError "TODO",
+    AuthorizationError "TODO"
+);
@@ -0,0 +1,18 @@
+use crate::commands::{LookupTopicCommand, LookupTopicResponseCommand};
+
+use super::Respond;
+
+pub struct SessionLookupTopicRespond {}
+impl Respond for SessionLookupTopicRespond {
+    type Request = LookupTopicCommand;
+    type Response = LookupTopicResponseCommand;
+    type Error = SessionLookupTopicRespondError;
+}
+
+make_x_respond_error!(
+    SessionLookupTopic;
+    ServiceNotReady "TODO",
+    MetadataError "TODO",
+    TopicNotFound "TODO",
+    AuthorizationError "TODO"
+);
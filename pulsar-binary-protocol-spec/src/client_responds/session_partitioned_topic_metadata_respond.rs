@@ -0,0 +1,18 @@
+use crate::commands::{PartitionedTopicMetadataCommand, PartitionedTopicMetadataResponseCommand};
+
+use super::Respond;
+
+pub struct SessionPartitionedTopicMetadataRespond {}
+impl Respond for SessionPartitionedTopicMetadataRespond {
+    type Request = PartitionedTopicMetadataCommand;
+    type Response = PartitionedTopicMetadataResponseCommand;
+    type Error = SessionPartitionedTopicMetadataRespondError;
+}
+
+make_x_respond_error!(
+    SessionPartitionedTopicMetadata;
+    ServiceNotReady "TODO",
+    MetadataError "TODO",
+    TopicNotFound "TODO",
+    AuthorizationError "TODO"
+);
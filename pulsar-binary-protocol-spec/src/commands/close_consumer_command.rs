@@ -0,0 +1,47 @@
+use protobuf::SingularPtrField;
+
+use crate::{
+    command::{Command, SimpleCommand},
+    protos::protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type, CommandCloseConsumer},
+    types::{ConsumerId, RequestId},
+};
+
+#[derive(Clone, Debug)]
+pub struct CloseConsumerCommand {
+    #[cfg(feature = "with-hacking-commands")]
+    pub inner_command: CommandCloseConsumer,
+    #[cfg(not(feature = "with-hacking-commands"))]
+    pub(crate) inner_command: CommandCloseConsumer,
+}
+impl CloseConsumerCommand {
+    pub fn new(consumer_id: ConsumerId) -> Self {
+        let mut inner_command = CommandCloseConsumer::new();
+        inner_command.set_consumer_id(consumer_id.into());
+
+        Self { inner_command }
+    }
+
+    pub fn get_consumer_id(&self) -> ConsumerId {
+        ConsumerId::new(self.inner_command.get_consumer_id())
+    }
+
+    pub fn set_request_id(&mut self, request_id: RequestId) -> &mut Self {
+        self.inner_command.set_request_id(request_id.into());
+        self
+    }
+    pub fn get_request_id(&self) -> RequestId {
+        RequestId::new(self.inner_command.get_request_id())
+    }
+}
+
+impl From<&CloseConsumerCommand> for Command {
+    fn from(c: &CloseConsumerCommand) -> Self {
+        let mut base_command = BaseCommand::new();
+        base_command.set_field_type(Type::CLOSE_CONSUMER);
+        base_command.close_consumer = SingularPtrField::some(c.inner_command.to_owned());
+
+        Command::Simple(SimpleCommand {
+            message: base_command,
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use protobuf::SingularPtrField;
+
+use crate::{
+    command::{Command, SimpleCommand},
+    protos::protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type, CommandCloseProducer},
+    types::{ProducerId, RequestId},
+};
+
+#[derive(Clone, Debug)]
+pub struct CloseProducerCommand {
+    #[cfg(feature = "with-hacking-commands")]
+    pub inner_command: CommandCloseProducer,
+    #[cfg(not(feature = "with-hacking-commands"))]
+    pub(crate) inner_command: CommandCloseProducer,
+}
+impl CloseProducerCommand {
+    pub fn new(producer_id: ProducerId) -> Self {
+        let mut inner_command = CommandCloseProducer::new();
+        inner_command.set_producer_id(producer_id.into());
+
+        Self { inner_command }
+    }
+
+    pub fn get_producer_id(&self) -> ProducerId {
+        ProducerId::new(self.inner_command.get_producer_id())
+    }
+
+    pub fn set_request_id(&mut self, request_id: RequestId) -> &mut Self {
+        self.inner_command.set_request_id(request_id.into());
+        self
+    }
+    pub fn get_request_id(&self) -> RequestId {
+        RequestId::new(self.inner_command.get_request_id())
+    }
+}
+
+impl From<&CloseProducerCommand> for Command {
+    fn from(c: &CloseProducerCommand) -> Self {
+        let mut base_command = BaseCommand::new();
+        base_command.set_field_type(Type::CLOSE_PRODUCER);
+        base_command.close_producer = SingularPtrField::some(c.inner_command.to_owned());
+
+        Command::Simple(SimpleCommand {
+            message: base_command,
+        })
+    }
+}
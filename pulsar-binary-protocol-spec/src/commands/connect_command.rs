@@ -1,6 +1,7 @@
 use protobuf::{ProtobufEnum as _, SingularPtrField};
 
 use crate::{
+    broker_service_url::Url,
     command::{Command, SimpleCommand},
     protos::protobuf::pulsar_api::{
         BaseCommand, BaseCommand_Type as Type, CommandConnect,
@@ -36,8 +37,39 @@ impl ConnectCommand {
         self
     }
 
+    // The proxy forwards the connection to the broker, which is the logical address of the lookup.
+    pub fn set_proxy_to_broker_url(&mut self, broker_url: &Url) -> &mut Self {
+        self.inner_command
+            .set_proxy_to_broker_url(format!("{}:{}", broker_url.host, broker_url.port));
+        self
+    }
+    pub fn get_proxy_to_broker_url(&self) -> Option<&str> {
+        if self.inner_command.has_proxy_to_broker_url() {
+            Some(self.inner_command.get_proxy_to_broker_url())
+        } else {
+            None
+        }
+    }
+
+    // Set by a proxy, for the client that it connects on behalf of.
+    pub fn set_original_principal(&mut self, principal: &str) -> &mut Self {
+        self.inner_command.set_original_principal(principal.into());
+        self
+    }
+
+    pub fn set_original_auth(&mut self, method_name: &str, data: &str) -> &mut Self {
+        self.inner_command
+            .set_original_auth_method(method_name.into());
+        self.inner_command.set_original_auth_data(data.into());
+        self
+    }
+
     pub fn hide_auth_data(&mut self, value: &[u8]) {
         self.inner_command.set_auth_data(value.into());
+        if self.inner_command.has_original_auth_data() {
+            self.inner_command
+                .set_original_auth_data(String::from_utf8_lossy(value).into_owned());
+        }
     }
 }
 
@@ -52,3 +84,25 @@ impl From<&ConnectCommand> for Command {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::error;
+
+    #[test]
+    fn proxy_to_broker_url() -> Result<(), Box<dyn error::Error>> {
+        let mut c = ConnectCommand::new("test");
+        assert_eq!(c.get_proxy_to_broker_url(), None);
+
+        c.set_proxy_to_broker_url(&"pulsar://broker.example.com:6650/".parse()?)
+            .set_original_auth("token", "foo");
+        assert_eq!(c.get_proxy_to_broker_url(), Some("broker.example.com:6650"));
+
+        c.hide_auth_data(b"******");
+        assert_eq!(c.inner_command.get_original_auth_data(), "******");
+
+        Ok(())
+    }
+}
@@ -25,6 +25,10 @@ impl FlowCommand {
         self.inner_command.set_consumer_id(consumer_id.into());
         self
     }
+
+    pub fn get_message_permits(&self) -> u32 {
+        self.inner_command.get_messagePermits()
+    }
 }
 
 impl From<&FlowCommand> for Command {
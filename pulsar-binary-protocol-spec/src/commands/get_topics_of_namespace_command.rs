@@ -0,0 +1,84 @@
+use protobuf::SingularPtrField;
+
+use crate::{
+    command::{Command, SimpleCommand},
+    protos::protobuf::pulsar_api::{
+        BaseCommand, BaseCommand_Type as Type, CommandGetTopicsOfNamespace,
+    },
+    types::{RequestId, TopicsOfNamespaceMode},
+};
+
+#[derive(Clone, Debug)]
+pub struct GetTopicsOfNamespaceCommand {
+    #[cfg(feature = "with-hacking-commands")]
+    pub inner_command: CommandGetTopicsOfNamespace,
+    #[cfg(not(feature = "with-hacking-commands"))]
+    pub(crate) inner_command: CommandGetTopicsOfNamespace,
+}
+impl GetTopicsOfNamespaceCommand {
+    // The namespace is tenant/namespace.
+    pub fn new(namespace: &str, mode: TopicsOfNamespaceMode) -> Self {
+        let mut inner_command = CommandGetTopicsOfNamespace::new();
+        inner_command.set_namespace(namespace.into());
+        inner_command.set_mode(mode.into());
+
+        Self { inner_command }
+    }
+
+    pub fn get_namespace(&self) -> &str {
+        self.inner_command.get_namespace()
+    }
+
+    pub fn set_request_id(&mut self, request_id: RequestId) -> &mut Self {
+        self.inner_command.set_request_id(request_id.into());
+        self
+    }
+    pub fn get_request_id(&self) -> RequestId {
+        RequestId::new(self.inner_command.get_request_id())
+    }
+}
+
+impl From<&GetTopicsOfNamespaceCommand> for Command {
+    fn from(c: &GetTopicsOfNamespaceCommand) -> Self {
+        let mut base_command = BaseCommand::new();
+        base_command.set_field_type(Type::GET_TOPICS_OF_NAMESPACE);
+        base_command.getTopicsOfNamespace = SingularPtrField::some(c.inner_command.to_owned());
+
+        Command::Simple(SimpleCommand {
+            message: base_command,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        command::CommandWithParsed,
+        frame::{FrameParseOutput, FrameParser, FrameRenderer},
+        protos::protobuf::pulsar_api::CommandGetTopicsOfNamespace_Mode as Protobuf_Mode,
+    };
+
+    #[test]
+    fn render_and_parse() {
+        let mut c = GetTopicsOfNamespaceCommand::new("public/default", TopicsOfNamespaceMode::All);
+        c.set_request_id(RequestId::new(3));
+
+        let mut buf = Vec::new();
+        FrameRenderer::new().render(&c, &mut buf).unwrap();
+
+        let message = match FrameParser::new().parse(&buf[..]).unwrap() {
+            FrameParseOutput::Completed(n, CommandWithParsed::Simple(c)) => {
+                assert_eq!(n, buf.len());
+                c.message
+            }
+            _ => panic!(),
+        };
+        assert_eq!(message.get_field_type(), Type::GET_TOPICS_OF_NAMESPACE);
+        let inner_command = message.get_getTopicsOfNamespace();
+        assert_eq!(inner_command.get_namespace(), "public/default");
+        assert_eq!(inner_command.get_mode(), Protobuf_Mode::ALL);
+        assert_eq!(inner_command.get_request_id(), 3);
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{protos::protobuf::pulsar_api::CommandGetTopicsOfNamespaceResponse, types::RequestId};
+
+#[derive(Clone, Debug)]
+pub struct GetTopicsOfNamespaceResponseCommand {
+    #[cfg(feature = "with-hacking-commands")]
+    pub inner_command: CommandGetTopicsOfNamespaceResponse,
+    #[cfg(not(feature = "with-hacking-commands"))]
+    pub(crate) inner_command: CommandGetTopicsOfNamespaceResponse,
+}
+impl GetTopicsOfNamespaceResponseCommand {
+    pub fn get_request_id(&self) -> RequestId {
+        RequestId::new(self.inner_command.get_request_id())
+    }
+
+    // The complete names, with the partitions of the partitioned topics.
+    pub fn get_topics(&self) -> &[String] {
+        self.inner_command.get_topics()
+    }
+}
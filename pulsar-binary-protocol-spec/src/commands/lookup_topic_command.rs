@@ -0,0 +1,53 @@
+use protobuf::SingularPtrField;
+
+use crate::{
+    command::{Command, SimpleCommand},
+    protos::protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type, CommandLookupTopic},
+    types::RequestId,
+};
+
+#[derive(Clone, Debug)]
+pub struct LookupTopicCommand {
+    #[cfg(feature = "with-hacking-commands")]
+    pub inner_command: CommandLookupTopic,
+    #[cfg(not(feature = "with-hacking-commands"))]
+    pub(crate) inner_command: CommandLookupTopic,
+}
+impl LookupTopicCommand {
+    pub fn new(topic: impl AsRef<str>) -> Self {
+        let mut inner_command = CommandLookupTopic::new();
+        inner_command.set_topic(topic.as_ref().into());
+
+        Self { inner_command }
+    }
+
+    pub fn get_topic(&self) -> &str {
+        self.inner_command.get_topic()
+    }
+
+    pub fn set_request_id(&mut self, request_id: RequestId) -> &mut Self {
+        self.inner_command.set_request_id(request_id.into());
+        self
+    }
+    pub fn get_request_id(&self) -> RequestId {
+        RequestId::new(self.inner_command.get_request_id())
+    }
+
+    // Set when following a redirect of an authoritative broker.
+    pub fn set_authoritative(&mut self, authoritative: bool) -> &mut Self {
+        self.inner_command.set_authoritative(authoritative);
+        self
+    }
+}
+
+impl From<&LookupTopicCommand> for Command {
+    fn from(c: &LookupTopicCommand) -> Self {
+        let mut base_command = BaseCommand::new();
+        base_command.set_field_type(Type::LOOKUP);
+        base_command.lookupTopic = SingularPtrField::some(c.inner_command.to_owned());
+
+        Command::Simple(SimpleCommand {
+            message: base_command,
+        })
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{
+    protos::protobuf::pulsar_api::{
+        CommandLookupTopicResponse, CommandLookupTopicResponse_LookupType as LookupType,
+    },
+    types::{RequestId, ServerError},
+};
+
+#[derive(Clone, Debug)]
+pub struct LookupTopicResponseCommand {
+    #[cfg(feature = "with-hacking-commands")]
+    pub inner_command: CommandLookupTopicResponse,
+    #[cfg(not(feature = "with-hacking-commands"))]
+    pub(crate) inner_command: CommandLookupTopicResponse,
+}
+impl LookupTopicResponseCommand {
+    pub fn get_request_id(&self) -> RequestId {
+        RequestId::new(self.inner_command.get_request_id())
+    }
+
+    pub fn get_broker_service_url(&self) -> Option<&str> {
+        if self.inner_command.has_brokerServiceUrl() {
+            Some(self.inner_command.get_brokerServiceUrl())
+        } else {
+            None
+        }
+    }
+
+    pub fn get_broker_service_url_tls(&self) -> Option<&str> {
+        if self.inner_command.has_brokerServiceUrlTls() {
+            Some(self.inner_command.get_brokerServiceUrlTls())
+        } else {
+            None
+        }
+    }
+
+    // The lookup should be sent again to the broker service url.
+    pub fn is_redirect(&self) -> bool {
+        self.inner_command.get_response() == LookupType::Redirect
+    }
+
+    pub fn is_authoritative(&self) -> bool {
+        self.inner_command.get_authoritative()
+    }
+
+    // The broker should be connected to through the service url, which is a proxy.
+    pub fn is_proxy_through_service_url(&self) -> bool {
+        self.inner_command.get_proxy_through_service_url()
+    }
+
+    pub(crate) fn is_failed(&self) -> bool {
+        self.inner_command.get_response() == LookupType::Failed
+    }
+
+    pub(crate) fn get_error(&self) -> ServerError {
+        self.inner_command.get_error().into()
+    }
+
+    pub(crate) fn get_message(&self) -> &str {
+        self.inner_command.get_message()
+    }
+}
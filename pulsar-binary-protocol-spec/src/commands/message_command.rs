@@ -1,7 +1,12 @@
 use crate::{
     command::{PayloadCommandPayloadErrorWithParsed, PayloadCommandPayloadWithParsed},
-    protos::protobuf::pulsar_api::{CommandMessage, MessageMetadata as Protobuf_MessageMetadata},
-    types::{ConsumerId, MessageIdData, MessageMetadata, SingleMessageMetadata},
+    protos::protobuf::pulsar_api::{
+        CommandMessage, KeyValue, MessageMetadata as Protobuf_MessageMetadata,
+    },
+    types::{
+        message::millis_to_datetime, AckValidationError, ConsumerId, Message, MessageIdData,
+        MessageMetadata, SingleMessageMetadata,
+    },
 };
 
 #[derive(Clone, Debug)]
@@ -43,6 +48,74 @@ impl MessageCommand {
     pub fn get_is_checksum_mismatch(&self) -> Option<bool> {
         self.is_checksum_match.map(|x| !x)
     }
+
+    // Flattens a batch into its entries, the error is the one to ack a corrupted message with.
+    pub fn to_messages(&self, topic: &str) -> Result<Vec<Message>, AckValidationError> {
+        if self.get_is_checksum_mismatch() == Some(true) {
+            return Err(AckValidationError::ChecksumMismatch);
+        }
+
+        let metadata = &self.message_metadata;
+        let message_id = self.inner_command.get_message_id();
+        let publish_time = millis_to_datetime(metadata.get_publish_time());
+        let redelivery_count = self.inner_command.get_redelivery_count();
+
+        let event_time = |event_time: u64| match event_time {
+            0 => None,
+            event_time => millis_to_datetime(event_time),
+        };
+        let properties = |kvs: &[KeyValue]| {
+            kvs.iter()
+                .map(|kv| (kv.get_key().to_owned(), kv.get_value().to_owned()))
+                .collect()
+        };
+
+        match &self.payload {
+            PayloadCommandPayloadWithParsed::Single(Ok(bytes)) => Ok(vec![Message {
+                topic: topic.to_owned(),
+                consumer_id: self.get_consumer_id(),
+                message_id: message_id.into(),
+                payload: bytes.to_owned(),
+                key: if metadata.has_partition_key() {
+                    Some(metadata.get_partition_key().to_owned())
+                } else {
+                    None
+                },
+                properties: properties(&metadata.properties),
+                event_time: event_time(metadata.get_event_time()),
+                publish_time,
+                redelivery_count,
+            }]),
+            PayloadCommandPayloadWithParsed::Batch(Ok(msgs)) => Ok(msgs
+                .iter()
+                .enumerate()
+                .map(|(i, (single_message_metadata, bytes))| {
+                    let mut message_id = message_id.to_owned();
+                    message_id.set_batch_index(i as i32);
+                    message_id.set_batch_size(msgs.len() as i32);
+
+                    Message {
+                        topic: topic.to_owned(),
+                        consumer_id: self.get_consumer_id(),
+                        message_id: (&message_id).into(),
+                        payload: bytes.to_owned(),
+                        key: if single_message_metadata.has_partition_key() {
+                            Some(single_message_metadata.get_partition_key().to_owned())
+                        } else {
+                            None
+                        },
+                        properties: properties(&single_message_metadata.properties),
+                        event_time: event_time(single_message_metadata.get_event_time())
+                            .or_else(|| event_time(metadata.get_event_time())),
+                        publish_time,
+                        redelivery_count,
+                    }
+                })
+                .collect()),
+            PayloadCommandPayloadWithParsed::Single(Err(err))
+            | PayloadCommandPayloadWithParsed::Batch(Err(err)) => Err(err.into()),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -68,3 +141,123 @@ impl<'a> From<&'a PayloadCommandPayloadWithParsed> for MessageCommandPayload<'a>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::protos::protobuf::pulsar_api::SingleMessageMetadata as Protobuf_SingleMessageMetadata;
+
+    fn message_command(payload: PayloadCommandPayloadWithParsed) -> MessageCommand {
+        let mut inner_command = CommandMessage::new();
+        inner_command.set_consumer_id(1);
+        inner_command.mut_message_id().set_ledgerId(2);
+        inner_command.mut_message_id().set_entryId(3);
+        inner_command.set_redelivery_count(4);
+
+        let mut message_metadata = Protobuf_MessageMetadata::new();
+        message_metadata.set_producer_name("p".into());
+        message_metadata.set_sequence_id(1);
+        message_metadata.set_publish_time(1_600_000_000_123);
+        message_metadata.set_partition_key("k".into());
+
+        let mut kv = KeyValue::new();
+        kv.set_key("a".into());
+        kv.set_value("1".into());
+        message_metadata.properties.push(kv);
+
+        MessageCommand {
+            inner_command,
+            message_metadata,
+            payload,
+            is_checksum_match: Some(true),
+        }
+    }
+
+    #[test]
+    fn to_messages_with_single() {
+        let c = message_command(PayloadCommandPayloadWithParsed::Single(Ok(b"foo".to_vec())));
+
+        let messages = c.to_messages("t").unwrap();
+        assert_eq!(messages.len(), 1);
+
+        let message = &messages[0];
+        assert_eq!(message.get_topic(), "t");
+        assert_eq!(message.get_consumer_id(), ConsumerId::new(1));
+        assert_eq!(message.get_payload(), b"foo");
+        assert_eq!(message.get_key(), Some("k"));
+        assert_eq!(
+            message.get_properties().get("a").map(String::as_str),
+            Some("1")
+        );
+        assert_eq!(message.get_event_time(), None);
+        assert_eq!(
+            message.get_publish_time().map(|dt| dt.timestamp_millis()),
+            Some(1_600_000_000_123)
+        );
+        assert_eq!(message.get_redelivery_count(), 4);
+        assert_eq!(message.get_message_id().get_entry_id(), 3);
+        assert!(!message.get_message_id().inner.has_batch_index());
+    }
+
+    #[test]
+    fn to_messages_with_batch() {
+        let mut smm_0 = Protobuf_SingleMessageMetadata::new();
+        smm_0.set_payload_size(3);
+        smm_0.set_event_time(1_600_000_000_456);
+        let mut smm_1 = Protobuf_SingleMessageMetadata::new();
+        smm_1.set_payload_size(3);
+        smm_1.set_partition_key("k1".into());
+
+        let c = message_command(PayloadCommandPayloadWithParsed::Batch(Ok(vec![
+            (smm_0, b"foo".to_vec()),
+            (smm_1, b"bar".to_vec()),
+        ])));
+
+        let messages = c.to_messages("t").unwrap();
+        assert_eq!(messages.len(), 2);
+
+        assert_eq!(messages[0].get_payload(), b"foo");
+        assert_eq!(messages[0].get_key(), None);
+        assert!(messages[0].get_properties().is_empty());
+        assert_eq!(
+            messages[0].get_event_time().map(|dt| dt.timestamp_millis()),
+            Some(1_600_000_000_456)
+        );
+        assert_eq!(messages[0].get_message_id().inner.get_batch_index(), 0);
+
+        assert_eq!(messages[1].get_payload(), b"bar");
+        assert_eq!(messages[1].get_key(), Some("k1"));
+        assert_eq!(messages[1].get_message_id().inner.get_batch_index(), 1);
+        assert_eq!(messages[1].get_message_id().inner.get_batch_size(), 2);
+    }
+
+    #[test]
+    fn to_messages_with_out_of_range_times() {
+        let mut c = message_command(PayloadCommandPayloadWithParsed::Single(Ok(b"foo".to_vec())));
+        c.message_metadata.set_publish_time(u64::MAX);
+        c.message_metadata.set_event_time(i64::MAX as u64);
+
+        let messages = c.to_messages("t").unwrap();
+        assert_eq!(messages[0].get_publish_time(), None);
+        assert_eq!(messages[0].get_event_time(), None);
+    }
+
+    #[test]
+    fn to_messages_with_corruption() {
+        let mut c = message_command(PayloadCommandPayloadWithParsed::Single(Ok(b"foo".to_vec())));
+        c.is_checksum_match = Some(false);
+        assert_eq!(
+            c.to_messages("t").unwrap_err(),
+            AckValidationError::ChecksumMismatch
+        );
+
+        let c = message_command(PayloadCommandPayloadWithParsed::Batch(Err(
+            PayloadCommandPayloadErrorWithParsed::BatchDeSerializeError,
+        )));
+        assert_eq!(
+            c.to_messages("t").unwrap_err(),
+            AckValidationError::BatchDeSerializeError
+        );
+    }
+}
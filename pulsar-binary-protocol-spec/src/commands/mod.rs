@@ -1,10 +1,18 @@
 pub mod ack_command;
 pub mod ack_response_command;
+pub mod close_consumer_command;
+pub mod close_producer_command;
 pub mod connect_command;
 pub mod connected_command;
 pub mod error_command;
 pub mod flow_command;
+pub mod get_topics_of_namespace_command;
+pub mod get_topics_of_namespace_response_command;
+pub mod lookup_topic_command;
+pub mod lookup_topic_response_command;
 pub mod message_command;
+pub mod partitioned_topic_metadata_command;
+pub mod partitioned_topic_metadata_response_command;
 pub mod ping_command;
 pub mod pong_command;
 pub mod producer_command;
@@ -18,11 +26,19 @@ pub mod success_command;
 
 pub use ack_command::AckCommand;
 pub use ack_response_command::AckResponseCommand;
+pub use close_consumer_command::CloseConsumerCommand;
+pub use close_producer_command::CloseProducerCommand;
 pub use connect_command::ConnectCommand;
 pub use connected_command::ConnectedCommand;
 pub use error_command::ErrorCommand;
 pub use flow_command::FlowCommand;
+pub use get_topics_of_namespace_command::GetTopicsOfNamespaceCommand;
+pub use get_topics_of_namespace_response_command::GetTopicsOfNamespaceResponseCommand;
+pub use lookup_topic_command::LookupTopicCommand;
+pub use lookup_topic_response_command::LookupTopicResponseCommand;
 pub use message_command::{MessageCommand, MessageCommandPayload};
+pub use partitioned_topic_metadata_command::PartitionedTopicMetadataCommand;
+pub use partitioned_topic_metadata_response_command::PartitionedTopicMetadataResponseCommand;
 pub use ping_command::PingCommand;
 pub use pong_command::PongCommand;
 pub use producer_command::ProducerCommand;
@@ -0,0 +1,49 @@
+use protobuf::SingularPtrField;
+
+use crate::{
+    command::{Command, SimpleCommand},
+    protos::protobuf::pulsar_api::{
+        BaseCommand, BaseCommand_Type as Type, CommandPartitionedTopicMetadata,
+    },
+    types::RequestId,
+};
+
+#[derive(Clone, Debug)]
+pub struct PartitionedTopicMetadataCommand {
+    #[cfg(feature = "with-hacking-commands")]
+    pub inner_command: CommandPartitionedTopicMetadata,
+    #[cfg(not(feature = "with-hacking-commands"))]
+    pub(crate) inner_command: CommandPartitionedTopicMetadata,
+}
+impl PartitionedTopicMetadataCommand {
+    pub fn new(topic: impl AsRef<str>) -> Self {
+        let mut inner_command = CommandPartitionedTopicMetadata::new();
+        inner_command.set_topic(topic.as_ref().into());
+
+        Self { inner_command }
+    }
+
+    pub fn get_topic(&self) -> &str {
+        self.inner_command.get_topic()
+    }
+
+    pub fn set_request_id(&mut self, request_id: RequestId) -> &mut Self {
+        self.inner_command.set_request_id(request_id.into());
+        self
+    }
+    pub fn get_request_id(&self) -> RequestId {
+        RequestId::new(self.inner_command.get_request_id())
+    }
+}
+
+impl From<&PartitionedTopicMetadataCommand> for Command {
+    fn from(c: &PartitionedTopicMetadataCommand) -> Self {
+        let mut base_command = BaseCommand::new();
+        base_command.set_field_type(Type::PARTITIONED_METADATA);
+        base_command.partitionMetadata = SingularPtrField::some(c.inner_command.to_owned());
+
+        Command::Simple(SimpleCommand {
+            message: base_command,
+        })
+    }
+}
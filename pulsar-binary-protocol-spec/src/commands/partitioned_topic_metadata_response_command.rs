@@ -0,0 +1,37 @@
+use crate::{
+    protos::protobuf::pulsar_api::{
+        CommandPartitionedTopicMetadataResponse,
+        CommandPartitionedTopicMetadataResponse_LookupType as LookupType,
+    },
+    types::{RequestId, ServerError},
+};
+
+#[derive(Clone, Debug)]
+pub struct PartitionedTopicMetadataResponseCommand {
+    #[cfg(feature = "with-hacking-commands")]
+    pub inner_command: CommandPartitionedTopicMetadataResponse,
+    #[cfg(not(feature = "with-hacking-commands"))]
+    pub(crate) inner_command: CommandPartitionedTopicMetadataResponse,
+}
+impl PartitionedTopicMetadataResponseCommand {
+    pub fn get_request_id(&self) -> RequestId {
+        RequestId::new(self.inner_command.get_request_id())
+    }
+
+    // 0 for a non-partitioned topic.
+    pub fn get_partitions(&self) -> u32 {
+        self.inner_command.get_partitions()
+    }
+
+    pub(crate) fn is_failed(&self) -> bool {
+        self.inner_command.get_response() == LookupType::Failed
+    }
+
+    pub(crate) fn get_error(&self) -> ServerError {
+        self.inner_command.get_error().into()
+    }
+
+    pub(crate) fn get_message(&self) -> &str {
+        self.inner_command.get_message()
+    }
+}
@@ -2,11 +2,8 @@ use protobuf::SingularPtrField;
 
 use crate::{
     command::{Command, SimpleCommand},
-    protos::{
-        protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type, CommandProducer},
-        utils::convert_tuple_slice_to_key_value_vector,
-    },
-    types::{ProducerId, RequestId},
+    protos::protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type, CommandProducer},
+    types::{MessageProperties, ProducerId, RequestId},
 };
 
 #[derive(Clone, Debug)]
@@ -15,13 +12,18 @@ pub struct ProducerCommand {
     pub inner_command: CommandProducer,
     #[cfg(not(feature = "with-hacking-commands"))]
     pub(crate) inner_command: CommandProducer,
+
+    initial_sequence_id: Option<u64>,
 }
 impl ProducerCommand {
-    pub fn new(topic: &str) -> Self {
+    pub fn new(topic: impl AsRef<str>) -> Self {
         let mut inner_command = CommandProducer::new();
-        inner_command.set_topic(topic.into());
+        inner_command.set_topic(topic.as_ref().into());
 
-        Self { inner_command }
+        Self {
+            inner_command,
+            initial_sequence_id: None,
+        }
     }
 
     pub fn set_producer_id(&mut self, producer_id: ProducerId) -> &mut Self {
@@ -44,8 +46,28 @@ impl ProducerCommand {
         self
     }
 
-    pub fn append_metadata(&mut self, metadata: &[(&str, &str)]) -> &mut Self {
-        for kv in convert_tuple_slice_to_key_value_vector(metadata) {
+    // Increased on every reconnection of the producer, so that the broker can tell
+    // the stale producer apart.
+    pub fn set_epoch(&mut self, epoch: u64) -> &mut Self {
+        self.inner_command.set_epoch(epoch);
+        self
+    }
+    pub fn get_epoch(&self) -> u64 {
+        self.inner_command.get_epoch()
+    }
+
+    // Not sent to the broker, the first message has the sequence id initial_sequence_id + 1,
+    // unless the broker has persisted a larger one.
+    pub fn set_initial_sequence_id(&mut self, initial_sequence_id: u64) -> &mut Self {
+        self.initial_sequence_id = Some(initial_sequence_id);
+        self
+    }
+    pub fn get_initial_sequence_id(&self) -> Option<u64> {
+        self.initial_sequence_id
+    }
+
+    pub fn append_metadata(&mut self, metadata: impl Into<MessageProperties>) -> &mut Self {
+        for kv in metadata.into().inner {
             self.inner_command.metadata.push(kv);
         }
 
@@ -1,6 +1,6 @@
 use crate::{
     protos::protobuf::pulsar_api::CommandProducerSuccess,
-    types::{ProducerName, RequestId},
+    types::{ProducerName, RequestId, SequenceId},
 };
 
 #[derive(Clone, Debug)]
@@ -18,4 +18,14 @@ impl ProducerSuccessCommand {
     pub fn get_producer_name(&self) -> ProducerName {
         ProducerName::new(self.inner_command.get_producer_name())
     }
+
+    // The last sequence id persisted by the broker for the producer name, with deduplication.
+    pub fn get_last_sequence_id(&self) -> Option<SequenceId> {
+        let last_sequence_id = self.inner_command.get_last_sequence_id();
+        if last_sequence_id < 0 {
+            None
+        } else {
+            Some(SequenceId::new(last_sequence_id as u64))
+        }
+    }
 }
@@ -115,6 +115,30 @@ impl SendCommand {
         SequenceId::new(self.inner_command.get_sequence_id())
     }
 
+    // The sequence id of the last message of a batch, the first one is the sequence id.
+    pub fn set_highest_sequence_id(&mut self, highest_sequence_id: SequenceId) -> &mut Self {
+        self.inner_command
+            .set_highest_sequence_id(highest_sequence_id.to_owned().into());
+        self.message_metadata
+            .set_highest_sequence_id(highest_sequence_id.into());
+        self
+    }
+
+    pub fn get_num_messages(&self) -> usize {
+        match &self.payload {
+            PayloadCommandPayload::Single(_) => 1,
+            PayloadCommandPayload::Batch(msgs) => msgs.len(),
+        }
+    }
+
+    // The size of the uncompressed messages.
+    pub fn get_payload_size(&self) -> usize {
+        match &self.payload {
+            PayloadCommandPayload::Single(msg) => msg.len(),
+            PayloadCommandPayload::Batch(msgs) => msgs.iter().map(|(_, msg)| msg.len()).sum(),
+        }
+    }
+
     pub fn set_deliver_at_time(&mut self, dt: DateTime<Utc>) -> &mut Self {
         self.message_metadata
             .set_deliver_at_time(dt.timestamp_millis() as i64);
@@ -127,6 +151,55 @@ impl SendCommand {
             .set_deliver_at_time((Utc::now() + dur).timestamp_millis() as i64);
         Ok(self)
     }
+
+    // Routes the message to a partition, and to a consumer of a KeyShared subscription.
+    pub fn set_partition_key(&mut self, partition_key: &str) -> &mut Self {
+        self.message_metadata
+            .set_partition_key(partition_key.to_owned());
+        self.message_metadata.set_partition_key_b64_encoded(false);
+        self
+    }
+    pub fn set_partition_key_bytes(&mut self, partition_key: &[u8]) -> &mut Self {
+        self.message_metadata
+            .set_partition_key(base64::encode(partition_key));
+        self.message_metadata.set_partition_key_b64_encoded(true);
+        self
+    }
+
+    // Used instead of the partition key by KeyShared subscriptions.
+    pub fn set_ordering_key(&mut self, ordering_key: &[u8]) -> &mut Self {
+        self.message_metadata
+            .set_ordering_key(ordering_key.to_owned());
+        self
+    }
+
+    pub fn set_event_time(&mut self, dt: DateTime<Utc>) -> &mut Self {
+        self.message_metadata
+            .set_event_time(dt.timestamp_millis() as u64);
+        self
+    }
+
+    // The clusters the message is replicated to, instead of all the clusters of the namespace.
+    pub fn set_replicate_to(&mut self, clusters: &[&str]) -> &mut Self {
+        self.message_metadata
+            .set_replicate_to(clusters.iter().map(|cluster| cluster.to_string()).collect());
+        self
+    }
+    pub fn disable_replication(&mut self) -> &mut Self {
+        self.set_replicate_to(&["__local__"])
+    }
+
+    pub fn set_schema_version(&mut self, schema_version: &[u8]) -> &mut Self {
+        self.message_metadata
+            .set_schema_version(schema_version.to_owned());
+        self
+    }
+
+    // A tombstone, removes the key from a compacted topic, the payload should be empty.
+    pub fn set_null_value(&mut self, null_value: bool) -> &mut Self {
+        self.message_metadata.set_null_value(null_value);
+        self
+    }
 }
 
 impl From<&SendCommand> for Command {
@@ -180,4 +253,45 @@ mod tests {
             _ => assert!(false),
         }
     }
+
+    #[test]
+    fn num_messages_and_payload_size() {
+        let c = SendCommand::single(SequenceIdBuilder::default().next(), None, b"foo", None);
+        assert_eq!(c.get_num_messages(), 1);
+        assert_eq!(c.get_payload_size(), 3);
+
+        let c = SendCommand::batch(
+            SequenceIdBuilder::default().next(),
+            vec![
+                (MessageProperties::from(&[("a", "1")]), &b"foo"[..]),
+                (MessageProperties::from(&[("b", "2")]), &b"ba"[..]),
+            ],
+            None,
+        );
+        assert_eq!(c.get_num_messages(), 2);
+        assert_eq!(c.get_payload_size(), 5);
+    }
+
+    #[test]
+    fn highest_sequence_id() {
+        let mut sequence_id_builder = SequenceIdBuilder::default();
+        let sequence_id = sequence_id_builder.next();
+        sequence_id_builder.next();
+        let highest_sequence_id = sequence_id_builder.next();
+
+        let mut c = SendCommand::batch(
+            sequence_id,
+            vec![
+                (MessageProperties::from(&[("a", "1")]), &b"foo"[..]),
+                (MessageProperties::from(&[("b", "2")]), &b"bar"[..]),
+                (MessageProperties::from(&[("c", "3")]), &b"baz"[..]),
+            ],
+            None,
+        );
+        c.set_highest_sequence_id(highest_sequence_id);
+
+        assert_eq!(c.inner_command.get_sequence_id(), 1);
+        assert_eq!(c.inner_command.get_highest_sequence_id(), 3);
+        assert_eq!(c.message_metadata.get_highest_sequence_id(), 3);
+    }
 }
@@ -2,11 +2,8 @@ use protobuf::SingularPtrField;
 
 use crate::{
     command::{Command, SimpleCommand},
-    protos::{
-        protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type, CommandSubscribe},
-        utils::convert_tuple_slice_to_key_value_vector,
-    },
-    types::{ConsumerId, RequestId, SubscribeType},
+    protos::protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type, CommandSubscribe},
+    types::{ConsumerId, MessageProperties, RequestId, SubscribeType},
 };
 
 #[derive(Clone, Debug)]
@@ -15,15 +12,28 @@ pub struct SubscribeCommand {
     pub inner_command: CommandSubscribe,
     #[cfg(not(feature = "with-hacking-commands"))]
     pub(crate) inner_command: CommandSubscribe,
+
+    receiver_queue_size: Option<u32>,
 }
 impl SubscribeCommand {
-    pub fn new(topic: &str, subscription: &str, subscribe_type: SubscribeType) -> Self {
+    pub fn new(topic: impl AsRef<str>, subscription: &str, subscribe_type: SubscribeType) -> Self {
         let mut inner_command = CommandSubscribe::new();
-        inner_command.set_topic(topic.into());
+        inner_command.set_topic(topic.as_ref().into());
         inner_command.set_subscription(subscription.into());
         inner_command.set_subType(subscribe_type.into());
 
-        Self { inner_command }
+        Self {
+            inner_command,
+            receiver_queue_size: None,
+        }
+    }
+
+    pub fn set_topic(&mut self, topic: impl AsRef<str>) -> &mut Self {
+        self.inner_command.set_topic(topic.as_ref().into());
+        self
+    }
+    pub fn get_topic(&self) -> &str {
+        self.inner_command.get_topic()
     }
 
     pub fn set_consumer_id(&mut self, consumer_id: ConsumerId) -> &mut Self {
@@ -46,8 +56,21 @@ impl SubscribeCommand {
         self
     }
 
-    pub fn append_metadata(&mut self, metadata: &[(&str, &str)]) -> &mut Self {
-        for kv in convert_tuple_slice_to_key_value_vector(metadata) {
+    // Not sent to the broker, the handler grants the permits of the queue on subscribing
+    // and again once half of the queue is consumed. Without it, the permits are granted by FLOW.
+    pub fn set_receiver_queue_size(
+        &mut self,
+        receiver_queue_size: impl Into<Option<u32>>,
+    ) -> &mut Self {
+        self.receiver_queue_size = receiver_queue_size.into().filter(|n| *n > 0);
+        self
+    }
+    pub fn get_receiver_queue_size(&self) -> Option<u32> {
+        self.receiver_queue_size
+    }
+
+    pub fn append_metadata(&mut self, metadata: impl Into<MessageProperties>) -> &mut Self {
+        for kv in metadata.into().inner {
             self.inner_command.metadata.push(kv);
         }
 
@@ -0,0 +1,150 @@
+use std::io::Error as IoError;
+
+use bytes::{Buf as _, BytesMut};
+use thiserror::Error;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::command::{Command, CommandWithParsed};
+
+use super::{FrameParseError, FrameParseOutput, FrameParser, FrameRenderError, FrameRenderer};
+
+#[derive(Error, Debug)]
+pub enum PulsarCodecError {
+    #[error("IoError {0:?}")]
+    IoError(#[from] IoError),
+    #[error("FrameParseError {0:?}")]
+    FrameParseError(#[from] FrameParseError),
+    #[error("FrameRenderError {0:?}")]
+    FrameRenderError(#[from] FrameRenderError),
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct PulsarCodec {
+    frame_parser: FrameParser,
+    frame_renderer: FrameRenderer,
+    frame_renderer_buf: Vec<u8>,
+}
+impl PulsarCodec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_parser_and_renderer(
+        frame_parser: FrameParser,
+        frame_renderer: FrameRenderer,
+    ) -> Self {
+        Self {
+            frame_parser,
+            frame_renderer,
+            frame_renderer_buf: Vec::new(),
+        }
+    }
+
+    pub fn get_mut_frame_parser(&mut self) -> &mut FrameParser {
+        &mut self.frame_parser
+    }
+
+    pub fn get_mut_frame_renderer(&mut self) -> &mut FrameRenderer {
+        &mut self.frame_renderer
+    }
+}
+
+impl Decoder for PulsarCodec {
+    type Item = CommandWithParsed;
+    type Error = PulsarCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.frame_parser.parse(&src[..])? {
+            FrameParseOutput::Completed(n, command) => {
+                src.advance(n);
+
+                Ok(Some(command))
+            }
+            FrameParseOutput::Partial(n) => {
+                // The parser keeps the state of the bytes it has consumed.
+                src.advance(n);
+
+                if let Some(total_size) = self.frame_parser.get_total_size() {
+                    src.reserve(total_size as usize);
+                }
+
+                Ok(None)
+            }
+        }
+    }
+}
+
+impl<C> Encoder<C> for PulsarCodec
+where
+    C: Into<Command>,
+{
+    type Error = PulsarCodecError;
+
+    fn encode(&mut self, item: C, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.frame_renderer_buf.clear();
+        self.frame_renderer
+            .render(item, &mut self.frame_renderer_buf)?;
+
+        dst.extend_from_slice(&self.frame_renderer_buf[..]);
+        self.frame_renderer_buf.clear();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::error;
+
+    use crate::{
+        commands::{PingCommand, SendCommand},
+        protos::protobuf::pulsar_api::BaseCommand_Type as Type,
+        types::{MessageProperties, ProducerId, ProducerName, SequenceId},
+    };
+
+    #[test]
+    fn simple() -> Result<(), Box<dyn error::Error>> {
+        let mut codec = PulsarCodec::new();
+
+        let mut send_command = SendCommand::single(
+            SequenceId::new(1),
+            MessageProperties::from(&[("a", "1")]),
+            b"foo",
+            None,
+        );
+        send_command.set_producer_id(ProducerId::new(1));
+        send_command.set_producer_name(ProducerName::new("standalone-0-0"));
+
+        let mut buf = BytesMut::new();
+        codec.encode(&PingCommand::new(), &mut buf)?;
+        codec.encode(&send_command, &mut buf)?;
+
+        // Feed the frames byte by byte.
+        let bytes = buf.split().freeze();
+        let mut commands = vec![];
+        for b in bytes.iter() {
+            buf.extend_from_slice(&[*b]);
+            if let Some(command) = codec.decode(&mut buf)? {
+                commands.push(command);
+            }
+        }
+        assert!(buf.is_empty());
+        assert_eq!(commands.len(), 2);
+
+        match &commands[0] {
+            CommandWithParsed::Simple(c) => assert_eq!(c.message.get_field_type(), Type::PING),
+            CommandWithParsed::Payload(c) => panic!("{:?}", c),
+        }
+        match &commands[1] {
+            CommandWithParsed::Simple(c) => panic!("{:?}", c),
+            CommandWithParsed::Payload(c) => {
+                assert_eq!(c.message.get_field_type(), Type::SEND);
+                assert_eq!(c.is_checksum_match, Some(true));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "with-tokio-codec")]
+pub mod codec;
 pub mod parser;
 pub mod renderer;
 
@@ -10,5 +12,8 @@ pub use parser::{
 };
 pub use renderer::{FrameRenderError, FrameRenderer};
 
+#[cfg(feature = "with-tokio-codec")]
+pub use codec::{PulsarCodec, PulsarCodecError};
+
 #[cfg(test)]
 mod tests;
@@ -1,9 +1,13 @@
 pub use ::protobuf;
+pub use base64;
+pub use url;
 
 #[cfg(feature = "with-asynchronous")]
 pub use async_channel;
 #[cfg(feature = "with-asynchronous")]
 pub use futures_channel;
+#[cfg(feature = "with-tokio-codec")]
+pub use tokio_util;
 
 #[macro_use]
 extern crate paste;
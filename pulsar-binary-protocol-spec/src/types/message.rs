@@ -0,0 +1,70 @@
+use std::{collections::HashMap, convert::TryFrom};
+
+use chrono::{DateTime, TimeZone as _, Utc};
+
+use super::{ConsumerId, MessageIdData};
+
+// A message of a consumer, one per entry of a batch.
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub(crate) topic: String,
+    pub(crate) consumer_id: ConsumerId,
+    pub(crate) message_id: MessageIdData,
+    pub(crate) payload: Vec<u8>,
+    pub(crate) key: Option<String>,
+    pub(crate) properties: HashMap<String, String>,
+    pub(crate) event_time: Option<DateTime<Utc>>,
+    pub(crate) publish_time: Option<DateTime<Utc>>,
+    pub(crate) redelivery_count: u32,
+}
+impl Message {
+    pub fn get_topic(&self) -> &str {
+        &self.topic
+    }
+
+    // Unique per connection only.
+    pub fn get_consumer_id(&self) -> ConsumerId {
+        self.consumer_id.to_owned()
+    }
+
+    // With the batch index for the entries of a batch.
+    pub fn get_message_id(&self) -> &MessageIdData {
+        &self.message_id
+    }
+
+    pub fn get_payload(&self) -> &[u8] {
+        &self.payload
+    }
+    pub fn into_payload(self) -> Vec<u8> {
+        self.payload
+    }
+
+    // The partition key.
+    pub fn get_key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    pub fn get_properties(&self) -> &HashMap<String, String> {
+        &self.properties
+    }
+
+    pub fn get_event_time(&self) -> Option<DateTime<Utc>> {
+        self.event_time
+    }
+
+    // None if the broker sent a time out of the range of DateTime.
+    pub fn get_publish_time(&self) -> Option<DateTime<Utc>> {
+        self.publish_time
+    }
+
+    pub fn get_redelivery_count(&self) -> u32 {
+        self.redelivery_count
+    }
+}
+
+// The times of the metadata are milliseconds since the epoch, 0 if not set.
+// None if out of the range of DateTime.
+pub(crate) fn millis_to_datetime(millis: u64) -> Option<DateTime<Utc>> {
+    let millis = i64::try_from(millis).ok()?;
+    Utc.timestamp_millis_opt(millis).single()
+}
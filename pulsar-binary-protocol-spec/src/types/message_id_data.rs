@@ -1,3 +1,14 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    num::ParseIntError,
+    str::FromStr,
+};
+
+use protobuf::{Message as _, ProtobufError};
+use thiserror::Error;
+
 use crate::protos::protobuf::pulsar_api::MessageIdData as Protobuf_MessageIdData;
 
 #[derive(Debug, Clone)]
@@ -8,6 +19,13 @@ pub struct MessageIdData {
     pub(crate) inner: Protobuf_MessageIdData,
 }
 impl MessageIdData {
+    pub fn new(ledger_id: u64, entry_id: u64) -> Self {
+        let mut inner = Protobuf_MessageIdData::new();
+        inner.set_ledgerId(ledger_id);
+        inner.set_entryId(entry_id);
+        Self { inner }
+    }
+
     pub fn get_ledger_id(&self) -> u64 {
         self.inner.get_ledgerId()
     }
@@ -15,6 +33,74 @@ impl MessageIdData {
     pub fn get_entry_id(&self) -> u64 {
         self.inner.get_entryId()
     }
+
+    pub fn get_partition(&self) -> Option<u32> {
+        Some(self.inner.get_partition())
+            .filter(|x| *x >= 0)
+            .map(|x| x as u32)
+    }
+    pub fn set_partition(&mut self, partition: u32) -> &mut Self {
+        self.inner.set_partition(partition as i32);
+        self
+    }
+
+    pub fn get_batch_index(&self) -> Option<u32> {
+        Some(self.inner.get_batch_index())
+            .filter(|x| *x >= 0)
+            .map(|x| x as u32)
+    }
+    pub fn get_batch_size(&self) -> Option<u32> {
+        Some(self.inner.get_batch_size())
+            .filter(|x| *x > 0)
+            .map(|x| x as u32)
+    }
+    pub fn set_batch_index(&mut self, batch_index: u32, batch_size: u32) -> &mut Self {
+        self.inner.set_batch_index(batch_index as i32);
+        self.inner.set_batch_size(batch_size as i32);
+        self
+    }
+
+    // Same as MessageId.toByteArray of the Java client.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut inner = Protobuf_MessageIdData::new();
+        inner.set_ledgerId(self.get_ledger_id());
+        inner.set_entryId(self.get_entry_id());
+        if let Some(partition) = self.get_partition() {
+            inner.set_partition(partition as i32);
+        }
+        if let Some(batch_index) = self.get_batch_index() {
+            inner.set_batch_index(batch_index as i32);
+        }
+        if let Some(batch_size) = self.get_batch_size() {
+            inner.set_batch_size(batch_size as i32);
+        }
+
+        inner
+            .write_to_bytes()
+            .expect("MessageIdData with ledgerId and entryId")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MessageIdDataParseError> {
+        // Not parse_from_bytes, its missing required fields error panics without descriptors.
+        let mut inner = Protobuf_MessageIdData::new();
+        inner
+            .merge_from_bytes(bytes)
+            .map_err(MessageIdDataParseError::DeserializeError)?;
+        if !inner.is_initialized() {
+            return Err(MessageIdDataParseError::RequiredFieldsMissing);
+        }
+        Ok(Self { inner })
+    }
+
+    fn sort_key(&self) -> (u64, u64, i32, i32) {
+        (
+            self.get_ledger_id(),
+            self.get_entry_id(),
+            self.inner.get_batch_index(),
+            // Only to keep Ord consistent with Eq.
+            self.inner.get_partition(),
+        )
+    }
 }
 
 impl From<&Protobuf_MessageIdData> for MessageIdData {
@@ -24,3 +110,163 @@ impl From<&Protobuf_MessageIdData> for MessageIdData {
         }
     }
 }
+
+impl PartialEq for MessageIdData {
+    fn eq(&self, other: &Self) -> bool {
+        self.sort_key() == other.sort_key()
+    }
+}
+impl Eq for MessageIdData {}
+
+impl PartialOrd for MessageIdData {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for MessageIdData {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl Hash for MessageIdData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sort_key().hash(state);
+    }
+}
+
+// Same as MessageId.toString of the Java client, ledgerId:entryId:partition[:batchIndex]
+impl fmt::Display for MessageIdData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.get_ledger_id(),
+            self.get_entry_id(),
+            self.inner.get_partition()
+        )?;
+        if let Some(batch_index) = self.get_batch_index() {
+            write!(f, ":{}", batch_index)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MessageIdData {
+    type Err = MessageIdDataParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split(':').collect::<Vec<_>>();
+        if parts.len() != 3 && parts.len() != 4 {
+            return Err(MessageIdDataParseError::FormatInvalid);
+        }
+
+        let mut message_id = Self::new(parts[0].parse()?, parts[1].parse()?);
+        let partition: i32 = parts[2].parse()?;
+        if partition >= 0 {
+            message_id.set_partition(partition as u32);
+        }
+        if let Some(batch_index) = parts.get(3) {
+            message_id.inner.set_batch_index(batch_index.parse()?);
+        }
+
+        Ok(message_id)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum MessageIdDataParseError {
+    #[error("DeserializeError {0:?}")]
+    DeserializeError(ProtobufError),
+    #[error("RequiredFieldsMissing")]
+    RequiredFieldsMissing,
+    #[error("FormatInvalid")]
+    FormatInvalid,
+    #[error("ParseIntError {0:?}")]
+    ParseIntError(#[from] ParseIntError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordering() {
+        let mut batched_0 = MessageIdData::new(1, 2);
+        batched_0.set_batch_index(0, 2);
+        let mut batched_1 = MessageIdData::new(1, 2);
+        batched_1.set_batch_index(1, 2);
+
+        let mut ids = vec![
+            MessageIdData::new(2, 0),
+            batched_1.clone(),
+            MessageIdData::new(1, 3),
+            batched_0.clone(),
+            MessageIdData::new(1, 1),
+        ];
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![
+                MessageIdData::new(1, 1),
+                batched_0,
+                batched_1,
+                MessageIdData::new(1, 3),
+                MessageIdData::new(2, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        // new MessageIdImpl(1, 2, -1).toByteArray()
+        let message_id = MessageIdData::new(1, 2);
+        assert_eq!(message_id.to_bytes(), vec![8, 1, 16, 2]);
+        assert_eq!(
+            MessageIdData::from_bytes(&[8, 1, 16, 2]).unwrap(),
+            message_id
+        );
+
+        // new BatchMessageIdImpl(1, 2, 3, 4, 5, null).toByteArray()
+        let mut message_id = MessageIdData::new(1, 2);
+        message_id.set_partition(3).set_batch_index(4, 5);
+        let bytes = vec![8, 1, 16, 2, 24, 3, 32, 4, 48, 5];
+        assert_eq!(message_id.to_bytes(), bytes);
+        let parsed = MessageIdData::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, message_id);
+        assert_eq!(parsed.get_batch_size(), Some(5));
+
+        assert!(matches!(
+            MessageIdData::from_bytes(&[8, 1]),
+            Err(MessageIdDataParseError::RequiredFieldsMissing)
+        ));
+        assert!(matches!(
+            MessageIdData::from_bytes(&[8]),
+            Err(MessageIdDataParseError::DeserializeError(_))
+        ));
+    }
+
+    #[test]
+    fn display_and_from_str() {
+        let message_id = MessageIdData::new(1, 2);
+        assert_eq!(message_id.to_string(), "1:2:-1");
+        assert_eq!("1:2:-1".parse::<MessageIdData>().unwrap(), message_id);
+
+        let mut message_id = MessageIdData::new(1, 2);
+        message_id.set_partition(3).set_batch_index(4, 5);
+        assert_eq!(message_id.to_string(), "1:2:3:4");
+        let parsed = "1:2:3:4".parse::<MessageIdData>().unwrap();
+        assert_eq!(parsed, message_id);
+        assert_eq!(parsed.get_partition(), Some(3));
+        assert_eq!(parsed.get_batch_index(), Some(4));
+
+        assert!(matches!(
+            "1:2".parse::<MessageIdData>(),
+            Err(MessageIdDataParseError::FormatInvalid)
+        ));
+        assert!(matches!(
+            "1:x:-1".parse::<MessageIdData>(),
+            Err(MessageIdDataParseError::ParseIntError(_))
+        ));
+    }
+}
@@ -1,8 +1,12 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use std::convert::TryFrom;
+
+use chrono::{DateTime, Utc};
 
 use crate::protos::protobuf::pulsar_api::MessageMetadata as Protobuf_MessageMetadata;
 
-use super::message_properties::MessageProperties;
+use super::{
+    message::millis_to_datetime, message_properties::MessageProperties, ProducerName, SequenceId,
+};
 
 pub struct MessageMetadata<'a> {
     #[cfg(feature = "with-hacking-commands")]
@@ -11,11 +15,24 @@ pub struct MessageMetadata<'a> {
     pub(crate) inner: &'a Protobuf_MessageMetadata,
 }
 impl<'a> MessageMetadata<'a> {
-    pub fn get_publish_time(&self) -> DateTime<Utc> {
-        DateTime::<Utc>::from_utc(
-            NaiveDateTime::from_timestamp(self.inner.get_publish_time() as i64, 0),
-            Utc,
-        )
+    pub fn get_producer_name(&self) -> ProducerName {
+        ProducerName::new(self.inner.get_producer_name())
+    }
+
+    pub fn get_sequence_id(&self) -> SequenceId {
+        SequenceId::new(self.inner.get_sequence_id())
+    }
+
+    // The times are None if out of the range of DateTime, e.g. negative.
+    pub fn get_publish_time(&self) -> Option<DateTime<Utc>> {
+        millis_to_datetime(self.inner.get_publish_time())
+    }
+
+    pub fn get_event_time(&self) -> Option<DateTime<Utc>> {
+        match self.inner.get_event_time() {
+            0 => None,
+            event_time => millis_to_datetime(event_time),
+        }
     }
 
     pub fn get_properties(&self) -> MessageProperties {
@@ -23,6 +40,103 @@ impl<'a> MessageMetadata<'a> {
             inner: self.inner.properties.to_owned().into_vec(),
         }
     }
+
+    // As it is sent, base64 encoded if is_partition_key_b64_encoded.
+    pub fn get_partition_key(&self) -> Option<&'a str> {
+        if self.inner.has_partition_key() {
+            Some(self.inner.get_partition_key())
+        } else {
+            None
+        }
+    }
+    pub fn is_partition_key_b64_encoded(&self) -> bool {
+        self.inner.get_partition_key_b64_encoded()
+    }
+    // Decoded if it is base64 encoded.
+    pub fn get_partition_key_bytes(&self) -> Option<Result<Vec<u8>, base64::DecodeError>> {
+        let partition_key = self.get_partition_key()?;
+        if self.is_partition_key_b64_encoded() {
+            Some(base64::decode(partition_key))
+        } else {
+            Some(Ok(partition_key.as_bytes().to_owned()))
+        }
+    }
+
+    pub fn get_ordering_key(&self) -> Option<&'a [u8]> {
+        if self.inner.has_ordering_key() {
+            Some(self.inner.get_ordering_key())
+        } else {
+            None
+        }
+    }
+
+    // The cluster the message is replicated from.
+    pub fn get_replicated_from(&self) -> Option<&'a str> {
+        if self.inner.has_replicated_from() {
+            Some(self.inner.get_replicated_from())
+        } else {
+            None
+        }
+    }
+
+    pub fn get_schema_version(&self) -> Option<&'a [u8]> {
+        if self.inner.has_schema_version() {
+            Some(self.inner.get_schema_version())
+        } else {
+            None
+        }
+    }
+
+    pub fn get_deliver_at_time(&self) -> Option<DateTime<Utc>> {
+        if self.inner.has_deliver_at_time() {
+            let deliver_at_time = u64::try_from(self.inner.get_deliver_at_time()).ok()?;
+            millis_to_datetime(deliver_at_time)
+        } else {
+            None
+        }
+    }
+
+    // The uuid and the chunk fields are set on the chunks of a large message.
+    pub fn get_uuid(&self) -> Option<&'a str> {
+        if self.inner.has_uuid() {
+            Some(self.inner.get_uuid())
+        } else {
+            None
+        }
+    }
+    // 1 if not a batch, the broker spends one flow permit for each.
+    pub fn get_num_messages_in_batch(&self) -> u32 {
+        self.inner.get_num_messages_in_batch().max(1) as u32
+    }
+    pub fn get_num_chunks_from_msg(&self) -> Option<u32> {
+        if self.inner.has_num_chunks_from_msg() {
+            Some(self.inner.get_num_chunks_from_msg() as u32)
+        } else {
+            None
+        }
+    }
+    pub fn get_total_chunk_msg_size(&self) -> Option<u32> {
+        if self.inner.has_total_chunk_msg_size() {
+            Some(self.inner.get_total_chunk_msg_size() as u32)
+        } else {
+            None
+        }
+    }
+    pub fn get_chunk_id(&self) -> Option<u32> {
+        if self.inner.has_chunk_id() {
+            Some(self.inner.get_chunk_id() as u32)
+        } else {
+            None
+        }
+    }
+
+    // A tombstone of a compacted topic.
+    pub fn is_null_value(&self) -> bool {
+        self.inner.get_null_value()
+    }
+    pub fn is_null_partition_key(&self) -> bool {
+        self.inner.get_null_partition_key()
+    }
 }
 
 impl<'a> From<&'a Protobuf_MessageMetadata> for MessageMetadata<'a> {
@@ -30,3 +144,134 @@ impl<'a> From<&'a Protobuf_MessageMetadata> for MessageMetadata<'a> {
         Self { inner: mm }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        command::CommandWithParsed,
+        commands::SendCommand,
+        frame::{FrameParseOutput, FrameParser, FrameRenderer},
+        types::{MessageProperties, ProducerId, SequenceIdBuilder},
+    };
+
+    fn render_and_parse(send_command: &SendCommand) -> Protobuf_MessageMetadata {
+        let mut buf = Vec::new();
+        FrameRenderer::new().render(send_command, &mut buf).unwrap();
+
+        match FrameParser::new().parse(&buf[..]).unwrap() {
+            FrameParseOutput::Completed(_, CommandWithParsed::Payload(c)) => c.metadata,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn with_defaults() {
+        let mut send_command = SendCommand::single(
+            SequenceIdBuilder::new(5).next(),
+            MessageProperties::from(&[("a", "1")]),
+            b"foo",
+            None,
+        );
+        send_command.set_producer_id(ProducerId::new(1));
+        send_command.set_producer_name(ProducerName::new("standalone-0-0"));
+        let publish_time = send_command.message_metadata.get_publish_time();
+
+        let message_metadata = render_and_parse(&send_command);
+        let metadata = MessageMetadata::from(&message_metadata);
+
+        assert_eq!(String::from(metadata.get_producer_name()), "standalone-0-0");
+        assert_eq!(u64::from(metadata.get_sequence_id()), 5);
+        assert_eq!(
+            metadata
+                .get_publish_time()
+                .map(|dt| dt.timestamp_millis() as u64),
+            Some(publish_time)
+        );
+        assert_eq!(metadata.get_event_time(), None);
+        assert_eq!(metadata.get_properties().to_vec(), vec![("a", "1")]);
+        assert_eq!(metadata.get_partition_key(), None);
+        assert_eq!(metadata.get_partition_key_bytes(), None);
+        assert_eq!(metadata.get_ordering_key(), None);
+        assert_eq!(metadata.get_replicated_from(), None);
+        assert_eq!(metadata.get_schema_version(), None);
+        assert_eq!(metadata.get_deliver_at_time(), None);
+        assert_eq!(metadata.get_uuid(), None);
+        assert_eq!(metadata.get_chunk_id(), None);
+        assert!(!metadata.is_null_value());
+    }
+
+    #[test]
+    fn with_all() {
+        let mut send_command =
+            SendCommand::single(SequenceIdBuilder::default().next(), None, b"", None);
+        send_command.set_producer_id(ProducerId::new(1));
+        send_command.set_producer_name(ProducerName::new("standalone-0-0"));
+
+        let mm = &mut send_command.message_metadata;
+        mm.set_publish_time(1_600_000_000_123);
+        mm.set_event_time(1_600_000_000_456);
+        mm.set_partition_key(base64::encode([0, 255]));
+        mm.set_partition_key_b64_encoded(true);
+        mm.set_ordering_key(b"ok".to_vec());
+        mm.set_replicated_from("us-west".into());
+        mm.set_schema_version(vec![0, 1]);
+        mm.set_deliver_at_time(1_600_000_000_789);
+        mm.set_uuid("p-1".into());
+        mm.set_num_chunks_from_msg(3);
+        mm.set_total_chunk_msg_size(100);
+        mm.set_chunk_id(2);
+        mm.set_null_value(true);
+
+        let message_metadata = render_and_parse(&send_command);
+        let metadata = MessageMetadata::from(&message_metadata);
+
+        assert_eq!(
+            metadata.get_publish_time().map(|dt| dt.to_rfc3339()),
+            Some("2020-09-13T12:26:40.123+00:00".to_owned())
+        );
+        assert_eq!(
+            metadata.get_event_time().map(|dt| dt.timestamp_millis()),
+            Some(1_600_000_000_456)
+        );
+        assert_eq!(metadata.get_partition_key(), Some("AP8="));
+        assert!(metadata.is_partition_key_b64_encoded());
+        assert_eq!(metadata.get_partition_key_bytes(), Some(Ok(vec![0, 255])));
+        assert_eq!(metadata.get_ordering_key(), Some(&b"ok"[..]));
+        assert_eq!(metadata.get_replicated_from(), Some("us-west"));
+        assert_eq!(metadata.get_schema_version(), Some(&[0, 1][..]));
+        assert_eq!(
+            metadata
+                .get_deliver_at_time()
+                .map(|dt| dt.timestamp_millis()),
+            Some(1_600_000_000_789)
+        );
+        assert_eq!(metadata.get_uuid(), Some("p-1"));
+        assert_eq!(metadata.get_num_chunks_from_msg(), Some(3));
+        assert_eq!(metadata.get_total_chunk_msg_size(), Some(100));
+        assert_eq!(metadata.get_chunk_id(), Some(2));
+        assert!(metadata.is_null_value());
+        assert!(!metadata.is_null_partition_key());
+    }
+
+    #[test]
+    fn with_out_of_range_times() {
+        let mut send_command =
+            SendCommand::single(SequenceIdBuilder::default().next(), None, b"", None);
+        send_command.set_producer_id(ProducerId::new(1));
+        send_command.set_producer_name(ProducerName::new("standalone-0-0"));
+
+        let mm = &mut send_command.message_metadata;
+        mm.set_publish_time(u64::MAX);
+        mm.set_event_time(i64::MAX as u64);
+        mm.set_deliver_at_time(-1);
+
+        let message_metadata = render_and_parse(&send_command);
+        let metadata = MessageMetadata::from(&message_metadata);
+
+        assert_eq!(metadata.get_publish_time(), None);
+        assert_eq!(metadata.get_event_time(), None);
+        assert_eq!(metadata.get_deliver_at_time(), None);
+    }
+}
@@ -1,10 +1,12 @@
-use std::fmt;
+use std::{collections::HashMap, fmt, iter::FromIterator};
 
 use seq_macro::seq;
 
-use crate::protos::protobuf::pulsar_api::KeyValue;
+use crate::protos::{
+    protobuf::pulsar_api::KeyValue, utils::convert_tuple_slice_to_key_value_vector,
+};
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct MessageProperties {
     #[cfg(feature = "with-hacking-commands")]
     pub inner: Vec<KeyValue>,
@@ -24,23 +26,74 @@ impl MessageProperties {
             .map(|kv| (kv.get_key(), kv.get_value()))
             .collect::<Vec<_>>()
     }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.inner
+            .iter()
+            .find(|kv| kv.get_key() == key)
+            .map(|kv| kv.get_value())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.inner.iter().map(|kv| (kv.get_key(), kv.get_value()))
+    }
+
+    // Replaces the value of an existing key, returning the previous one.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        let key = key.into();
+        let value = value.into();
+
+        if let Some(kv) = self.inner.iter_mut().find(|kv| kv.get_key() == key) {
+            Some(std::mem::replace(kv.mut_value(), value))
+        } else {
+            self.inner.push(new_key_value(key, value));
+            None
+        }
+    }
+}
+
+fn new_key_value(key: String, value: String) -> KeyValue {
+    let mut kv = KeyValue::new();
+    kv.set_key(key);
+    kv.set_value(value);
+    kv
+}
+
+impl From<&[(&str, &str)]> for MessageProperties {
+    fn from(v: &[(&str, &str)]) -> Self {
+        Self {
+            inner: convert_tuple_slice_to_key_value_vector(v),
+        }
+    }
+}
+
+impl From<HashMap<String, String>> for MessageProperties {
+    fn from(v: HashMap<String, String>) -> Self {
+        v.into_iter().collect()
+    }
+}
+
+// Keeps the pairs as they are, also the duplicated keys, like From<&[(&str, &str)]>.
+impl<K, V> FromIterator<(K, V)> for MessageProperties
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            inner: iter
+                .into_iter()
+                .map(|(k, v)| new_key_value(k.into(), v.into()))
+                .collect(),
+        }
+    }
 }
 
 seq!(N in 0..=10 {
     #(
         impl From<&[(&str, &str); N]> for MessageProperties {
             fn from(v: &[(&str, &str); N]) -> Self {
-                let inner = v
-                    .iter()
-                    .map(|(k, v)| {
-                        let mut kv = KeyValue::new();
-                        kv.set_key(k.to_owned().into());
-                        kv.set_value(v.to_owned().into());
-                        kv
-                    })
-                    .collect::<Vec<_>>();
-
-                Self { inner }
+                Self::from(&v[..])
             }
         }
 
@@ -49,9 +102,7 @@ seq!(N in 0..=10 {
                 if let Some(v) = v {
                     v.into()
                 } else {
-                    Self {
-                        inner: Default::default(),
-                    }
+                    Self::default()
                 }
             }
         }
@@ -67,4 +118,30 @@ mod tests {
         assert_eq!(MessageProperties::from(&[("a", "1")]).inner.len(), 1);
         assert_eq!(MessageProperties::from(Some(&[("a", "1")])).inner.len(), 1);
     }
+
+    #[test]
+    fn dynamic() {
+        let pairs = (0..20)
+            .map(|i| (format!("k{}", i), i.to_string()))
+            .collect::<Vec<_>>();
+        let mut properties = pairs.iter().cloned().collect::<MessageProperties>();
+        assert_eq!(properties.iter().count(), 20);
+        assert_eq!(properties.get("k11"), Some("11"));
+        assert_eq!(properties.get("k20"), None);
+
+        assert_eq!(properties.insert("k11", "x"), Some("11".to_owned()));
+        assert_eq!(properties.insert("k20", "20"), None);
+        assert_eq!(properties.get("k11"), Some("x"));
+        assert_eq!(properties.iter().count(), 21);
+
+        let map = pairs.into_iter().collect::<HashMap<_, _>>();
+        let properties = MessageProperties::from(map);
+        assert_eq!(properties.get("k19"), Some("19"));
+
+        let slice: &[(&str, &str)] = &[("a", "1"), ("a", "2")];
+        assert_eq!(MessageProperties::from(slice).inner.len(), 2);
+        let properties = slice.iter().cloned().collect::<MessageProperties>();
+        assert_eq!(properties.to_vec(), slice.to_vec());
+        assert_eq!(properties.get("a"), Some("1"));
+    }
 }
@@ -4,14 +4,18 @@ mod macro_make_x_id_builder_and_x_id;
 pub mod ack_type;
 pub mod ack_validation_error;
 pub mod compression_type;
+pub mod message;
 pub mod message_id_data;
 pub mod message_metadata;
 pub mod message_properties;
+pub mod outgoing_message;
 pub mod producer_name;
 pub mod protocol_version;
 pub mod server_error;
 pub mod single_message_metadata;
 pub mod subscribe_type;
+pub mod topic_name;
+pub mod topics_of_namespace_mode;
 
 pub mod consumer_id;
 pub mod producer_id;
@@ -21,14 +25,18 @@ pub mod sequence_id;
 pub use ack_type::AckType;
 pub use ack_validation_error::AckValidationError;
 pub use compression_type::CompressionType;
-pub use message_id_data::MessageIdData;
+pub use message::Message;
+pub use message_id_data::{MessageIdData, MessageIdDataParseError};
 pub use message_metadata::MessageMetadata;
 pub use message_properties::MessageProperties;
+pub use outgoing_message::OutgoingMessage;
 pub use producer_name::ProducerName;
 pub use protocol_version::ProtocolVersion;
 pub use server_error::ServerError;
 pub use single_message_metadata::SingleMessageMetadata;
 pub use subscribe_type::SubscribeType;
+pub use topic_name::{TopicDomain, TopicName, TopicNameParseError};
+pub use topics_of_namespace_mode::TopicsOfNamespaceMode;
 
 pub use consumer_id::{ConsumerId, ConsumerIdBuilder};
 pub use producer_id::{ProducerId, ProducerIdBuilder};
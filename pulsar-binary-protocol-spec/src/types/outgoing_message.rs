@@ -0,0 +1,210 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::commands::SendCommand;
+
+use super::{CompressionType, MessageProperties, SequenceId};
+
+// A message to be sent by a producer, which assigns the sequence id.
+#[derive(Clone, Debug)]
+pub struct OutgoingMessage {
+    payload: Vec<u8>,
+    properties: Option<MessageProperties>,
+    compression: Option<CompressionType>,
+    partition_key: Option<PartitionKey>,
+    ordering_key: Option<Vec<u8>>,
+    event_time: Option<DateTime<Utc>>,
+    replicate_to: Option<Vec<String>>,
+    schema_version: Option<Vec<u8>>,
+    null_value: bool,
+    deliver_at_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug)]
+enum PartitionKey {
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl OutgoingMessage {
+    pub fn new(payload: impl AsRef<[u8]>) -> Self {
+        Self {
+            payload: payload.as_ref().to_owned(),
+            properties: None,
+            compression: None,
+            partition_key: None,
+            ordering_key: None,
+            event_time: None,
+            replicate_to: None,
+            schema_version: None,
+            null_value: false,
+            deliver_at_time: None,
+        }
+    }
+
+    // Removes the partition key from a compacted topic.
+    pub fn tombstone(partition_key: &str) -> Self {
+        let mut message = Self::new(vec![]);
+        message
+            .set_partition_key(partition_key)
+            .set_null_value(true);
+        message
+    }
+
+    pub fn set_properties(&mut self, properties: impl Into<MessageProperties>) -> &mut Self {
+        self.properties = Some(properties.into());
+        self
+    }
+
+    pub fn set_compression(&mut self, compression: CompressionType) -> &mut Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn set_partition_key(&mut self, partition_key: &str) -> &mut Self {
+        self.partition_key = Some(PartitionKey::String(partition_key.to_owned()));
+        self
+    }
+    pub fn set_partition_key_bytes(&mut self, partition_key: &[u8]) -> &mut Self {
+        self.partition_key = Some(PartitionKey::Bytes(partition_key.to_owned()));
+        self
+    }
+
+    pub fn set_ordering_key(&mut self, ordering_key: &[u8]) -> &mut Self {
+        self.ordering_key = Some(ordering_key.to_owned());
+        self
+    }
+
+    pub fn set_event_time(&mut self, dt: DateTime<Utc>) -> &mut Self {
+        self.event_time = Some(dt);
+        self
+    }
+
+    pub fn set_replicate_to(&mut self, clusters: &[&str]) -> &mut Self {
+        self.replicate_to = Some(clusters.iter().map(|cluster| cluster.to_string()).collect());
+        self
+    }
+    pub fn disable_replication(&mut self) -> &mut Self {
+        self.set_replicate_to(&["__local__"])
+    }
+
+    pub fn set_schema_version(&mut self, schema_version: &[u8]) -> &mut Self {
+        self.schema_version = Some(schema_version.to_owned());
+        self
+    }
+
+    pub fn set_null_value(&mut self, null_value: bool) -> &mut Self {
+        self.null_value = null_value;
+        self
+    }
+
+    pub fn set_deliver_at_time(&mut self, dt: DateTime<Utc>) -> &mut Self {
+        self.deliver_at_time = Some(dt);
+        self
+    }
+    pub fn set_deliver_after(&mut self, dur: StdDuration) -> Result<&mut Self, String> {
+        let dur = Duration::from_std(dur).map_err(|err| err.to_string())?;
+
+        self.deliver_at_time = Some(Utc::now() + dur);
+        Ok(self)
+    }
+
+    pub fn get_payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn into_send_command(self, sequence_id: SequenceId) -> SendCommand {
+        let mut c =
+            SendCommand::single(sequence_id, self.properties, self.payload, self.compression);
+
+        match self.partition_key {
+            Some(PartitionKey::String(key)) => {
+                c.set_partition_key(&key);
+            }
+            Some(PartitionKey::Bytes(key)) => {
+                c.set_partition_key_bytes(&key);
+            }
+            None => {}
+        }
+        if let Some(ordering_key) = self.ordering_key {
+            c.set_ordering_key(&ordering_key);
+        }
+        if let Some(event_time) = self.event_time {
+            c.set_event_time(event_time);
+        }
+        if let Some(replicate_to) = self.replicate_to {
+            c.set_replicate_to(&replicate_to.iter().map(|s| s.as_str()).collect::<Vec<_>>());
+        }
+        if let Some(schema_version) = self.schema_version {
+            c.set_schema_version(&schema_version);
+        }
+        if self.null_value {
+            c.set_null_value(true);
+        }
+        if let Some(deliver_at_time) = self.deliver_at_time {
+            c.set_deliver_at_time(deliver_at_time);
+        }
+
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone as _;
+
+    use crate::{command::PayloadCommandPayload, types::SequenceIdBuilder};
+
+    #[test]
+    fn into_send_command() {
+        let mut message = OutgoingMessage::new("foo");
+        message.set_properties(&[("a", "1")]);
+
+        let c = message.into_send_command(SequenceIdBuilder::default().next());
+        assert_eq!(c.get_sequence_id(), SequenceIdBuilder::default().next());
+        assert_eq!(c.message_metadata.get_properties()[0].get_key(), "a");
+        match c.payload {
+            PayloadCommandPayload::Single(bytes) => assert_eq!(bytes, b"foo"),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn into_send_command_with_metadata() {
+        let mut message = OutgoingMessage::new("foo");
+        message
+            .set_partition_key_bytes(&[1, 2])
+            .set_ordering_key(b"ok")
+            .set_event_time(Utc.timestamp_millis_opt(1_600_000_000_123).unwrap())
+            .disable_replication()
+            .set_schema_version(&[0, 0, 0, 1])
+            .set_deliver_at_time(Utc.timestamp_millis_opt(1_600_000_001_000).unwrap());
+
+        let c = message.into_send_command(SequenceIdBuilder::default().next());
+        let metadata = &c.message_metadata;
+        assert_eq!(metadata.get_partition_key(), "AQI=");
+        assert!(metadata.get_partition_key_b64_encoded());
+        assert_eq!(metadata.get_ordering_key(), b"ok");
+        assert_eq!(metadata.get_event_time(), 1_600_000_000_123);
+        assert_eq!(metadata.get_replicate_to(), &["__local__".to_owned()]);
+        assert_eq!(metadata.get_schema_version(), &[0, 0, 0, 1]);
+        assert!(!metadata.has_null_value());
+        assert_eq!(metadata.get_deliver_at_time(), 1_600_000_001_000);
+    }
+
+    #[test]
+    fn tombstone() {
+        let c =
+            OutgoingMessage::tombstone("k").into_send_command(SequenceIdBuilder::default().next());
+        assert_eq!(c.message_metadata.get_partition_key(), "k");
+        assert!(!c.message_metadata.get_partition_key_b64_encoded());
+        assert!(c.message_metadata.get_null_value());
+        match c.payload {
+            PayloadCommandPayload::Single(bytes) => assert!(bytes.is_empty()),
+            _ => panic!(),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use thiserror::Error;
+
+const PUBLIC_TENANT: &str = "public";
+const DEFAULT_NAMESPACE: &str = "default";
+const PARTITIONED_TOPIC_SUFFIX: &str = "-partition-";
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum TopicDomain {
+    Persistent,
+    NonPersistent,
+}
+impl TopicDomain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Persistent => "persistent",
+            Self::NonPersistent => "non-persistent",
+        }
+    }
+}
+
+// domain://tenant/namespace/local_name, e.g. persistent://public/default/my-topic-partition-0
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub struct TopicName {
+    domain: TopicDomain,
+    tenant: String,
+    namespace: String,
+    local_name: String,
+    partition_index: Option<u32>,
+    complete_name: String,
+}
+impl TopicName {
+    pub fn new(
+        domain: TopicDomain,
+        tenant: &str,
+        namespace: &str,
+        local_name: &str,
+    ) -> Result<Self, TopicNameParseError> {
+        for part in &[tenant, namespace, local_name] {
+            if part.is_empty() || part.contains('/') {
+                return Err(TopicNameParseError::FormatInvalid(format!(
+                    "{}://{}/{}/{}",
+                    domain.as_str(),
+                    tenant,
+                    namespace,
+                    local_name
+                )));
+            }
+        }
+
+        let partition_index = local_name.rfind(PARTITIONED_TOPIC_SUFFIX).and_then(|i| {
+            local_name[i + PARTITIONED_TOPIC_SUFFIX.len()..]
+                .parse()
+                .ok()
+        });
+
+        Ok(Self {
+            domain,
+            tenant: tenant.to_owned(),
+            namespace: namespace.to_owned(),
+            local_name: local_name.to_owned(),
+            partition_index,
+            complete_name: format!(
+                "{}://{}/{}/{}",
+                domain.as_str(),
+                tenant,
+                namespace,
+                local_name
+            ),
+        })
+    }
+
+    pub fn get_domain(&self) -> TopicDomain {
+        self.domain
+    }
+    pub fn get_tenant(&self) -> &str {
+        &self.tenant
+    }
+    // Without the tenant.
+    pub fn get_namespace(&self) -> &str {
+        &self.namespace
+    }
+    pub fn get_local_name(&self) -> &str {
+        &self.local_name
+    }
+
+    pub fn get_partition_index(&self) -> Option<u32> {
+        self.partition_index
+    }
+    pub fn is_partition(&self) -> bool {
+        self.partition_index.is_some()
+    }
+    // The topic that the partition belongs to, or itself.
+    pub fn get_partitioned_topic_name(&self) -> Self {
+        match self.local_name.rfind(PARTITIONED_TOPIC_SUFFIX) {
+            Some(i) if self.is_partition() => Self::new(
+                self.domain,
+                &self.tenant,
+                &self.namespace,
+                &self.local_name[..i],
+            )
+            .expect("valid TopicName"),
+            _ => self.to_owned(),
+        }
+    }
+    pub fn get_partition(&self, index: u32) -> Self {
+        let topic = self.get_partitioned_topic_name();
+        Self::new(
+            topic.domain,
+            &topic.tenant,
+            &topic.namespace,
+            &format!("{}{}{}", topic.local_name, PARTITIONED_TOPIC_SUFFIX, index),
+        )
+        .expect("valid TopicName")
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.complete_name
+    }
+}
+
+impl FromStr for TopicName {
+    type Err = TopicNameParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (domain, rest) = match s.split_once("://") {
+            Some(("persistent", rest)) => (TopicDomain::Persistent, rest),
+            Some(("non-persistent", rest)) => (TopicDomain::NonPersistent, rest),
+            Some((domain, _)) => return Err(TopicNameParseError::DomainInvalid(domain.to_owned())),
+            None if !s.contains('/') => {
+                return Self::new(TopicDomain::Persistent, PUBLIC_TENANT, DEFAULT_NAMESPACE, s)
+            }
+            None => (TopicDomain::Persistent, s),
+        };
+
+        match rest.split('/').collect::<Vec<_>>()[..] {
+            [tenant, namespace, local_name] => Self::new(domain, tenant, namespace, local_name),
+            _ => Err(TopicNameParseError::FormatInvalid(s.to_owned())),
+        }
+    }
+}
+
+impl TryFrom<&str> for TopicName {
+    type Error = TopicNameParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl AsRef<str> for TopicName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for TopicName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.complete_name)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum TopicNameParseError {
+    #[error("DomainInvalid {0:?}")]
+    DomainInvalid(String),
+    #[error("FormatInvalid {0:?}")]
+    FormatInvalid(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let t: TopicName = "my-topic".parse().unwrap();
+        assert_eq!(t.as_str(), "persistent://public/default/my-topic");
+        assert_eq!(t.get_domain(), TopicDomain::Persistent);
+        assert_eq!(t.get_tenant(), "public");
+        assert_eq!(t.get_namespace(), "default");
+        assert_eq!(t.get_local_name(), "my-topic");
+        assert_eq!(t.get_partition_index(), None);
+
+        let t: TopicName = "my-tenant/my-ns/my-topic".parse().unwrap();
+        assert_eq!(t.as_str(), "persistent://my-tenant/my-ns/my-topic");
+
+        let t: TopicName = "non-persistent://my-tenant/my-ns/my-topic-partition-3"
+            .parse()
+            .unwrap();
+        assert_eq!(t.get_domain(), TopicDomain::NonPersistent);
+        assert_eq!(t.get_partition_index(), Some(3));
+        assert_eq!(
+            t.get_partitioned_topic_name().as_str(),
+            "non-persistent://my-tenant/my-ns/my-topic"
+        );
+        assert_eq!(
+            t.get_partition(1).as_str(),
+            "non-persistent://my-tenant/my-ns/my-topic-partition-1"
+        );
+
+        let c = crate::commands::ProducerCommand::new(&t);
+        assert_eq!(c.inner_command.get_topic(), t.as_str());
+
+        let t: TopicName = "my-topic-partition-x".parse().unwrap();
+        assert_eq!(t.get_partition_index(), None);
+        assert_eq!(t.get_partitioned_topic_name(), t);
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(matches!(
+            "http://public/default/my-topic".parse::<TopicName>(),
+            Err(TopicNameParseError::DomainInvalid(_))
+        ));
+        for s in &[
+            "",
+            "public/my-topic",
+            "persistent://public/default",
+            "persistent://public/default/",
+            "persistent://public//my-topic",
+            "persistent://public/cluster/default/my-topic",
+        ] {
+            assert!(
+                matches!(
+                    s.parse::<TopicName>(),
+                    Err(TopicNameParseError::FormatInvalid(_))
+                ),
+                "{}",
+                s
+            );
+        }
+    }
+}
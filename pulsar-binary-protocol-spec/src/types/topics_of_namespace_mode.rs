@@ -0,0 +1,18 @@
+use crate::protos::protobuf::pulsar_api::CommandGetTopicsOfNamespace_Mode as Protobuf_Mode;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum TopicsOfNamespaceMode {
+    Persistent,
+    NonPersistent,
+    All,
+}
+
+impl From<TopicsOfNamespaceMode> for Protobuf_Mode {
+    fn from(mode: TopicsOfNamespaceMode) -> Self {
+        match mode {
+            TopicsOfNamespaceMode::Persistent => Protobuf_Mode::PERSISTENT,
+            TopicsOfNamespaceMode::NonPersistent => Protobuf_Mode::NON_PERSISTENT,
+            TopicsOfNamespaceMode::All => Protobuf_Mode::ALL,
+        }
+    }
+}
@@ -3,7 +3,7 @@ use pulsar_binary_protocol_spec::{
     client_channel_messages::SessionSendHandlerChannelMessage,
     client_handler::{handle_with_connect, HandlerHandleOutput},
     client_responds::ConnectRespondError,
-    ConnectCommand,
+    ConnectCommand, ConnectedCommand,
 };
 use thiserror::Error;
 
@@ -36,59 +36,72 @@ pub enum RawConnectError {
 
 impl<S> AsyncClient<S>
 where
-    S: AsyncRead + AsyncWrite + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
 {
     pub async fn raw_connect(
         mut self,
         mut command_connect: ConnectCommand,
     ) -> Result<(AsyncSession, AsyncHandler<S>), RawConnectError> {
-        self.connection
-            .write_command(&command_connect)
-            .await
-            .map_err(ConnectRespondError::from)?;
-        let commands = self
-            .connection
-            .try_read_commands(1)
-            .await
-            .map_err(ConnectRespondError::from)?;
+        let connected_command = connect(&mut self.connection, &command_connect).await?;
+
+        let (sender, receiver) = unbounded::<SessionSendHandlerChannelMessage>();
+        // The handler keeps the auth data for reconnecting.
+        let handler = AsyncHandler::new(self.connection, receiver, command_connect.to_owned());
 
-        let commands = commands
-            .ok_or_else(|| RawConnectError::Unknown("Not receive any command".to_owned()))?;
-        let command = commands
-            .first()
-            .ok_or_else(|| RawConnectError::Unknown("Not receive any command".to_owned()))?;
+        command_connect.hide_auth_data(b"******");
 
-        match handle_with_connect(&command) {
-            Ok(HandlerHandleOutput::OnConnectResponded(Ok(connected_command))) => {
-                if let Some(max_message_size) = connected_command.get_max_message_size() {
-                    self.connection
-                        .get_mut_frame_renderer()
-                        .get_mut_config()
-                        .set_max_frame_size(max_message_size);
-                    self.connection
-                        .get_mut_frame_parser()
-                        .get_mut_config()
-                        .set_max_frame_size(max_message_size);
-                }
+        Ok((
+            AsyncSession::new(sender, command_connect, connected_command),
+            handler,
+        ))
+    }
+}
 
-                command_connect.hide_auth_data(b"******");
+pub(crate) async fn connect<S>(
+    connection: &mut AsyncConnection<S>,
+    command_connect: &ConnectCommand,
+) -> Result<ConnectedCommand, RawConnectError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    connection
+        .write_command(command_connect)
+        .await
+        .map_err(ConnectRespondError::from)?;
+    let commands = connection
+        .try_read_commands(1)
+        .await
+        .map_err(ConnectRespondError::from)?;
 
-                let (sender, receiver) = unbounded::<SessionSendHandlerChannelMessage>();
+    let commands =
+        commands.ok_or_else(|| RawConnectError::Unknown("Not receive any command".to_owned()))?;
+    let command = commands
+        .first()
+        .ok_or_else(|| RawConnectError::Unknown("Not receive any command".to_owned()))?;
 
-                Ok((
-                    AsyncSession::new(sender, command_connect, connected_command),
-                    AsyncHandler::new(self.connection, receiver),
-                ))
+    match handle_with_connect(command) {
+        Ok(HandlerHandleOutput::OnConnectResponded(Ok(connected_command))) => {
+            if let Some(max_message_size) = connected_command.get_max_message_size() {
+                connection
+                    .get_mut_frame_renderer()
+                    .get_mut_config()
+                    .set_max_frame_size(max_message_size);
+                connection
+                    .get_mut_frame_parser()
+                    .get_mut_config()
+                    .set_max_frame_size(max_message_size);
             }
-            Ok(HandlerHandleOutput::OnConnectResponded(Err(err))) => Err(err.into()),
-            Ok(output) => Err(RawConnectError::Unknown(format!(
-                "Receive wrong command {:?}",
-                output
-            ))),
-            Err(err) => Err(RawConnectError::Unknown(format!(
-                "Receive wrong command {:?}",
-                err
-            ))),
+
+            Ok(connected_command)
         }
+        Ok(HandlerHandleOutput::OnConnectResponded(Err(err))) => Err(err.into()),
+        Ok(output) => Err(RawConnectError::Unknown(format!(
+            "Receive wrong command {:?}",
+            output
+        ))),
+        Err(err) => Err(RawConnectError::Unknown(format!(
+            "Receive wrong command {:?}",
+            err
+        ))),
     }
 }
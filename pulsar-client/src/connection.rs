@@ -1,17 +1,40 @@
-use std::{cmp::max, time::Duration};
+use std::{
+    cmp::max,
+    io::{Error as IoError, ErrorKind as IoErrorKind},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
+use futures_util::future::pending;
 use pulsar_binary_protocol_spec::{
+    async_channel::{bounded, TryRecvError},
+    client_channel::{AC_Receiver, AC_Sender},
     client_handler::{ReadCommandError, WriteCommandError},
     command::{Command, CommandWithParsed},
-    frame::{FrameParseOutput, FrameParser, FrameRenderer},
+    frame::{FrameParseOutput, FrameParser, FrameRenderError, FrameRenderer},
     types::{ConsumerIdBuilder, ProducerIdBuilder, RequestIdBuilder},
+    PingCommand,
 };
+use thiserror::Error;
 
-use super::{AsyncRead, AsyncReadWithTimeoutExt, AsyncWrite, AsyncWriteExt};
+use super::{
+    sleep, split, AsyncRead, AsyncReadExt, AsyncReadWithTimeoutExt, AsyncWrite, AsyncWriteExt,
+    ReadHalf, WriteHalf,
+};
 
 #[derive(Default, Debug, Clone)]
 pub struct AsyncConnectionConfig {
     read_timeout: Option<Duration>,
+    read_channel_capacity: Option<usize>,
+    write_channel_capacity: Option<usize>,
+    write_coalesce_size: Option<usize>,
+    keepalive_interval: Option<Option<Duration>>,
+    keepalive_timeout: Option<Duration>,
+    operation_timeout: Option<Duration>,
+    send_timeout: Option<Option<Duration>>,
 }
 impl AsyncConnectionConfig {
     pub fn set_read_timeout(&mut self, dur: Duration) -> &mut Self {
@@ -22,6 +45,73 @@ impl AsyncConnectionConfig {
         self.read_timeout
             .unwrap_or_else(|| Duration::from_millis(100))
     }
+
+    pub fn set_read_channel_capacity(&mut self, cap: usize) -> &mut Self {
+        self.read_channel_capacity = Some(cap);
+        self
+    }
+    fn get_read_channel_capacity(&self) -> usize {
+        max(self.read_channel_capacity.unwrap_or(64), 1)
+    }
+
+    pub fn set_write_channel_capacity(&mut self, cap: usize) -> &mut Self {
+        self.write_channel_capacity = Some(cap);
+        self
+    }
+    fn get_write_channel_capacity(&self) -> usize {
+        max(self.write_channel_capacity.unwrap_or(128), 1)
+    }
+
+    // The writer keeps appending queued frames to one buffer until it reaches this size.
+    pub fn set_write_coalesce_size(&mut self, size: usize) -> &mut Self {
+        self.write_coalesce_size = Some(size);
+        self
+    }
+    fn get_write_coalesce_size(&self) -> usize {
+        self.write_coalesce_size.unwrap_or(64 * 1024)
+    }
+
+    // A PING is sent after the connection read nothing for this long, None disables it.
+    pub fn set_keepalive_interval(&mut self, dur: impl Into<Option<Duration>>) -> &mut Self {
+        self.keepalive_interval = Some(dur.into());
+        self
+    }
+    fn get_keepalive_interval(&self) -> Option<Duration> {
+        self.keepalive_interval
+            .unwrap_or_else(|| Some(Duration::from_secs(30)))
+    }
+
+    // The connection is dead if nothing is read within this time after the PING is written.
+    pub fn set_keepalive_timeout(&mut self, dur: Duration) -> &mut Self {
+        self.keepalive_timeout = Some(dur);
+        self
+    }
+    fn get_keepalive_timeout(&self) -> Duration {
+        self.keepalive_timeout
+            .or_else(|| self.get_keepalive_interval())
+            .unwrap_or_else(|| Duration::from_secs(30))
+    }
+
+    // Creating producers and consumers, lookups and acks fail with Timeout after this.
+    pub fn set_operation_timeout(&mut self, dur: Duration) -> &mut Self {
+        self.operation_timeout = Some(dur);
+        self
+    }
+    fn get_operation_timeout(&self) -> Duration {
+        self.operation_timeout
+            .unwrap_or_else(|| Duration::from_secs(30))
+    }
+
+    // Sends fail with Timeout after this, also while the producer is reconnecting.
+    // None disables it.
+    pub fn set_send_timeout(&mut self, dur: impl Into<Option<Duration>>) -> &mut Self {
+        self.send_timeout = Some(dur.into());
+        self
+    }
+    fn get_send_timeout(&self) -> Option<Duration> {
+        self.send_timeout
+            .unwrap_or_else(|| Some(Duration::from_secs(30)))
+    }
 }
 
 pub struct AsyncConnection<S> {
@@ -29,10 +119,7 @@ pub struct AsyncConnection<S> {
     config: AsyncConnectionConfig,
     frame_renderer: FrameRenderer,
     frame_renderer_buf: Vec<u8>,
-    frame_parser: FrameParser,
-    frame_parser_buf: Vec<u8>,
-    frame_parser_buf_n_read: usize,
-    frame_parser_buf_n_parsed: usize,
+    frame_reader: FrameReader,
     pub(crate) request_id_builder: RequestIdBuilder,
     pub(crate) producer_id_builder: ProducerIdBuilder,
     pub(crate) consumer_id_builder: ConsumerIdBuilder,
@@ -48,10 +135,7 @@ where
             config: config.into().unwrap_or_default(),
             frame_renderer: FrameRenderer::default(),
             frame_renderer_buf: Vec::with_capacity(5 * 1024 * 1024),
-            frame_parser: FrameParser::default(),
-            frame_parser_buf: vec![0; 5 * 1024 * 1024],
-            frame_parser_buf_n_read: 0,
-            frame_parser_buf_n_parsed: 0,
+            frame_reader: FrameReader::default(),
             request_id_builder: Default::default(),
             producer_id_builder: Default::default(),
             consumer_id_builder: Default::default(),
@@ -63,7 +147,7 @@ where
     }
 
     pub(crate) fn get_mut_frame_parser(&mut self) -> &mut FrameParser {
-        &mut self.frame_parser
+        &mut self.frame_reader.frame_parser
     }
 
     pub(crate) async fn write_command<C>(&mut self, command: C) -> Result<(), WriteCommandError>
@@ -83,50 +167,364 @@ where
         &mut self,
         max_size: impl Into<Option<usize>>,
     ) -> Result<Option<Vec<CommandWithParsed>>, ReadCommandError> {
+        let frame_reader = &mut self.frame_reader;
+
+        frame_reader.reserve_buf();
         let n = self
             .stream
             .read_with_timeout(
-                &mut self.frame_parser_buf[self.frame_parser_buf_n_read..],
+                &mut frame_reader.buf[frame_reader.buf_n_read..],
                 self.config.get_read_timeout(),
             )
             .await?;
-        self.frame_parser_buf_n_read += n;
+        frame_reader.buf_n_read += n;
         if n == 0 {
             return Ok(None);
         }
 
+        let commands = frame_reader.parse_commands(max_size)?;
+
+        if commands.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(commands))
+        }
+    }
+}
+
+impl<S> AsyncConnection<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    // Splits the stream so that reading never waits for a pending write.
+    // The reader, the writer and the keepalive should be run concurrently with the handler.
+    pub(crate) fn into_split(
+        self,
+    ) -> (
+        AsyncConnectionReader<ReadHalf<S>>,
+        AsyncConnectionWriter<WriteHalf<S>>,
+        AsyncConnectionKeepalive,
+        AsyncConnectionHandle,
+    ) {
+        let (read_half, write_half) = split(self.stream);
+
+        let (commands_sender, commands_receiver) = bounded(self.config.get_read_channel_capacity());
+        let (frames_sender, frames_receiver) = bounded(self.config.get_write_channel_capacity());
+
+        let progress = Arc::new(AsyncConnectionProgress::default());
+
+        (
+            AsyncConnectionReader {
+                stream: read_half,
+                frame_reader: self.frame_reader,
+                sender: commands_sender,
+                progress: progress.to_owned(),
+            },
+            AsyncConnectionWriter {
+                stream: write_half,
+                receiver: frames_receiver,
+                buf: Vec::with_capacity(self.config.get_write_coalesce_size()),
+                coalesce_size: self.config.get_write_coalesce_size(),
+                progress: progress.to_owned(),
+            },
+            AsyncConnectionKeepalive {
+                interval: self.config.get_keepalive_interval(),
+                timeout: self.config.get_keepalive_timeout(),
+                frame_renderer: self.frame_renderer.to_owned(),
+                sender: frames_sender.to_owned(),
+                progress,
+            },
+            AsyncConnectionHandle {
+                operation_timeout: self.config.get_operation_timeout(),
+                send_timeout: self.config.get_send_timeout(),
+                frame_renderer: self.frame_renderer,
+                sender: frames_sender,
+                commands_receiver,
+                request_id_builder: self.request_id_builder,
+                producer_id_builder: self.producer_id_builder,
+                consumer_id_builder: self.consumer_id_builder,
+            },
+        )
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AsyncConnectionTaskError {
+    #[error("ReadCommandError {0:?}")]
+    ReadCommandError(#[from] ReadCommandError),
+    #[error("WriteError {0:?}")]
+    WriteError(#[from] IoError),
+    #[error("ChannelClosed")]
+    ChannelClosed,
+    #[error("FrameRenderError {0:?}")]
+    FrameRenderError(#[from] FrameRenderError),
+    #[error("KeepaliveTimeout")]
+    KeepaliveTimeout,
+    #[error("ReconnectTimeout")]
+    ReconnectTimeout,
+    #[error("PendingSequenceOutOfOrder")]
+    PendingSequenceOutOfOrder,
+}
+
+// Shared by the reader, the writer and the keepalive.
+#[derive(Default)]
+struct AsyncConnectionProgress {
+    // Counts the reads, and the commands delivered to the handler.
+    n_reads: AtomicU64,
+    // The reader waits for the handler, the PONG can not be read meanwhile.
+    delivering: AtomicBool,
+    // Counts the writes, also the partial ones.
+    n_writes: AtomicU64,
+}
+impl AsyncConnectionProgress {
+    fn get_n_reads(&self) -> u64 {
+        self.n_reads.load(Ordering::Relaxed)
+    }
+    fn get_n_writes(&self) -> u64 {
+        self.n_writes.load(Ordering::Relaxed)
+    }
+    fn is_reading(&self, n_reads: u64) -> bool {
+        self.get_n_reads() != n_reads || self.delivering.load(Ordering::Relaxed)
+    }
+}
+
+pub(crate) struct AsyncConnectionReader<R> {
+    stream: R,
+    frame_reader: FrameReader,
+    sender: AC_Sender<Vec<CommandWithParsed>>,
+    progress: Arc<AsyncConnectionProgress>,
+}
+
+impl<R> AsyncConnectionReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    pub(crate) async fn run(mut self) -> Result<(), AsyncConnectionTaskError> {
+        loop {
+            let commands = self.read_commands().await?;
+
+            self.progress.delivering.store(true, Ordering::Relaxed);
+            self.sender
+                .send(commands)
+                .await
+                .map_err(|_| AsyncConnectionTaskError::ChannelClosed)?;
+            self.progress.delivering.store(false, Ordering::Relaxed);
+            self.progress.n_reads.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    async fn read_commands(&mut self) -> Result<Vec<CommandWithParsed>, ReadCommandError> {
+        let frame_reader = &mut self.frame_reader;
+
+        loop {
+            let commands = frame_reader.parse_commands(None)?;
+            if !commands.is_empty() {
+                return Ok(commands);
+            }
+
+            frame_reader.reserve_buf();
+            let n = self
+                .stream
+                .read(&mut frame_reader.buf[frame_reader.buf_n_read..])
+                .await?;
+            frame_reader.buf_n_read += n;
+            if n == 0 {
+                return Err(IoError::new(IoErrorKind::UnexpectedEof, "connection closed").into());
+            }
+            self.progress.n_reads.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+pub(crate) struct AsyncConnectionWriter<W> {
+    stream: W,
+    receiver: AC_Receiver<Vec<u8>>,
+    buf: Vec<u8>,
+    coalesce_size: usize,
+    progress: Arc<AsyncConnectionProgress>,
+}
+
+impl<W> AsyncConnectionWriter<W>
+where
+    W: AsyncWrite + Unpin,
+{
+    pub(crate) async fn run(mut self) -> Result<(), AsyncConnectionTaskError> {
+        while let Ok(frame) = self.receiver.recv().await {
+            self.buf.extend_from_slice(&frame[..]);
+
+            while self.buf.len() < self.coalesce_size {
+                match self.receiver.try_recv() {
+                    Ok(frame) => self.buf.extend_from_slice(&frame[..]),
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+                }
+            }
+
+            // Not write_all, the keepalive waits while a large write makes progress.
+            let mut n_written = 0;
+            while n_written < self.buf.len() {
+                let n = self.stream.write(&self.buf[n_written..]).await?;
+                if n == 0 {
+                    return Err(IoError::new(IoErrorKind::WriteZero, "connection closed").into());
+                }
+                n_written += n;
+                self.progress.n_writes.fetch_add(1, Ordering::Relaxed);
+            }
+            self.stream.flush().await?;
+
+            self.buf.clear();
+        }
+
+        Err(AsyncConnectionTaskError::ChannelClosed)
+    }
+}
+
+// Pings the broker when the connection is idle, and fails when the broker stays silent.
+// Not while the reader waits for the handler, the PONG may already be read.
+pub(crate) struct AsyncConnectionKeepalive {
+    interval: Option<Duration>,
+    timeout: Duration,
+    frame_renderer: FrameRenderer,
+    sender: AC_Sender<Vec<u8>>,
+    progress: Arc<AsyncConnectionProgress>,
+}
+
+impl AsyncConnectionKeepalive {
+    pub(crate) async fn run(mut self) -> Result<(), AsyncConnectionTaskError> {
+        let interval = match self.interval {
+            Some(interval) => interval,
+            None => return pending().await,
+        };
+
+        loop {
+            let n_reads = self.progress.get_n_reads();
+            sleep(interval).await;
+            if self.progress.is_reading(n_reads) {
+                continue;
+            }
+
+            let mut buf = vec![];
+            self.frame_renderer.render(&PingCommand::new(), &mut buf)?;
+            self.sender
+                .send(buf)
+                .await
+                .map_err(|_| AsyncConnectionTaskError::ChannelClosed)?;
+
+            // Any bytes count, the PONG can be queued behind other commands.
+            // Waits again while the writer makes progress, the PING can be queued behind a large write.
+            loop {
+                let n_writes = self.progress.get_n_writes();
+                sleep(self.timeout).await;
+                if self.progress.is_reading(n_reads) {
+                    break;
+                }
+                if self.progress.get_n_writes() == n_writes {
+                    return Err(AsyncConnectionTaskError::KeepaliveTimeout);
+                }
+            }
+        }
+    }
+}
+
+// Handler side of the split connection, renders commands and queues the frames for the writer.
+pub(crate) struct AsyncConnectionHandle {
+    pub(crate) operation_timeout: Duration,
+    pub(crate) send_timeout: Option<Duration>,
+    frame_renderer: FrameRenderer,
+    sender: AC_Sender<Vec<u8>>,
+    pub(crate) commands_receiver: AC_Receiver<Vec<CommandWithParsed>>,
+    pub(crate) request_id_builder: RequestIdBuilder,
+    pub(crate) producer_id_builder: ProducerIdBuilder,
+    pub(crate) consumer_id_builder: ConsumerIdBuilder,
+}
+
+impl AsyncConnectionHandle {
+    pub(crate) async fn write_command<C>(&mut self, command: C) -> Result<(), WriteCommandError>
+    where
+        C: Into<Command>,
+    {
+        let mut buf = vec![];
+        self.frame_renderer.render(command, &mut buf)?;
+
+        self.sender
+            .send(buf)
+            .await
+            .map_err(|_| IoError::new(IoErrorKind::BrokenPipe, "writer closed"))?;
+
+        Ok(())
+    }
+}
+
+struct FrameReader {
+    frame_parser: FrameParser,
+    buf: Vec<u8>,
+    buf_n_read: usize,
+    buf_n_parsed: usize,
+}
+
+impl Default for FrameReader {
+    fn default() -> Self {
+        Self {
+            frame_parser: FrameParser::default(),
+            buf: vec![0; 5 * 1024 * 1024],
+            buf_n_read: 0,
+            buf_n_parsed: 0,
+        }
+    }
+}
+
+impl FrameReader {
+    fn reserve_buf(&mut self) {
+        if self.buf_n_read < self.buf.len() {
+            return;
+        }
+
+        let buf_n_parsed = self.buf_n_parsed;
+        if buf_n_parsed > 0 {
+            self.buf.rotate_left(buf_n_parsed);
+            self.buf_n_read -= buf_n_parsed;
+            self.buf_n_parsed = 0;
+        } else {
+            let len = self.buf.len();
+            self.buf.resize(len * 2, 0);
+        }
+    }
+
+    fn parse_commands(
+        &mut self,
+        max_size: impl Into<Option<usize>>,
+    ) -> Result<Vec<CommandWithParsed>, ReadCommandError> {
         let max_size = max_size.into();
 
         let mut commands = vec![];
         loop {
-            match self.frame_parser.parse(
-                &self.frame_parser_buf
-                    [self.frame_parser_buf_n_parsed..self.frame_parser_buf_n_read],
-            )? {
+            match self
+                .frame_parser
+                .parse(&self.buf[self.buf_n_parsed..self.buf_n_read])?
+            {
                 FrameParseOutput::Completed(n, command) => {
-                    self.frame_parser_buf_n_parsed += n;
+                    self.buf_n_parsed += n;
 
-                    let frame_parser_buf_n_parsed = self.frame_parser_buf_n_parsed;
-                    self.frame_parser_buf.rotate_left(frame_parser_buf_n_parsed);
-                    self.frame_parser_buf_n_read -= frame_parser_buf_n_parsed;
-                    self.frame_parser_buf_n_parsed = 0;
+                    let buf_n_parsed = self.buf_n_parsed;
+                    self.buf.rotate_left(buf_n_parsed);
+                    self.buf_n_read -= buf_n_parsed;
+                    self.buf_n_parsed = 0;
 
                     commands.push(command);
 
                     if let Some(max_size) = max_size {
                         if max(max_size, 1) >= commands.len() {
-                            return Ok(Some(commands));
+                            return Ok(commands);
                         }
                     }
 
                     continue;
                 }
                 FrameParseOutput::Partial(n) => {
-                    self.frame_parser_buf_n_parsed += n;
+                    self.buf_n_parsed += n;
 
                     if let Some(total_size) = self.frame_parser.get_total_size() {
-                        if self.frame_parser_buf.len() < total_size as usize {
-                            self.frame_parser_buf.resize(total_size as usize, 0)
+                        if self.buf.len() < total_size as usize {
+                            self.buf.resize(total_size as usize, 0)
                         }
                     }
 
@@ -135,10 +533,6 @@ where
             }
         }
 
-        if commands.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(commands))
-        }
+        Ok(commands)
     }
 }
@@ -5,32 +5,85 @@ use pulsar_binary_protocol_spec::{
     types::ConsumerId, SubscribeCommand, SuccessCommand,
 };
 
+use crate::sync_wrapper::SyncWrapper;
+
 mod get_message;
+mod multi_topics_consumer;
 mod raw_ack;
 mod raw_flow;
 mod raw_redeliver_unacknowledged_messages;
+mod receive_message;
+
+pub use multi_topics_consumer::{AsyncMultiTopicsConsumer, MultiTopicsConsumerError};
+pub use raw_ack::RawAckError;
+pub use raw_redeliver_unacknowledged_messages::RawRedeliverUnacknowledgedMessagesError;
+pub use receive_message::ReceiveMessageError;
+
+use receive_message::ConsumerStreamState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsumerCorruptedMessagePolicy {
+    // The message is acked with the validation error and skipped.
+    Discard,
+    // The Stream yields CorruptedMessage, the message is left to the caller.
+    Fail,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct AsyncConsumerConfig {
+    corrupted_message_policy: Option<ConsumerCorruptedMessagePolicy>,
+}
+impl AsyncConsumerConfig {
+    // For the messages of the Stream, with a checksum mismatch or a payload that cannot be read.
+    pub fn set_corrupted_message_policy(
+        &mut self,
+        policy: ConsumerCorruptedMessagePolicy,
+    ) -> &mut Self {
+        self.corrupted_message_policy = Some(policy);
+        self
+    }
+    fn get_corrupted_message_policy(&self) -> ConsumerCorruptedMessagePolicy {
+        self.corrupted_message_policy
+            .unwrap_or(ConsumerCorruptedMessagePolicy::Discard)
+    }
+}
 
 pub struct AsyncConsumer {
     sender: AC_Sender<ConsumerSendHandlerChannelMessage>,
     subscribe_command: SubscribeCommand,
     success_command: SuccessCommand,
+    //
+    corrupted_message_policy: ConsumerCorruptedMessagePolicy,
+    stream_state: SyncWrapper<ConsumerStreamState>,
 }
 impl AsyncConsumer {
     pub(crate) fn new(
         sender: AC_Sender<ConsumerSendHandlerChannelMessage>,
         subscribe_command: SubscribeCommand,
         success_command: SuccessCommand,
+        config: AsyncConsumerConfig,
     ) -> Self {
         Self {
             sender,
             subscribe_command,
             success_command,
+            corrupted_message_policy: config.get_corrupted_message_policy(),
+            stream_state: SyncWrapper::new(ConsumerStreamState::default()),
         }
     }
 
     pub fn get_consumer_id(&self) -> ConsumerId {
         self.subscribe_command.get_consumer_id()
     }
+
+    pub fn get_topic(&self) -> &str {
+        self.subscribe_command.get_topic()
+    }
+
+    // True once the connection is lost or the handler is dropped.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
 }
 
 impl fmt::Debug for AsyncConsumer {
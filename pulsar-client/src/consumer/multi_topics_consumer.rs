@@ -0,0 +1,243 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll},
+};
+
+use futures_util::stream::{Stream, StreamExt as _};
+use pulsar_binary_protocol_spec::{
+    types::{ConsumerId, Message, MessageIdData},
+    AckCommand, RedeliverUnacknowledgedMessagesCommand,
+};
+use thiserror::Error;
+
+use super::{
+    AsyncConsumer, RawAckError, RawRedeliverUnacknowledgedMessagesError, ReceiveMessageError,
+};
+
+#[derive(Error, Debug)]
+pub enum MultiTopicsConsumerError {
+    #[error("ConsumerNotFound {0:?} {1:?}")]
+    ConsumerNotFound(String, ConsumerId),
+    #[error("RawAckError {0:?}")]
+    RawAckError(#[from] RawAckError),
+    #[error("RawRedeliverUnacknowledgedMessagesError {0:?}")]
+    RawRedeliverUnacknowledgedMessagesError(#[from] RawRedeliverUnacknowledgedMessagesError),
+}
+
+// Index of the consumer polled first, and the consumers whose Stream ended.
+struct MultiTopicsConsumerStreamState {
+    next: usize,
+    terminated: Vec<bool>,
+}
+
+// The acked entries of each batch, by topic, consumer id, ledger id and entry id.
+type BatchAcks = BTreeMap<(String, ConsumerId, u64, u64), Vec<bool>>;
+
+// One consumer per topic, with the same subscription. The consumer ids are unique per
+// connection only, so the consumer of a message is found by its topic and consumer id.
+pub struct AsyncMultiTopicsConsumer {
+    consumers: Vec<AsyncConsumer>,
+    stream_state: MultiTopicsConsumerStreamState,
+    batch_acks: Mutex<BatchAcks>,
+}
+impl AsyncMultiTopicsConsumer {
+    pub fn new(consumers: Vec<AsyncConsumer>) -> Self {
+        let terminated = vec![false; consumers.len()];
+        Self {
+            consumers,
+            stream_state: MultiTopicsConsumerStreamState {
+                next: 0,
+                terminated,
+            },
+            batch_acks: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn add_consumer(&mut self, consumer: AsyncConsumer) -> &mut Self {
+        self.stream_state.terminated.push(false);
+        self.consumers.push(consumer);
+        self
+    }
+
+    // Dropping the removed consumer closes it on the broker.
+    pub fn remove_consumer(&mut self, topic: &str) -> Option<AsyncConsumer> {
+        let index = self.consumers.iter().position(|c| c.get_topic() == topic)?;
+
+        let stream_state = &mut self.stream_state;
+        stream_state.terminated.remove(index);
+        if stream_state.next > index {
+            stream_state.next -= 1;
+        }
+        if stream_state.next >= stream_state.terminated.len() {
+            stream_state.next = 0;
+        }
+
+        self.batch_acks
+            .get_mut()
+            .expect("poisoned")
+            .retain(|(t, _, _, _), _| t != topic);
+
+        Some(self.consumers.remove(index))
+    }
+
+    pub fn get_consumers(&self) -> &[AsyncConsumer] {
+        &self.consumers
+    }
+
+    pub fn get_consumer(&self, topic: &str, consumer_id: &ConsumerId) -> Option<&AsyncConsumer> {
+        self.consumers
+            .iter()
+            .find(|c| c.get_topic() == topic && &c.get_consumer_id() == consumer_id)
+    }
+
+    pub fn get_topics(&self) -> Vec<&str> {
+        self.consumers.iter().map(|c| c.get_topic()).collect()
+    }
+
+    // True once every consumer is closed.
+    pub fn is_closed(&self) -> bool {
+        self.consumers.iter().all(|c| c.is_closed())
+    }
+
+    pub async fn raw_ack(
+        &self,
+        topic: &str,
+        consumer_id: &ConsumerId,
+        ack_command: AckCommand,
+    ) -> Result<(), MultiTopicsConsumerError> {
+        let consumer = self.get_consumer_or_err(topic, consumer_id)?;
+        consumer.raw_ack(ack_command).await?;
+        Ok(())
+    }
+
+    // The broker acks the whole entry of a batch, so it is acked once every message of the
+    // batch is acked, like the BatchMessageAcker of the Java client.
+    pub async fn ack(&self, message: &Message) -> Result<(), MultiTopicsConsumerError> {
+        let message_id = message.get_message_id();
+        let message_id = match (message_id.get_batch_index(), message_id.get_batch_size()) {
+            (Some(batch_index), Some(batch_size)) => {
+                if !self.ack_batch_index(message, batch_index, batch_size) {
+                    return Ok(());
+                }
+
+                let mut entry_id =
+                    MessageIdData::new(message_id.get_ledger_id(), message_id.get_entry_id());
+                if let Some(partition) = message_id.get_partition() {
+                    entry_id.set_partition(partition);
+                }
+                entry_id
+            }
+            _ => message_id.to_owned(),
+        };
+
+        let ack_command = AckCommand::individual(&[message_id], None);
+        self.raw_ack(message.get_topic(), &message.get_consumer_id(), ack_command)
+            .await
+    }
+
+    // Grouped by consumer, one command per consumer.
+    pub async fn redeliver_unacknowledged_messages(
+        &self,
+        messages: &[Message],
+    ) -> Result<(), MultiTopicsConsumerError> {
+        let mut message_ids_by_consumer: BTreeMap<(&str, ConsumerId), Vec<MessageIdData>> =
+            BTreeMap::new();
+        for message in messages {
+            message_ids_by_consumer
+                .entry((message.get_topic(), message.get_consumer_id()))
+                .or_default()
+                .push(message.get_message_id().to_owned());
+        }
+
+        for ((topic, consumer_id), message_ids) in message_ids_by_consumer {
+            let consumer = self.get_consumer_or_err(topic, &consumer_id)?;
+            consumer
+                .raw_redeliver_unacknowledged_messages(RedeliverUnacknowledgedMessagesCommand::new(
+                    &message_ids,
+                ))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // Returns true once every message of the batch is acked.
+    fn ack_batch_index(&self, message: &Message, batch_index: u32, batch_size: u32) -> bool {
+        let message_id = message.get_message_id();
+        let key = (
+            message.get_topic().to_owned(),
+            message.get_consumer_id(),
+            message_id.get_ledger_id(),
+            message_id.get_entry_id(),
+        );
+
+        let mut batch_acks = self.batch_acks.lock().expect("poisoned");
+        let acked = batch_acks
+            .entry(key.to_owned())
+            .or_insert_with(|| vec![false; batch_size as usize]);
+        if let Some(x) = acked.get_mut(batch_index as usize) {
+            *x = true;
+        }
+        if acked.iter().all(|x| *x) {
+            batch_acks.remove(&key);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn get_consumer_or_err(
+        &self,
+        topic: &str,
+        consumer_id: &ConsumerId,
+    ) -> Result<&AsyncConsumer, MultiTopicsConsumerError> {
+        self.get_consumer(topic, consumer_id).ok_or_else(|| {
+            MultiTopicsConsumerError::ConsumerNotFound(topic.to_owned(), consumer_id.to_owned())
+        })
+    }
+}
+
+// The consumers are polled in turn, starting after the one that yielded last,
+// so that a busy topic does not starve the others. Ends once every consumer ended.
+impl Stream for AsyncMultiTopicsConsumer {
+    type Item = Result<Message, ReceiveMessageError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let stream_state = &mut this.stream_state;
+        let n = this.consumers.len();
+
+        for i in 0..n {
+            let index = (stream_state.next + i) % n;
+            if stream_state.terminated[index] {
+                continue;
+            }
+
+            match this.consumers[index].poll_next_unpin(cx) {
+                Poll::Ready(Some(item)) => {
+                    stream_state.next = (index + 1) % n;
+                    return Poll::Ready(Some(item));
+                }
+                Poll::Ready(None) => stream_state.terminated[index] = true,
+                Poll::Pending => {}
+            }
+        }
+
+        if stream_state.terminated.iter().all(|x| *x) {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl fmt::Debug for AsyncMultiTopicsConsumer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncMultiTopicsConsumer")
+            .field("consumers", &self.consumers)
+            .finish()
+    }
+}
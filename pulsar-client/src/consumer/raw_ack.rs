@@ -1,4 +1,5 @@
 use pulsar_binary_protocol_spec::{
+    client_channel::AC_Sender,
     client_channel_messages::{
         handler_reply_consumer_channel_message::HandlerReplyConsumerAckChannelMessage,
         ConsumerSendHandlerChannelMessage,
@@ -22,17 +23,24 @@ pub enum RawAckError {
 }
 impl AsyncConsumer {
     pub async fn raw_ack(&self, ack_command: AckCommand) -> Result<(), RawAckError> {
-        let (sender, receiver) = channel::<HandlerReplyConsumerAckChannelMessage>();
+        raw_ack(&self.sender, ack_command).await
+    }
+}
+
+pub(super) async fn raw_ack(
+    sender: &AC_Sender<ConsumerSendHandlerChannelMessage>,
+    ack_command: AckCommand,
+) -> Result<(), RawAckError> {
+    let (s, r) = channel::<HandlerReplyConsumerAckChannelMessage>();
 
-        self.sender
-            .send(ConsumerSendHandlerChannelMessage::Ack(ack_command, sender))
-            .await
-            .map_err(|_| RawAckError::ConsumerChannelClosed)?;
+    sender
+        .send(ConsumerSendHandlerChannelMessage::Ack(ack_command, s))
+        .await
+        .map_err(|_| RawAckError::ConsumerChannelClosed)?;
 
-        match receiver.await {
-            Ok(Ok(_)) => Ok(()),
-            Ok(Err(err)) => Err(RawAckError::RespondError(err)),
-            Err(_) => Err(RawAckError::ChannelClosed),
-        }
+    match r.await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(err)) => Err(RawAckError::RespondError(err)),
+        Err(_) => Err(RawAckError::ChannelClosed),
     }
 }
@@ -0,0 +1,154 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{
+    future::{BoxFuture, FutureExt as _},
+    stream::Stream,
+};
+use log::warn;
+use pulsar_binary_protocol_spec::{
+    client_channel::AC_Sender,
+    client_channel_messages::{
+        handler_reply_consumer_channel_message::HandlerReplyConsumerReceiveMessageChannelMessage,
+        ConsumerSendHandlerChannelMessage,
+    },
+    futures_channel::oneshot::channel,
+    types::{AckValidationError, Message, MessageIdData},
+    AckCommand, MessageCommand,
+};
+use thiserror::Error;
+
+use super::{raw_ack::raw_ack, AsyncConsumer, ConsumerCorruptedMessagePolicy};
+
+#[derive(Error, Debug)]
+pub enum ReceiveMessageError {
+    #[error("ConsumerChannelClosed")]
+    ConsumerChannelClosed,
+    #[error("ChannelClosed")]
+    ChannelClosed,
+    #[error("CorruptedMessage {0:?} {1:?}")]
+    CorruptedMessage(MessageIdData, AckValidationError),
+}
+
+#[derive(Default)]
+pub(super) struct ConsumerStreamState {
+    receiving: Option<BoxFuture<'static, Result<Vec<Message>, ReceiveMessageError>>>,
+    messages: VecDeque<Message>,
+}
+
+impl AsyncConsumer {
+    // Waits for the next message, the permits are granted by the handler
+    // if the receiver queue size of the SubscribeCommand is set.
+    pub async fn receive_message(&self) -> Result<MessageCommand, ReceiveMessageError> {
+        receive_message(self.sender.to_owned()).await
+    }
+}
+
+async fn receive_message(
+    sender: AC_Sender<ConsumerSendHandlerChannelMessage>,
+) -> Result<MessageCommand, ReceiveMessageError> {
+    let (s, r) = channel::<HandlerReplyConsumerReceiveMessageChannelMessage>();
+
+    sender
+        .send(ConsumerSendHandlerChannelMessage::ReceiveMessage(s))
+        .await
+        .map_err(|_| ReceiveMessageError::ConsumerChannelClosed)?;
+
+    r.await.map_err(|_| ReceiveMessageError::ChannelClosed)
+}
+
+// Receives until a message with one entry at least, the corrupted ones are handled by the policy.
+async fn receive_messages(
+    sender: AC_Sender<ConsumerSendHandlerChannelMessage>,
+    topic: String,
+    corrupted_message_policy: ConsumerCorruptedMessagePolicy,
+) -> Result<Vec<Message>, ReceiveMessageError> {
+    loop {
+        let message_command = receive_message(sender.to_owned()).await?;
+
+        let validation_error = match message_command.to_messages(&topic) {
+            Ok(messages) if messages.is_empty() => continue,
+            Ok(messages) => return Ok(messages),
+            Err(validation_error) => validation_error,
+        };
+        let message_id = match message_command.get_message_id() {
+            Some(message_id) => message_id,
+            None => continue,
+        };
+
+        match corrupted_message_policy {
+            ConsumerCorruptedMessagePolicy::Discard => {
+                warn!("discard {:?} {:?}", message_id, validation_error);
+
+                let ack_command = AckCommand::individual(&[message_id], validation_error);
+                if let Err(err) = raw_ack(&sender, ack_command).await {
+                    warn!("ack the corrupted message failed {:?}", err);
+                }
+            }
+            ConsumerCorruptedMessagePolicy::Fail => {
+                return Err(ReceiveMessageError::CorruptedMessage(
+                    message_id,
+                    validation_error,
+                ))
+            }
+        }
+    }
+}
+
+// Yields the entries of the batches one by one, ends once the connection is lost
+// or the handler is dropped.
+impl Stream for AsyncConsumer {
+    type Item = Result<Message, ReceiveMessageError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let sender = &this.sender;
+        let stream_state = this.stream_state.get_mut();
+
+        if let Some(message) = stream_state.messages.pop_front() {
+            return Poll::Ready(Some(Ok(message)));
+        }
+
+        if stream_state.receiving.is_none() {
+            if sender.is_closed() {
+                return Poll::Ready(None);
+            }
+            stream_state.receiving = Some(
+                receive_messages(
+                    sender.to_owned(),
+                    this.subscribe_command.get_topic().to_owned(),
+                    this.corrupted_message_policy,
+                )
+                .boxed(),
+            );
+        }
+
+        let res = match stream_state
+            .receiving
+            .as_mut()
+            .expect("receiving")
+            .poll_unpin(cx)
+        {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+        stream_state.receiving = None;
+
+        match res {
+            Ok(messages) => {
+                stream_state.messages.extend(messages);
+                Poll::Ready(stream_state.messages.pop_front().map(Ok))
+            }
+            Err(ReceiveMessageError::ConsumerChannelClosed)
+            | Err(ReceiveMessageError::ChannelClosed)
+                if sender.is_closed() =>
+            {
+                Poll::Ready(None)
+            }
+            Err(err) => Poll::Ready(Some(Err(err))),
+        }
+    }
+}
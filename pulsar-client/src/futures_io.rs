@@ -1,11 +1,26 @@
+use std::time::Duration;
+
+use futures_timer::Delay;
+use futures_util::io::{ReadHalf, WriteHalf};
 use futures_x_io_timeoutable::{
     futures_io::rw::AsyncReadWithTimeoutExt,
     futures_x_io::{
         futures_io::{AsyncRead, AsyncWrite},
-        futures_util_io::AsyncWriteExt,
+        futures_util_io::{AsyncReadExt, AsyncWriteExt},
     },
 };
 
+fn split<S>(stream: S) -> (ReadHalf<S>, WriteHalf<S>)
+where
+    S: AsyncRead + AsyncWrite,
+{
+    AsyncReadExt::split(stream)
+}
+
+async fn sleep(dur: Duration) {
+    Delay::new(dur).await
+}
+
 #[path = "connection.rs"]
 pub mod connection;
 
@@ -14,3 +29,14 @@ pub mod client;
 
 #[path = "handler/mod.rs"]
 pub mod handler;
+
+#[path = "pool.rs"]
+pub mod pool;
+
+#[cfg(feature = "pattern_consumer")]
+#[path = "pattern_consumer.rs"]
+pub mod pattern_consumer;
+
+#[cfg(feature = "http_lookup")]
+#[path = "http_lookup.rs"]
+pub mod http_lookup;
@@ -0,0 +1,81 @@
+use futures_util::{
+    future,
+    stream::{self, BoxStream, SelectAll},
+    StreamExt as _,
+};
+use pulsar_binary_protocol_spec::{
+    client_channel::AC_Receiver,
+    client_channel_messages::{
+        ConsumerSendHandlerChannelMessage, ProducerSendHandlerChannelMessage,
+        SessionSendHandlerChannelMessage,
+    },
+    command::CommandWithParsed,
+    types::{ConsumerId, ProducerId},
+};
+
+// None means the channel is closed.
+pub(super) enum HandlerChannelMessage {
+    Commands(Option<Vec<CommandWithParsed>>),
+    Session(Option<Box<SessionSendHandlerChannelMessage>>),
+    Producer(ProducerId, Option<Box<ProducerSendHandlerChannelMessage>>),
+    Consumer(ConsumerId, Option<Box<ConsumerSendHandlerChannelMessage>>),
+}
+
+#[derive(Default)]
+pub(super) struct HandlerChannelMessages(SelectAll<BoxStream<'static, HandlerChannelMessage>>);
+impl HandlerChannelMessages {
+    pub(super) fn add_commands(&mut self, receiver: &AC_Receiver<Vec<CommandWithParsed>>) {
+        self.0.push(
+            with_closed(receiver.to_owned())
+                .map(HandlerChannelMessage::Commands)
+                .boxed(),
+        );
+    }
+
+    pub(super) fn add_session(&mut self, receiver: &AC_Receiver<SessionSendHandlerChannelMessage>) {
+        self.0.push(
+            with_closed(receiver.to_owned())
+                .map(|msg| HandlerChannelMessage::Session(msg.map(Box::new)))
+                .boxed(),
+        );
+    }
+
+    pub(super) fn add_producer(
+        &mut self,
+        producer_id: ProducerId,
+        receiver: &AC_Receiver<ProducerSendHandlerChannelMessage>,
+    ) {
+        self.0.push(
+            with_closed(receiver.to_owned())
+                .map(move |msg| {
+                    HandlerChannelMessage::Producer(producer_id.to_owned(), msg.map(Box::new))
+                })
+                .boxed(),
+        );
+    }
+
+    pub(super) fn add_consumer(
+        &mut self,
+        consumer_id: ConsumerId,
+        receiver: &AC_Receiver<ConsumerSendHandlerChannelMessage>,
+    ) {
+        self.0.push(
+            with_closed(receiver.to_owned())
+                .map(move |msg| {
+                    HandlerChannelMessage::Consumer(consumer_id.to_owned(), msg.map(Box::new))
+                })
+                .boxed(),
+        );
+    }
+
+    pub(super) async fn next(&mut self) -> Option<HandlerChannelMessage> {
+        self.0.next().await
+    }
+}
+
+fn with_closed<T>(receiver: AC_Receiver<T>) -> impl stream::Stream<Item = Option<T>>
+where
+    T: Send + 'static,
+{
+    receiver.map(Some).chain(stream::once(future::ready(None)))
+}
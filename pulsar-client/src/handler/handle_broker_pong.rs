@@ -3,7 +3,7 @@ use pulsar_binary_protocol_spec::PongCommand;
 use super::HandleError;
 
 pub(super) fn handle_broker_pong(_pong_command: PongCommand) -> Result<(), HandleError> {
-    // TODO
+    // The keepalive of the connection already saw the bytes of the PONG.
 
     Ok(())
 }
@@ -1,16 +1,33 @@
 use log::error;
 use pulsar_binary_protocol_spec::{client_handler::PendingMessages, MessageCommand};
 
+use super::ReceivingConsumers;
+
+// Returns true if the message was taken by a waiting receive.
 pub(super) fn handle_broker_push_message(
     message_command: MessageCommand,
     pending_messages: &mut PendingMessages,
-) {
+    receiving_consumers: &mut ReceivingConsumers,
+) -> bool {
     let consumer_id = message_command.get_consumer_id();
 
+    let message_command = match receiving_consumers.remove(&consumer_id) {
+        Some(sender) => match sender.send(message_command) {
+            Ok(_) => return true,
+            Err(message_command) => message_command,
+        },
+        None => message_command,
+    };
+
     if let Some(pending_message_value) = pending_messages.get_mut(&consumer_id) {
         pending_message_value.push(message_command);
     } else {
-        error!("not init consumer_id {:?}", consumer_id);
-        unreachable!();
+        // Pushed before the broker closed the dropped consumer.
+        error!(
+            "consumer_id {:?} not found, the message is dropped",
+            consumer_id
+        );
     }
+
+    false
 }
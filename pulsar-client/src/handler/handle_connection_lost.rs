@@ -0,0 +1,180 @@
+use std::mem;
+
+use pulsar_binary_protocol_spec::{
+    client_channel::{
+        handler_channel_storage::{ConsumerReceiver, ProducerReceiver, SessionReceiver},
+        HandlerChannelStorage, HandlerChannelStorageItem,
+    },
+    client_channel_messages::{
+        ConsumerSendHandlerChannelMessage, ProducerSendHandlerChannelMessage,
+        SessionSendHandlerChannelMessage,
+    },
+    client_half_requests::{
+        ConsumerFlowHalfRequestError, ConsumerRedeliverUnacknowledgedMessagesHalfRequestError,
+    },
+    client_handler::{
+        PendingRequestValue, PendingRequests, PendingSequenceValue, PendingSequences,
+        WriteCommandError,
+    },
+    client_responds::{
+        ConsumerAckRespondError, ProducerSendRespondError, SessionCreateConsumerRespondError,
+        SessionCreateProducerRespondError, SessionGetTopicsOfNamespaceRespondError,
+        SessionLookupTopicRespondError, SessionPartitionedTopicMetadataRespondError,
+    },
+};
+
+// Fails everything that waits for the broker, and closes the channels so that the session,
+// producers and consumers see the disconnect.
+pub(super) fn handle_connection_lost(
+    channel_storage: &HandlerChannelStorage,
+    pending_requests: &mut PendingRequests,
+    pending_sequences: &mut PendingSequences,
+) {
+    fail_pending_requests(pending_requests);
+
+    for (_, pending_sequence) in mem::take(pending_sequences) {
+        fail_pending_sequence(pending_sequence);
+    }
+
+    for item in channel_storage.items() {
+        match item {
+            HandlerChannelStorageItem::Session(r) => close_session(r),
+            HandlerChannelStorageItem::Producer(_, _, r) => close_producer(r),
+            HandlerChannelStorageItem::Consumer(_, r) => close_consumer(r),
+        }
+    }
+}
+
+pub(super) fn fail_pending_requests(pending_requests: &mut PendingRequests) {
+    for (_, pending_request) in mem::take(pending_requests) {
+        fail_pending_request(pending_request, None);
+    }
+}
+
+// With the error of its write, or ConnectionLost.
+pub(super) fn fail_pending_request(
+    pending_request: PendingRequestValue,
+    err: Option<WriteCommandError>,
+) {
+    match pending_request {
+        PendingRequestValue::SessionCreateProducer(_, s) => {
+            let _ = s.send(Err(respond_error(
+                err,
+                SessionCreateProducerRespondError::ConnectionLost,
+            )));
+        }
+        PendingRequestValue::SessionCreateConsumer(_, s) => {
+            let _ = s.send(Err(respond_error(
+                err,
+                SessionCreateConsumerRespondError::ConnectionLost,
+            )));
+        }
+        PendingRequestValue::SessionLookupTopic(_, s) => {
+            let _ = s.send(Err(respond_error(
+                err,
+                SessionLookupTopicRespondError::ConnectionLost,
+            )));
+        }
+        PendingRequestValue::SessionPartitionedTopicMetadata(_, s) => {
+            let _ = s.send(Err(respond_error(
+                err,
+                SessionPartitionedTopicMetadataRespondError::ConnectionLost,
+            )));
+        }
+        PendingRequestValue::SessionGetTopicsOfNamespace(_, s) => {
+            let _ = s.send(Err(respond_error(
+                err,
+                SessionGetTopicsOfNamespaceRespondError::ConnectionLost,
+            )));
+        }
+        PendingRequestValue::ConsumerAck(s) => {
+            let _ = s.send(Err(respond_error(
+                err,
+                ConsumerAckRespondError::ConnectionLost,
+            )));
+        }
+        PendingRequestValue::ProducerReconnect(_)
+        | PendingRequestValue::ConsumerReconnect(_)
+        | PendingRequestValue::ConsumerClose(_)
+        | PendingRequestValue::ProducerClose(_) => {}
+    }
+}
+
+fn respond_error<E>(err: Option<WriteCommandError>, connection_lost: E) -> E
+where
+    E: From<WriteCommandError>,
+{
+    match err {
+        Some(WriteCommandError::FrameRenderError(err)) => {
+            WriteCommandError::FrameRenderError(err).into()
+        }
+        // The writer stops with the connection.
+        Some(WriteCommandError::WriteError(_)) | None => connection_lost,
+    }
+}
+
+pub(super) fn fail_pending_sequence(pending_sequence: PendingSequenceValue) {
+    let _ = pending_sequence
+        .sender
+        .send(Err(ProducerSendRespondError::ConnectionLost));
+}
+
+fn close_session(r: &SessionReceiver) {
+    r.close();
+    while let Ok(msg) = r.try_recv() {
+        match msg {
+            SessionSendHandlerChannelMessage::CreateProducer(_, s) => {
+                let _ = s.send(Err(SessionCreateProducerRespondError::ConnectionLost));
+            }
+            SessionSendHandlerChannelMessage::CreateConsumer(_, s) => {
+                let _ = s.send(Err(SessionCreateConsumerRespondError::ConnectionLost));
+            }
+            SessionSendHandlerChannelMessage::LookupTopic(_, s) => {
+                let _ = s.send(Err(SessionLookupTopicRespondError::ConnectionLost));
+            }
+            SessionSendHandlerChannelMessage::PartitionedTopicMetadata(_, s) => {
+                let _ = s.send(Err(
+                    SessionPartitionedTopicMetadataRespondError::ConnectionLost,
+                ));
+            }
+            SessionSendHandlerChannelMessage::GetTopicsOfNamespace(_, s) => {
+                let _ = s.send(Err(SessionGetTopicsOfNamespaceRespondError::ConnectionLost));
+            }
+        }
+    }
+}
+
+pub(super) fn close_producer(r: &ProducerReceiver) {
+    r.close();
+    while let Ok(msg) = r.try_recv() {
+        match msg {
+            ProducerSendHandlerChannelMessage::Send(_, s, _) => {
+                let _ = s.send(Err(ProducerSendRespondError::ConnectionLost));
+            }
+        }
+    }
+}
+
+pub(super) fn close_consumer(r: &ConsumerReceiver) {
+    r.close();
+    while let Ok(msg) = r.try_recv() {
+        match msg {
+            ConsumerSendHandlerChannelMessage::Flow(_, s) => {
+                let _ = s.send(Err(ConsumerFlowHalfRequestError::ConnectionLost));
+            }
+            ConsumerSendHandlerChannelMessage::GetMessage(s) => {
+                let _ = s.send(None);
+            }
+            // Dropping the sender closes the receive.
+            ConsumerSendHandlerChannelMessage::ReceiveMessage(_) => {}
+            ConsumerSendHandlerChannelMessage::Ack(_, s) => {
+                let _ = s.send(Err(ConsumerAckRespondError::ConnectionLost));
+            }
+            ConsumerSendHandlerChannelMessage::RedeliverUnacknowledgedMessages(_, s) => {
+                let _ = s.send(Err(
+                    ConsumerRedeliverUnacknowledgedMessagesHalfRequestError::ConnectionLost,
+                ));
+            }
+        }
+    }
+}
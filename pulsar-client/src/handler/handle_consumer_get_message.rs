@@ -5,22 +5,29 @@ use pulsar_binary_protocol_spec::{
     client_handler::PendingMessages, types::ConsumerId,
 };
 
+// Returns the number of the messages in the taken batch, if a message was taken.
 pub(super) fn handle_consumer_get_message(
     consumer_id: ConsumerId,
     sender: FC_Sender<HandlerReplyConsumerGetMessageChannelMessage>,
     pending_messages: &mut PendingMessages,
-) {
+) -> Option<u32> {
     if let Some(pending_message_value) = pending_messages.get_mut(&consumer_id) {
         if !pending_message_value.is_empty() {
             let message_command = pending_message_value.remove(0);
+            let n = message_command
+                .get_message_metadata()
+                .get_num_messages_in_batch();
 
             match sender.send(Some(message_command)) {
-                Ok(_) => {}
+                Ok(_) => Some(n),
                 Err(message_command) => {
                     pending_message_value.push(message_command.unwrap());
+                    None
                 }
             }
-        } else if sender.send(None).is_ok() {
+        } else {
+            let _ = sender.send(None);
+            None
         }
     } else {
         error!("not init consumer_id {:?}", consumer_id);
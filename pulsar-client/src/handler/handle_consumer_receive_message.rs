@@ -0,0 +1,35 @@
+use pulsar_binary_protocol_spec::{
+    client_channel::FC_Sender,
+    client_channel_messages::handler_reply_consumer_channel_message::HandlerReplyConsumerReceiveMessageChannelMessage,
+    client_handler::PendingMessages, types::ConsumerId,
+};
+
+use super::ReceivingConsumers;
+
+// Replies with the oldest pending message, or waits for the next pushed one.
+// Returns the number of the messages in the taken batch, if a message was taken.
+pub(super) fn handle_consumer_receive_message(
+    consumer_id: ConsumerId,
+    sender: FC_Sender<HandlerReplyConsumerReceiveMessageChannelMessage>,
+    pending_messages: &mut PendingMessages,
+    receiving_consumers: &mut ReceivingConsumers,
+) -> Option<u32> {
+    let pending_message_value = pending_messages.entry(consumer_id.to_owned()).or_default();
+
+    if pending_message_value.is_empty() {
+        receiving_consumers.insert(consumer_id, sender);
+        return None;
+    }
+
+    let message_command = pending_message_value.remove(0);
+    let n = message_command
+        .get_message_metadata()
+        .get_num_messages_in_batch();
+    match sender.send(message_command) {
+        Ok(_) => Some(n),
+        Err(message_command) => {
+            pending_message_value.insert(0, message_command);
+            None
+        }
+    }
+}
@@ -9,7 +9,7 @@ use pulsar_binary_protocol_spec::{
     client_responds::{Respond, SessionCreateConsumerRespond},
 };
 
-use super::HandleError;
+use super::{channel_messages::HandlerChannelMessages, HandleError};
 
 pub(super) fn handle_session_create_consumer(
     consumer_command: <SessionCreateConsumerRespond as Respond>::Request,
@@ -19,12 +19,14 @@ pub(super) fn handle_session_create_consumer(
         <SessionCreateConsumerRespond as Respond>::Error,
     >,
     channel_storage: &mut HandlerChannelStorage,
+    channel_messages: &mut HandlerChannelMessages,
 ) -> Result<(), HandleError> {
     match res {
         Ok(c) => {
             let consumer_id = consumer_command.get_consumer_id();
 
             let (s, r) = bounded::<ConsumerSendHandlerChannelMessage>(10);
+            channel_messages.add_consumer(consumer_id.to_owned(), &r);
             channel_storage.add_consumer(consumer_id, r);
             match sender.send(Ok((consumer_command, c, s))) {
                 Ok(_) => {}
@@ -9,7 +9,7 @@ use pulsar_binary_protocol_spec::{
     client_responds::{Respond, SessionCreateProducerRespond},
 };
 
-use super::HandleError;
+use super::{channel_messages::HandlerChannelMessages, HandleError};
 
 pub(super) fn handle_session_create_producer(
     producer_command: <SessionCreateProducerRespond as Respond>::Request,
@@ -19,12 +19,14 @@ pub(super) fn handle_session_create_producer(
         <SessionCreateProducerRespond as Respond>::Error,
     >,
     channel_storage: &mut HandlerChannelStorage,
+    channel_messages: &mut HandlerChannelMessages,
 ) -> Result<(), HandleError> {
     match res {
         Ok(c) => {
             let producer_id = producer_command.get_producer_id();
 
             let (s, r) = bounded::<ProducerSendHandlerChannelMessage>(10);
+            channel_messages.add_producer(producer_id.to_owned(), &r);
             channel_storage.add_producer(producer_id, c.get_producer_name(), r);
             match sender.send(Ok((producer_command, c, s))) {
                 Ok(_) => {}
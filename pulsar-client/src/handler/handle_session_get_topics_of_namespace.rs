@@ -0,0 +1,26 @@
+use log::error;
+use pulsar_binary_protocol_spec::{
+    client_channel::FC_Sender,
+    client_channel_messages::handler_reply_session_channel_message::HandlerReplySessionGetTopicsOfNamespaceChannelMessage,
+    client_responds::{Respond, SessionGetTopicsOfNamespaceRespond},
+};
+
+use super::HandleError;
+
+pub(super) fn handle_session_get_topics_of_namespace(
+    get_topics_of_namespace_command: <SessionGetTopicsOfNamespaceRespond as Respond>::Request,
+    sender: FC_Sender<HandlerReplySessionGetTopicsOfNamespaceChannelMessage>,
+    res: Result<
+        <SessionGetTopicsOfNamespaceRespond as Respond>::Response,
+        <SessionGetTopicsOfNamespaceRespond as Respond>::Error,
+    >,
+) -> Result<(), HandleError> {
+    match sender.send(res.map(|c| (get_topics_of_namespace_command, c))) {
+        Ok(_) => {}
+        Err(_) => {
+            error!("channel closed");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use log::error;
+use pulsar_binary_protocol_spec::{
+    client_channel::FC_Sender,
+    client_channel_messages::handler_reply_session_channel_message::HandlerReplySessionLookupTopicChannelMessage,
+    client_responds::{Respond, SessionLookupTopicRespond},
+};
+
+use super::HandleError;
+
+pub(super) fn handle_session_lookup_topic(
+    lookup_topic_command: <SessionLookupTopicRespond as Respond>::Request,
+    sender: FC_Sender<HandlerReplySessionLookupTopicChannelMessage>,
+    res: Result<
+        <SessionLookupTopicRespond as Respond>::Response,
+        <SessionLookupTopicRespond as Respond>::Error,
+    >,
+) -> Result<(), HandleError> {
+    match sender.send(res.map(|c| (lookup_topic_command, c))) {
+        Ok(_) => {}
+        Err(_) => {
+            error!("channel closed");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use log::error;
+use pulsar_binary_protocol_spec::{
+    client_channel::FC_Sender,
+    client_channel_messages::handler_reply_session_channel_message::HandlerReplySessionPartitionedTopicMetadataChannelMessage,
+    client_responds::{Respond, SessionPartitionedTopicMetadataRespond},
+};
+
+use super::HandleError;
+
+pub(super) fn handle_session_partitioned_topic_metadata(
+    partitioned_topic_metadata_command: <SessionPartitionedTopicMetadataRespond as Respond>::Request,
+    sender: FC_Sender<HandlerReplySessionPartitionedTopicMetadataChannelMessage>,
+    res: Result<
+        <SessionPartitionedTopicMetadataRespond as Respond>::Response,
+        <SessionPartitionedTopicMetadataRespond as Respond>::Error,
+    >,
+) -> Result<(), HandleError> {
+    match sender.send(res.map(|c| (partitioned_topic_metadata_command, c))) {
+        Ok(_) => {}
+        Err(_) => {
+            error!("channel closed");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std::{collections::VecDeque, time::Instant};
+
+use pulsar_binary_protocol_spec::{
+    client_handler::{PendingRequestValue, PendingRequests, PendingSequences},
+    client_responds::{
+        ConsumerAckRespondError, ProducerSendRespondError, SessionCreateConsumerRespondError,
+        SessionCreateProducerRespondError, SessionGetTopicsOfNamespaceRespondError,
+        SessionLookupTopicRespondError, SessionPartitionedTopicMetadataRespondError,
+    },
+    types::{ProducerId, RequestId, SequenceId},
+};
+
+// Ordered by deadline, the timeouts of one connection are fixed.
+#[derive(Default)]
+pub(super) struct PendingDeadlines {
+    requests: VecDeque<(Instant, RequestId)>,
+    sequences: VecDeque<(Instant, (ProducerId, SequenceId))>,
+}
+
+impl PendingDeadlines {
+    pub(super) fn add_request(&mut self, deadline: Instant, request_id: RequestId) {
+        self.requests.push_back((deadline, request_id));
+    }
+
+    pub(super) fn add_sequence(
+        &mut self,
+        deadline: Instant,
+        producer_id: ProducerId,
+        sequence_id: SequenceId,
+    ) {
+        self.sequences
+            .push_back((deadline, (producer_id, sequence_id)));
+    }
+
+    pub(super) fn next(&self) -> Option<Instant> {
+        let request_deadline = self.requests.front().map(|(deadline, _)| *deadline);
+        let sequence_deadline = self.sequences.front().map(|(deadline, _)| *deadline);
+
+        match (request_deadline, sequence_deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+// Fails the pending requests and sequences whose deadline has passed, the broker may still
+// respond to them later. True if a producer or consumer was not registered again in time,
+// the connection is then closed and reconnected.
+pub(super) fn handle_timeout(
+    pending_deadlines: &mut PendingDeadlines,
+    pending_requests: &mut PendingRequests,
+    pending_sequences: &mut PendingSequences,
+    now: Instant,
+) -> bool {
+    let mut is_reconnect_timeout = false;
+
+    while let Some((deadline, _)) = pending_deadlines.requests.front() {
+        if *deadline > now {
+            break;
+        }
+        let (_, request_id) = pending_deadlines.requests.pop_front().expect("front");
+
+        match pending_requests.remove(&request_id) {
+            Some(PendingRequestValue::SessionCreateProducer(_, s)) => {
+                let _ = s.send(Err(SessionCreateProducerRespondError::Timeout));
+            }
+            Some(PendingRequestValue::SessionCreateConsumer(_, s)) => {
+                let _ = s.send(Err(SessionCreateConsumerRespondError::Timeout));
+            }
+            Some(PendingRequestValue::SessionLookupTopic(_, s)) => {
+                let _ = s.send(Err(SessionLookupTopicRespondError::Timeout));
+            }
+            Some(PendingRequestValue::SessionPartitionedTopicMetadata(_, s)) => {
+                let _ = s.send(Err(SessionPartitionedTopicMetadataRespondError::Timeout));
+            }
+            Some(PendingRequestValue::SessionGetTopicsOfNamespace(_, s)) => {
+                let _ = s.send(Err(SessionGetTopicsOfNamespaceRespondError::Timeout));
+            }
+            Some(PendingRequestValue::ConsumerAck(s)) => {
+                let _ = s.send(Err(ConsumerAckRespondError::Timeout));
+            }
+            Some(PendingRequestValue::ProducerReconnect(_))
+            | Some(PendingRequestValue::ConsumerReconnect(_)) => {
+                is_reconnect_timeout = true;
+            }
+            Some(PendingRequestValue::ConsumerClose(_))
+            | Some(PendingRequestValue::ProducerClose(_)) => {}
+            None => {}
+        }
+    }
+
+    while let Some((deadline, _)) = pending_deadlines.sequences.front() {
+        if *deadline > now {
+            break;
+        }
+        let (_, (producer_id, sequence_id)) =
+            pending_deadlines.sequences.pop_front().expect("front");
+
+        if let Some(pending_sequence) = pending_sequences.remove(&producer_id, &sequence_id) {
+            let _ = pending_sequence
+                .sender
+                .send(Err(ProducerSendRespondError::Timeout));
+        }
+    }
+
+    is_reconnect_timeout
+}
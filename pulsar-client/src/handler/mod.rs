@@ -1,296 +1,1085 @@
-use std::io::ErrorKind as IoErrorKind;
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    io::Error as IoError,
+    marker::PhantomData,
+    mem,
+    sync::Arc,
+    time::Instant,
+};
 
-use log::{error, trace};
+use futures_util::{
+    future::{pending, select, select_all, BoxFuture, Either, Fuse},
+    pin_mut, FutureExt as _,
+};
+use log::{error, trace, warn};
 use pulsar_binary_protocol_spec::{
-    async_channel::TryRecvError,
-    client_channel::{AC_Receiver, HandlerChannelStorage, HandlerChannelStorageItem},
+    client_channel::{AC_Receiver, FC_Sender, HandlerChannelStorage},
     client_channel_messages::{
         consumer_send_handler_channel_message::ConsumerSendHandlerChannelMessageGroup,
         producer_send_handler_channel_message::ProducerSendHandlerChannelMessageGroup,
+        ConsumerSendHandlerChannelMessage, ProducerSendHandlerChannelMessage,
         SessionSendHandlerChannelMessage,
     },
     client_handler::{
-        handle, HandlerHandleOutput, OnResponded, PendingMessages, PendingRequests,
-        PendingSequences, ReadCommandError,
+        handle, HandlerHandleError, HandlerHandleOutput, OnResponded, PendingMessages,
+        PendingRequestValue, PendingRequests, PendingSequences,
     },
-    PongCommand,
+    command::CommandWithParsed,
+    types::{ConsumerId, ProducerId},
+    CloseConsumerCommand, CloseProducerCommand, ConnectCommand, FlowCommand, MessageCommand,
+    PongCommand, ProducerCommand, SubscribeCommand,
 };
 use thiserror::Error;
 
-use super::{connection::AsyncConnection, AsyncRead, AsyncWrite};
+use crate::reconnect::ReconnectConfig;
 
+use super::{
+    client::{connect, RawConnectError},
+    connection::{AsyncConnection, AsyncConnectionHandle, AsyncConnectionTaskError},
+    sleep, AsyncRead, AsyncWrite,
+};
+
+mod channel_messages;
 mod handle_broker_pong;
 mod handle_broker_push_message;
+mod handle_connection_lost;
 mod handle_consumer_ack;
 mod handle_consumer_get_message;
+mod handle_consumer_receive_message;
 mod handle_producer_send;
 mod handle_session_create_consumer;
 mod handle_session_create_producer;
+mod handle_session_get_topics_of_namespace;
+mod handle_session_lookup_topic;
+mod handle_session_partitioned_topic_metadata;
+mod handle_timeout;
+
+use channel_messages::{HandlerChannelMessage, HandlerChannelMessages};
+use handle_timeout::PendingDeadlines;
+
+// The receives that wait for the next pushed message, one per consumer.
+type ReceivingConsumers = HashMap<ConsumerId, FC_Sender<MessageCommand>>;
+
+type ConnectionTask = Fuse<BoxFuture<'static, Result<(), AsyncConnectionTaskError>>>;
+
+type Connecting =
+    BoxFuture<'static, Result<(ConnectionTask, AsyncConnectionHandle), ReconnectError>>;
+
+type Reconnector = Arc<dyn Fn(ConnectCommand) -> Connecting + Send + Sync>;
 
 pub struct AsyncHandler<S> {
-    connection: AsyncConnection<S>,
+    // Reads and writes the split stream, runs concurrently with the handling of events.
+    connection_task: ConnectionTask,
+    inner: AsyncHandlerInner,
+    is_closed: bool,
+    // Not hidden, it is sent again on reconnecting.
+    connect_command: ConnectCommand,
+    reconnect: Option<(Reconnector, ReconnectConfig)>,
+    reconnecting: Option<Reconnecting>,
+    // The stream is owned by the connection task.
+    _stream: PhantomData<fn() -> S>,
+}
+
+// The connection is lost, the next one connects after the backoff delay.
+struct Reconnecting {
+    attempt: usize,
+    // Why the connection was lost.
+    err: AsyncConnectionTaskError,
+    connecting: Connecting,
+}
+
+struct AsyncHandlerInner {
+    connection: AsyncConnectionHandle,
     channel_storage: HandlerChannelStorage,
+    channel_messages: HandlerChannelMessages,
     pending_requests: PendingRequests,
     pending_sequences: PendingSequences,
     pending_messages: PendingMessages,
+    pending_deadlines: PendingDeadlines,
+    // Kept for re-registering after a reconnection.
+    producer_commands: HashMap<ProducerId, ProducerCommand>,
+    subscribe_commands: HashMap<ConsumerId, SubscribeCommand>,
+    consumer_permits: HashMap<ConsumerId, u32>,
+    receiving_consumers: ReceivingConsumers,
+    // The messages taken since the last FLOW, of the consumers with a receiver queue size.
+    consumer_taken_messages: HashMap<ConsumerId, u32>,
+    // Sends are queued until the broker accepts the producer again.
+    reconnecting_producers: HashSet<ProducerId>,
+    // The connection is closed after the current message, e.g. a re-registration timed out.
+    require_close: Option<AsyncConnectionTaskError>,
 }
 
 impl<S> AsyncHandler<S>
 where
-    S: AsyncRead + AsyncWrite + Unpin,
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
 {
     pub(crate) fn new(
         connection: AsyncConnection<S>,
         receiver: AC_Receiver<SessionSendHandlerChannelMessage>,
+        connect_command: ConnectCommand,
     ) -> Self {
+        let (connection_task, connection) = split_connection(connection);
+
+        let mut channel_messages = HandlerChannelMessages::default();
+        channel_messages.add_commands(&connection.commands_receiver);
+        channel_messages.add_session(&receiver);
+
         Self {
-            connection,
-            channel_storage: HandlerChannelStorage::new(receiver),
-            pending_requests: PendingRequests::default(),
-            pending_sequences: PendingSequences::default(),
-            pending_messages: PendingMessages::default(),
+            connection_task,
+            inner: AsyncHandlerInner {
+                connection,
+                channel_storage: HandlerChannelStorage::new(receiver),
+                channel_messages,
+                pending_requests: PendingRequests::default(),
+                pending_sequences: PendingSequences::default(),
+                pending_messages: PendingMessages::default(),
+                pending_deadlines: PendingDeadlines::default(),
+                producer_commands: HashMap::new(),
+                subscribe_commands: HashMap::new(),
+                consumer_permits: HashMap::new(),
+                receiving_consumers: HashMap::new(),
+                consumer_taken_messages: HashMap::new(),
+                reconnecting_producers: HashSet::new(),
+                require_close: None,
+            },
+            is_closed: false,
+            connect_command,
+            reconnect: None,
+            reconnecting: None,
+            _stream: PhantomData,
         }
     }
+
+    // Once set, a lost connection is replaced by a new one from the connector, with backoff.
+    // The producers and consumers are re-registered and the pending sends are resent,
+    // only the pending requests of the session and the acks fail with ConnectionLost.
+    pub fn set_reconnect<F, Fut>(&mut self, connector: F, config: ReconnectConfig) -> &mut Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<AsyncConnection<S>, IoError>> + Send + 'static,
+    {
+        let reconnector = move |connect_command: ConnectCommand| {
+            let connecting = connector();
+            async move {
+                let mut connection = connecting.await?;
+                connect(&mut connection, &connect_command).await?;
+                Ok(split_connection(connection))
+            }
+            .boxed()
+        };
+
+        self.reconnect = Some((Arc::new(reconnector), config));
+        self
+    }
+}
+
+fn split_connection<S>(connection: AsyncConnection<S>) -> (ConnectionTask, AsyncConnectionHandle)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (reader, writer, keepalive, connection) = connection.into_split();
+
+    let connection_task = select_all(vec![
+        reader.run().boxed(),
+        writer.run().boxed(),
+        keepalive.run().boxed(),
+    ])
+    .map(|(res, _, _)| res)
+    .boxed()
+    .fuse();
+
+    (connection_task, connection)
+}
+
+#[derive(Error, Debug)]
+pub enum ReconnectError {
+    #[error("ConnectError {0:?}")]
+    ConnectError(#[from] IoError),
+    #[error("RawConnectError {0:?}")]
+    RawConnectError(#[from] RawConnectError),
+    #[error("RetriesExhausted")]
+    RetriesExhausted,
 }
 
 #[derive(Error, Debug)]
 pub enum HandleError {
-    #[error("ConnectionRequireClose")]
-    ConnectionRequireClose,
+    #[error("ConnectionRequireClose {0:?}")]
+    ConnectionRequireClose(AsyncConnectionTaskError),
+    #[error("ConnectionClosed")]
+    ConnectionClosed,
 }
 
-impl<S> AsyncHandler<S>
-where
-    S: AsyncRead + AsyncWrite + Unpin,
-{
+impl<S> AsyncHandler<S> {
+    // Handles the next command of the connection or the next message of the
+    // session/producer/consumer channels, while the connection keeps reading and writing.
+    // While reconnecting, the messages are still handled and the timeouts still fire.
+    // Once the connection is lost, returns the channels of the session/producers/consumers,
+    // they are closed and everything that waited for the broker has failed with ConnectionLost.
     pub async fn handle(&mut self) -> Result<(), (HandleError, HandlerChannelStorage)> {
-        //
-        let mut channel_storage_del_session = false;
-        let mut channel_storage_del_producer_ids = vec![];
-        let mut channel_storage_del_consumer_ids = vec![];
-        for item in self.channel_storage.items().into_iter() {
-            match item {
-                HandlerChannelStorageItem::Session(r) => match r.try_recv() {
-                    Ok(msg) => {
-                        let ((request_id, pending_request), command) = msg
-                            .into_pending_request_and_command(
-                                &self.connection.request_id_builder,
-                                &self.connection.producer_id_builder,
-                                &self.connection.consumer_id_builder,
-                            );
+        if self.is_closed {
+            return Err((
+                HandleError::ConnectionClosed,
+                HandlerChannelStorage::default(),
+            ));
+        }
+
+        let res = if self.reconnecting.is_some() {
+            self.handle_reconnecting().await
+        } else {
+            match self.handle_next().await {
+                Ok(_) => Ok(()),
+                Err(err) => {
+                    error!("connection lost {:?}", err);
+                    self.start_reconnecting(err)
+                }
+            }
+        };
+        let err = match res {
+            Ok(_) => return Ok(()),
+            Err(err) => err,
+        };
+
+        self.is_closed = true;
+        self.inner.channel_messages = HandlerChannelMessages::default();
+        handle_connection_lost::handle_connection_lost(
+            &self.inner.channel_storage,
+            &mut self.inner.pending_requests,
+            &mut self.inner.pending_sequences,
+        );
+
+        Err((
+            HandleError::ConnectionRequireClose(err),
+            mem::take(&mut self.inner.channel_storage),
+        ))
+    }
+
+    // Only waiting for the next message is raced with the connection task. A dequeued message
+    // is handled to the end, once the connection is lost its writes fail and it is failed
+    // with ConnectionLost or kept for resending.
+    async fn handle_next(&mut self) -> Result<(), AsyncConnectionTaskError> {
+        let msg = {
+            let next_message = self.inner.next_message();
+            pin_mut!(next_message);
+
+            match select(&mut self.connection_task, next_message).await {
+                Either::Left((res, _)) => return Err(connection_task_error(res)),
+                Either::Right((msg, _)) => msg,
+            }
+        };
+
+        {
+            let handle_message = self.inner.handle_message(msg);
+            pin_mut!(handle_message);
+
+            match select(&mut self.connection_task, handle_message).await {
+                Either::Left((res, handle_message)) => {
+                    handle_message.await;
+                    return Err(connection_task_error(res));
+                }
+                Either::Right(((), _)) => {}
+            }
+        }
+
+        match self.inner.require_close.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    // The broker never responds to the requests of the lost connection, they fail at once.
+    // The sends are kept until their producer is accepted again.
+    // Err if there is no reconnect, or no retry.
+    fn start_reconnecting(
+        &mut self,
+        err: AsyncConnectionTaskError,
+    ) -> Result<(), AsyncConnectionTaskError> {
+        let connecting = match self.connect_later(0) {
+            Some(connecting) => connecting,
+            None => return Err(err),
+        };
+
+        // Stops the reader, the writer and the keepalive, the writes fail from now on.
+        self.connection_task = Fuse::terminated();
+        self.inner.require_close = None;
+        handle_connection_lost::fail_pending_requests(&mut self.inner.pending_requests);
+        let producer_ids = self.inner.producer_commands.keys().cloned();
+        self.inner.reconnecting_producers.extend(producer_ids);
+
+        self.reconnecting = Some(Reconnecting {
+            attempt: 0,
+            err,
+            connecting,
+        });
+        Ok(())
+    }
+
+    fn connect_later(&self, attempt: usize) -> Option<Connecting> {
+        let (reconnector, config) = self.reconnect.as_ref()?;
+        if config.is_retries_exhausted(attempt) {
+            error!("reconnect failed {:?}", ReconnectError::RetriesExhausted);
+            return None;
+        }
+
+        let reconnector = reconnector.to_owned();
+        let delay = config.get_delay(attempt);
+        let connect_command = self.connect_command.to_owned();
+        Some(
+            async move {
+                sleep(delay).await;
+                reconnector(connect_command).await
+            }
+            .boxed(),
+        )
+    }
+
+    // Handles the next message, or the new connection once it is connected.
+    // Err with why the connection was lost once the retries are exhausted.
+    async fn handle_reconnecting(&mut self) -> Result<(), AsyncConnectionTaskError> {
+        let reconnecting = self.reconnecting.as_mut().expect("reconnecting");
+        let res = {
+            let next_message = self.inner.next_message();
+            pin_mut!(next_message);
+
+            match select(&mut reconnecting.connecting, next_message).await {
+                Either::Left((res, _)) => Either::Left(res),
+                Either::Right((msg, _)) => Either::Right(msg),
+            }
+        };
+        let res = match res {
+            Either::Left(res) => res,
+            Either::Right(msg) => {
+                self.inner.handle_message(msg).await;
+                // Nothing is registered on the lost connection.
+                self.inner.require_close = None;
+                return Ok(());
+            }
+        };
+
+        let Reconnecting { attempt, err, .. } = self.reconnecting.take().expect("reconnecting");
+        let attempt = attempt + 1;
+
+        let (connection_task, connection) = match res {
+            Ok(v) => v,
+            Err(reconnect_err) => {
+                warn!("reconnect attempt {} failed {:?}", attempt, reconnect_err);
+
+                let connecting = match self.connect_later(attempt) {
+                    Some(connecting) => connecting,
+                    None => return Err(err),
+                };
+                self.reconnecting = Some(Reconnecting {
+                    attempt,
+                    err,
+                    connecting,
+                });
+                return Ok(());
+            }
+        };
+
+        self.set_connection(connection_task, connection);
+
+        let res = {
+            let reregister = self.inner.reregister();
+            pin_mut!(reregister);
+
+            match select(&mut self.connection_task, reregister).await {
+                Either::Left((res, reregister)) => {
+                    reregister.await;
+                    Err(connection_task_error(res))
+                }
+                Either::Right(((), _)) => Ok(()),
+            }
+        };
+
+        match res {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                warn!("reconnect attempt {} lost {:?}", attempt, err);
+                self.start_reconnecting(err)
+            }
+        }
+    }
+
+    fn set_connection(
+        &mut self,
+        connection_task: ConnectionTask,
+        mut connection: AsyncConnectionHandle,
+    ) {
+        // Ids stay unique across connections.
+        let old_connection = &mut self.inner.connection;
+        mem::swap(
+            &mut connection.request_id_builder,
+            &mut old_connection.request_id_builder,
+        );
+        mem::swap(
+            &mut connection.producer_id_builder,
+            &mut old_connection.producer_id_builder,
+        );
+        mem::swap(
+            &mut connection.consumer_id_builder,
+            &mut old_connection.consumer_id_builder,
+        );
+
+        self.connection_task = connection_task;
+        self.inner.connection = connection;
+        self.inner
+            .channel_messages
+            .add_commands(&self.inner.connection.commands_receiver);
+    }
+}
+
+fn connection_task_error(res: Result<(), AsyncConnectionTaskError>) -> AsyncConnectionTaskError {
+    match res {
+        Ok(_) => AsyncConnectionTaskError::ChannelClosed,
+        Err(err) => err,
+    }
+}
+
+impl AsyncHandlerInner {
+    // Cancellation safe, nothing is dequeued until it returns.
+    // None once the next expired request is swept, or the connection requires closing.
+    async fn next_message(&mut self) -> Option<HandlerChannelMessage> {
+        if handle_timeout::handle_timeout(
+            &mut self.pending_deadlines,
+            &mut self.pending_requests,
+            &mut self.pending_sequences,
+            Instant::now(),
+        ) {
+            self.require_close = Some(AsyncConnectionTaskError::ReconnectTimeout);
+            return None;
+        }
+
+        let next = self.channel_messages.next();
+        pin_mut!(next);
+        // Wakes up for sweeping the next expired request.
+        let timeout = match self.pending_deadlines.next() {
+            Some(deadline) => {
+                sleep(deadline.saturating_duration_since(Instant::now())).left_future()
+            }
+            None => pending().right_future(),
+        };
+        pin_mut!(timeout);
+
+        match select(next, timeout).await {
+            Either::Left((msg, _)) => msg,
+            Either::Right(_) => None,
+        }
+    }
+
+    async fn handle_message(&mut self, msg: Option<HandlerChannelMessage>) {
+        match msg {
+            Some(HandlerChannelMessage::Commands(Some(commands))) => {
+                for command in commands.iter() {
+                    self.handle_command(command).await;
+                }
+            }
+            Some(HandlerChannelMessage::Commands(None)) => {
+                // The connection task reports why the reader stopped.
+            }
+            Some(HandlerChannelMessage::Session(Some(msg))) => {
+                self.handle_session_message(*msg).await;
+            }
+            Some(HandlerChannelMessage::Session(None)) => {
+                self.channel_storage.del_session();
+            }
+            Some(HandlerChannelMessage::Producer(producer_id, Some(msg))) => {
+                self.handle_producer_message(producer_id, *msg).await;
+            }
+            Some(HandlerChannelMessage::Producer(producer_id, None)) => {
+                self.handle_producer_dropped(producer_id).await;
+            }
+            Some(HandlerChannelMessage::Consumer(consumer_id, Some(msg))) => {
+                self.handle_consumer_message(consumer_id, *msg).await;
+            }
+            Some(HandlerChannelMessage::Consumer(consumer_id, None)) => {
+                self.handle_consumer_dropped(consumer_id).await;
+            }
+            None => {}
+        }
+    }
+
+    // Registers the producers and the consumers again on a new connection,
+    // the pending sends are resent once their producer is accepted.
+    // The queued messages are dropped, the broker redelivers them to the new subscription.
+    async fn reregister(&mut self) {
+        for (consumer_id, messages) in self.pending_messages.iter_mut() {
+            if let Some(permits) = self.consumer_permits.get_mut(consumer_id) {
+                let n = messages
+                    .iter()
+                    .map(|c| c.get_message_metadata().get_num_messages_in_batch())
+                    .sum();
+                *permits = permits.saturating_add(n);
+            }
+            messages.clear();
+        }
+
+        for (producer_id, producer_command) in self.producer_commands.iter_mut() {
+            // The broker may have assigned the name, it identifies the producer for deduplication.
+            if let Some((producer_name, _)) =
+                self.channel_storage.get_producer(producer_id.to_owned())
+            {
+                producer_command.set_producer_name(&String::from(producer_name.to_owned()));
+            }
+            producer_command.set_epoch(producer_command.get_epoch() + 1);
+
+            let request_id = self.connection.request_id_builder.next();
+            producer_command.set_request_id(request_id.to_owned());
+
+            if let Err(err) = self.connection.write_command(&*producer_command).await {
+                error!("{:?}", err);
+                return;
+            }
+            self.pending_deadlines.add_request(
+                Instant::now() + self.connection.operation_timeout,
+                request_id.to_owned(),
+            );
+            self.pending_requests.insert(
+                request_id,
+                PendingRequestValue::ProducerReconnect(producer_command.to_owned()),
+            );
+        }
+
+        for subscribe_command in self.subscribe_commands.values_mut() {
+            let request_id = self.connection.request_id_builder.next();
+            subscribe_command.set_request_id(request_id.to_owned());
+
+            if let Err(err) = self.connection.write_command(&*subscribe_command).await {
+                error!("{:?}", err);
+                return;
+            }
+            self.pending_deadlines.add_request(
+                Instant::now() + self.connection.operation_timeout,
+                request_id.to_owned(),
+            );
+            self.pending_requests.insert(
+                request_id,
+                PendingRequestValue::ConsumerReconnect(subscribe_command.to_owned()),
+            );
+        }
+    }
+
+    async fn handle_session_message(&mut self, msg: SessionSendHandlerChannelMessage) {
+        let ((request_id, pending_request), command) = msg.into_pending_request_and_command(
+            &self.connection.request_id_builder,
+            &self.connection.producer_id_builder,
+            &self.connection.consumer_id_builder,
+        );
+
+        match self.connection.write_command(command).await {
+            Ok(_) => {
+                self.pending_deadlines.add_request(
+                    Instant::now() + self.connection.operation_timeout,
+                    request_id.to_owned(),
+                );
+                self.pending_requests.insert(request_id, pending_request);
+            }
+            Err(err) => {
+                handle_connection_lost::fail_pending_request(pending_request, Some(err));
+            }
+        }
+    }
+
+    async fn handle_producer_message(
+        &mut self,
+        producer_id: ProducerId,
+        msg: ProducerSendHandlerChannelMessage,
+    ) {
+        let producer_name = match self.channel_storage.get_producer(producer_id.to_owned()) {
+            Some((producer_name, _)) => producer_name.to_owned(),
+            None => {
+                error!("not init producer_id {:?}", producer_id);
+                return;
+            }
+        };
+
+        let group = msg.into_group(producer_id.to_owned(), producer_name);
+
+        match group {
+            ProducerSendHandlerChannelMessageGroup::PendingSequence(
+                sequence_id,
+                pending_sequence,
+            ) => {
+                // Kept even if the write fails, it is resent after a reconnection
+                // or failed with ConnectionLost.
+                if !self.reconnecting_producers.contains(&producer_id) {
+                    match self
+                        .connection
+                        .write_command(pending_sequence.command.to_owned())
+                        .await
+                    {
+                        Ok(_) => {}
+                        Err(err) => {
+                            error!("{:?}", err);
+                        }
+                    }
+                }
+
+                if let Some(send_timeout) = self.connection.send_timeout {
+                    self.pending_deadlines.add_sequence(
+                        Instant::now() + send_timeout,
+                        producer_id.to_owned(),
+                        sequence_id.to_owned(),
+                    );
+                }
+                self.pending_sequences.insert(sequence_id, pending_sequence);
+            }
+        }
+    }
+
+    async fn handle_consumer_message(
+        &mut self,
+        consumer_id: ConsumerId,
+        msg: ConsumerSendHandlerChannelMessage,
+    ) {
+        let message_permits = match &msg {
+            ConsumerSendHandlerChannelMessage::Flow(c, _) => c.get_message_permits(),
+            _ => 0,
+        };
+
+        let group = msg.into_group(consumer_id.to_owned(), &self.connection.request_id_builder);
 
-                        match self.connection.write_command(command).await {
-                            Ok(_) => {
-                                self.pending_requests.insert(request_id, pending_request);
+        match group {
+            ConsumerSendHandlerChannelMessageGroup::Flow(command, s) => {
+                self.pending_messages
+                    .entry(consumer_id.to_owned())
+                    .or_default();
+
+                match self.connection.write_command(command).await {
+                    Ok(_) => {
+                        // Granted again after a reconnection.
+                        let permits = self.consumer_permits.entry(consumer_id).or_insert(0);
+                        *permits = permits.saturating_add(message_permits);
+
+                        match s.send(Ok(())) {
+                            Ok(_) => {}
+                            Err(_) => {
+                                error!("channel closed");
                             }
+                        }
+                    }
+                    Err(err) => match s.send(Err(err.into())) {
+                        Ok(_) => {}
+                        Err(_) => {
+                            error!("channel closed");
+                        }
+                    },
+                }
+            }
+            ConsumerSendHandlerChannelMessageGroup::GetMessage(s) => {
+                if let Some(n) = handle_consumer_get_message::handle_consumer_get_message(
+                    consumer_id.to_owned(),
+                    s,
+                    &mut self.pending_messages,
+                ) {
+                    self.handle_consumer_taken_message(consumer_id, n).await;
+                }
+            }
+            ConsumerSendHandlerChannelMessageGroup::ReceiveMessage(s) => {
+                if let Some(n) = handle_consumer_receive_message::handle_consumer_receive_message(
+                    consumer_id.to_owned(),
+                    s,
+                    &mut self.pending_messages,
+                    &mut self.receiving_consumers,
+                ) {
+                    self.handle_consumer_taken_message(consumer_id, n).await;
+                }
+            }
+            ConsumerSendHandlerChannelMessageGroup::PendingRequest(
+                request_id,
+                pending_request,
+                command,
+            ) => match self.connection.write_command(*command).await {
+                Ok(_) => {
+                    self.pending_deadlines.add_request(
+                        Instant::now() + self.connection.operation_timeout,
+                        request_id.to_owned(),
+                    );
+                    self.pending_requests.insert(request_id, pending_request);
+                }
+                Err(err) => {
+                    handle_connection_lost::fail_pending_request(pending_request, Some(err));
+                }
+            },
+            ConsumerSendHandlerChannelMessageGroup::RedeliverUnacknowledgedMessages(command, s) => {
+                match self.connection.write_command(command).await {
+                    Ok(_) => match s.send(Ok(())) {
+                        Ok(_) => {}
+                        Err(_) => {
+                            error!("channel closed");
+                        }
+                    },
+                    Err(err) => match s.send(Err(err.into())) {
+                        Ok(_) => {}
+                        Err(_) => {
+                            error!("channel closed");
+                        }
+                    },
+                }
+            }
+        }
+    }
+
+    async fn handle_command(&mut self, command: &CommandWithParsed) {
+        match handle(
+            command,
+            &mut self.pending_requests,
+            &mut self.pending_sequences,
+        ) {
+            Ok(output) => match output {
+                HandlerHandleOutput::BrokerPing(_) => {
+                    trace!("receive BrokerPing");
+
+                    let c = PongCommand::new();
+                    match self.connection.write_command(&c).await {
+                        Ok(_) => {}
+                        Err(err) => {
+                            error!("{:?}", err);
+                        }
+                    };
+                }
+                HandlerHandleOutput::OnPingResponded(c) => {
+                    trace!("receive BrokerPong");
+
+                    match handle_broker_pong::handle_broker_pong(c) {
+                        Ok(_) => {}
+                        Err(err) => {
+                            error!("{:?}", err);
+                        }
+                    }
+                }
+                HandlerHandleOutput::OnConnectResponded(_) => {
+                    error!("unreachable");
+                }
+                HandlerHandleOutput::OnResponded(res) => match *res {
+                    OnResponded::SessionCreateProducer(producer_command, s, res) => {
+                        if res.is_ok() {
+                            self.producer_commands.insert(
+                                producer_command.get_producer_id(),
+                                producer_command.to_owned(),
+                            );
+                        }
+
+                        match handle_session_create_producer::handle_session_create_producer(
+                            producer_command,
+                            s,
+                            res,
+                            &mut self.channel_storage,
+                            &mut self.channel_messages,
+                        ) {
+                            Ok(_) => {}
                             Err(err) => {
                                 error!("{:?}", err);
                             }
                         }
                     }
-                    Err(TryRecvError::Empty) => {}
-                    Err(TryRecvError::Closed) => {
-                        channel_storage_del_session = true;
+                    OnResponded::SessionCreateConsumer(subscribe_command, s, res) => {
+                        if res.is_ok() {
+                            self.subscribe_commands.insert(
+                                subscribe_command.get_consumer_id(),
+                                subscribe_command.to_owned(),
+                            );
+
+                            if let Some(receiver_queue_size) =
+                                subscribe_command.get_receiver_queue_size()
+                            {
+                                self.grant_consumer_permits(
+                                    subscribe_command.get_consumer_id(),
+                                    receiver_queue_size,
+                                )
+                                .await;
+                            }
+                        }
+
+                        match handle_session_create_consumer::handle_session_create_consumer(
+                            subscribe_command,
+                            s,
+                            res,
+                            &mut self.channel_storage,
+                            &mut self.channel_messages,
+                        ) {
+                            Ok(_) => {}
+                            Err(err) => {
+                                error!("{:?}", err);
+                            }
+                        }
                     }
-                },
-                HandlerChannelStorageItem::Producer(producer_id, producer_name, r) => match r
-                    .try_recv()
-                {
-                    Ok(msg) => {
-                        let group = msg.into_group(producer_id, producer_name.to_owned());
-
-                        match group {
-                            ProducerSendHandlerChannelMessageGroup::PendingSequence(
-                                sequence_id,
-                                pending_sequence,
-                                command,
-                            ) => match self.connection.write_command(command).await {
-                                Ok(_) => {
-                                    self.pending_sequences.insert(sequence_id, pending_sequence);
-                                }
-                                Err(err) => {
-                                    error!("{:?}", err);
-                                }
-                            },
+                    OnResponded::SessionLookupTopic(lookup_topic_command, s, res) => {
+                        match handle_session_lookup_topic::handle_session_lookup_topic(
+                            lookup_topic_command,
+                            s,
+                            res,
+                        ) {
+                            Ok(_) => {}
+                            Err(err) => {
+                                error!("{:?}", err);
+                            }
                         }
                     }
-                    Err(TryRecvError::Empty) => {}
-                    Err(TryRecvError::Closed) => {
-                        channel_storage_del_producer_ids.push(producer_id);
+                    OnResponded::SessionPartitionedTopicMetadata(
+                        partitioned_topic_metadata_command,
+                        s,
+                        res,
+                    ) => {
+                        match handle_session_partitioned_topic_metadata::handle_session_partitioned_topic_metadata(
+                            partitioned_topic_metadata_command,
+                            s,
+                            res,
+                        ) {
+                            Ok(_) => {}
+                            Err(err) => {
+                                error!("{:?}", err);
+                            }
+                        }
                     }
-                },
-
-                HandlerChannelStorageItem::Consumer(consumer_id, r) => match r.try_recv() {
-                    Ok(msg) => {
-                        let group = msg.into_group(
-                            consumer_id.to_owned(),
-                            &self.connection.request_id_builder,
-                        );
-
-                        match group {
-                            ConsumerSendHandlerChannelMessageGroup::Flow(command, s) => {
-                                self.pending_messages
-                                    .entry(consumer_id.to_owned())
-                                    .or_insert_with(Default::default);
-
-                                match self.connection.write_command(command).await {
-                                    Ok(_) => match s.send(Ok(())) {
-                                        Ok(_) => {}
-                                        Err(_) => {
-                                            error!("channel closed");
-                                        }
-                                    },
-                                    Err(err) => match s.send(Err(err.into())) {
-                                        Ok(_) => {}
-                                        Err(_) => {
-                                            error!("channel closed");
-                                        }
-                                    },
-                                }
+                    OnResponded::SessionGetTopicsOfNamespace(
+                        get_topics_of_namespace_command,
+                        s,
+                        res,
+                    ) => {
+                        match handle_session_get_topics_of_namespace::handle_session_get_topics_of_namespace(
+                            get_topics_of_namespace_command,
+                            s,
+                            res,
+                        ) {
+                            Ok(_) => {}
+                            Err(err) => {
+                                error!("{:?}", err);
                             }
-                            ConsumerSendHandlerChannelMessageGroup::GetMessage(s) => {
-                                handle_consumer_get_message::handle_consumer_get_message(
-                                    consumer_id,
-                                    s,
-                                    &mut self.pending_messages,
-                                )
+                        }
+                    }
+                    OnResponded::ProducerSend(s, res) => {
+                        match handle_producer_send::handle_producer_send(s, res) {
+                            Ok(_) => {}
+                            Err(err) => {
+                                error!("{:?}", err);
                             }
-                            ConsumerSendHandlerChannelMessageGroup::PendingRequest(
-                                request_id,
-                                pending_request,
-                                command,
-                            ) => match self.connection.write_command(*command).await {
-                                Ok(_) => {
-                                    self.pending_requests.insert(request_id, pending_request);
-                                }
-                                Err(err) => {
-                                    error!("{:?}", err);
-                                }
-                            },
-                            ConsumerSendHandlerChannelMessageGroup::RedeliverUnacknowledgedMessages(command, s) => {
-                                match self.connection.write_command(command).await {
-                                    Ok(_) => match s.send(Ok(())) {
-                                        Ok(_) => {}
-                                        Err(_) => {
-                                            error!("channel closed");
-                                        }
-                                    },
-                                    Err(err) => match s.send(Err(err.into())) {
-                                        Ok(_) => {}
-                                        Err(_) => {
-                                            error!("channel closed");
-                                        }
-                                    },
-                                }
+                        }
+                    }
+                    OnResponded::ConsumerAck(s, res) => {
+                        match handle_consumer_ack::handle_consumer_ack(s, res) {
+                            Ok(_) => {}
+                            Err(err) => {
+                                error!("{:?}", err);
                             }
                         }
                     }
-                    Err(TryRecvError::Empty) => {}
-                    Err(TryRecvError::Closed) => {
-                        channel_storage_del_consumer_ids.push(consumer_id);
+                    OnResponded::ProducerReconnect(producer_command, res) => {
+                        self.handle_producer_reconnect(
+                            producer_command.get_producer_id(),
+                            res.is_ok(),
+                        )
+                        .await;
+                        if let Err(err) = res {
+                            error!("{:?}", err);
+                        }
+                    }
+                    OnResponded::ConsumerClose(_, res) => {
+                        if let Err(err) = res {
+                            error!("{:?}", err);
+                        }
+                    }
+                    OnResponded::ProducerClose(_, res) => {
+                        if let Err(err) = res {
+                            error!("{:?}", err);
+                        }
+                    }
+                    OnResponded::ConsumerReconnect(subscribe_command, res) => {
+                        self.handle_consumer_reconnect(
+                            subscribe_command.get_consumer_id(),
+                            res.is_ok(),
+                        )
+                        .await;
+                        if let Err(err) = res {
+                            error!("{:?}", err);
+                        }
                     }
                 },
+                HandlerHandleOutput::BrokerPushMessage(c) => {
+                    let consumer_id = c.get_consumer_id();
+                    let n = c.get_message_metadata().get_num_messages_in_batch();
+                    if let Some(permits) = self.consumer_permits.get_mut(&consumer_id) {
+                        *permits = permits.saturating_sub(n);
+                    }
+
+                    if handle_broker_push_message::handle_broker_push_message(
+                        *c,
+                        &mut self.pending_messages,
+                        &mut self.receiving_consumers,
+                    ) {
+                        self.handle_consumer_taken_message(consumer_id, n).await;
+                    }
+                }
+            },
+            Err(HandlerHandleError::PendingSequenceOutOfOrder(c)) => {
+                error!("receipt out of order {:?}", c);
+                // The sends are resent in order on a new connection.
+                self.require_close = Some(AsyncConnectionTaskError::PendingSequenceOutOfOrder);
+            }
+            Err(err) => {
+                error!("{:?}", err);
             }
         }
-        if channel_storage_del_session {
-            self.channel_storage.del_session();
+    }
+
+    // Resends the pending sends of the producer in sequence order,
+    // or fails them if the broker refused the producer.
+    async fn handle_producer_reconnect(&mut self, producer_id: ProducerId, is_accepted: bool) {
+        self.reconnecting_producers.remove(&producer_id);
+
+        if is_accepted {
+            for pending_sequence in self.pending_sequences.get_producer(&producer_id) {
+                match self
+                    .connection
+                    .write_command(pending_sequence.command.to_owned())
+                    .await
+                {
+                    Ok(_) => {}
+                    Err(err) => {
+                        error!("{:?}", err);
+                        break;
+                    }
+                }
+            }
+        } else {
+            for pending_sequence in self.pending_sequences.remove_producer(&producer_id) {
+                handle_connection_lost::fail_pending_sequence(pending_sequence);
+            }
+
+            self.producer_commands.remove(&producer_id);
+            if let Some((_, r)) = self.channel_storage.get_producer(producer_id) {
+                handle_connection_lost::close_producer(r);
+            }
+        }
+    }
+
+    // Closes the producer on the broker, so that its name can be used again,
+    // the sends that are not responded yet fail.
+    async fn handle_producer_dropped(&mut self, producer_id: ProducerId) {
+        self.producer_commands.remove(&producer_id);
+        self.reconnecting_producers.remove(&producer_id);
+        for pending_sequence in self.pending_sequences.remove_producer(&producer_id) {
+            handle_connection_lost::fail_pending_sequence(pending_sequence);
+        }
+        self.channel_storage.del_producer(producer_id.to_owned());
+
+        let request_id = self.connection.request_id_builder.next();
+        let mut c = CloseProducerCommand::new(producer_id);
+        c.set_request_id(request_id.to_owned());
+        match self.connection.write_command(&c).await {
+            Ok(_) => {
+                self.pending_deadlines.add_request(
+                    Instant::now() + self.connection.operation_timeout,
+                    request_id.to_owned(),
+                );
+                self.pending_requests
+                    .insert(request_id, PendingRequestValue::ProducerClose(c));
+            }
+            Err(err) => {
+                error!("{:?}", err);
+            }
         }
-        for producer_id in channel_storage_del_producer_ids {
-            self.channel_storage.del_producer(producer_id);
+    }
+
+    // Closes the consumer on the broker, the messages it has not taken are dropped.
+    async fn handle_consumer_dropped(&mut self, consumer_id: ConsumerId) {
+        self.subscribe_commands.remove(&consumer_id);
+        self.consumer_permits.remove(&consumer_id);
+        self.receiving_consumers.remove(&consumer_id);
+        self.consumer_taken_messages.remove(&consumer_id);
+        self.pending_messages.remove(&consumer_id);
+        self.channel_storage.del_consumer(consumer_id.to_owned());
+
+        let request_id = self.connection.request_id_builder.next();
+        let mut c = CloseConsumerCommand::new(consumer_id);
+        c.set_request_id(request_id.to_owned());
+        match self.connection.write_command(&c).await {
+            Ok(_) => {
+                self.pending_deadlines.add_request(
+                    Instant::now() + self.connection.operation_timeout,
+                    request_id.to_owned(),
+                );
+                self.pending_requests
+                    .insert(request_id, PendingRequestValue::ConsumerClose(c));
+            }
+            Err(err) => {
+                error!("{:?}", err);
+            }
         }
-        for consumer_id in channel_storage_del_consumer_ids {
-            self.channel_storage.del_consumer(consumer_id);
+    }
+
+    // Grants the permits of the taken messages once half of the receiver queue is taken.
+    // The entries of a batch count one by one, the Stream takes the next message once
+    // it has yielded the entries of the previous one.
+    async fn handle_consumer_taken_message(&mut self, consumer_id: ConsumerId, n: u32) {
+        let receiver_queue_size = match self
+            .subscribe_commands
+            .get(&consumer_id)
+            .and_then(|c| c.get_receiver_queue_size())
+        {
+            Some(n) => n,
+            None => return,
+        };
+
+        let taken = self
+            .consumer_taken_messages
+            .entry(consumer_id.to_owned())
+            .or_insert(0);
+        *taken += n;
+        if *taken < (receiver_queue_size / 2).max(1) {
+            return;
         }
+        let permits = mem::take(taken);
 
-        //
+        self.grant_consumer_permits(consumer_id, permits).await;
+    }
 
-        match self.connection.try_read_commands(None).await {
-            Ok(Some(commands)) => {
-                for command in commands.iter() {
-                    match handle(command, &mut self.pending_requests, &mut self.pending_sequences) {
-                        Ok(output) => {
-                            match output {
-                                HandlerHandleOutput::BrokerPing(_) => {
-                                    trace!("receive BrokerPing");
-
-                                    let c= PongCommand::new();
-                                    match self.connection.write_command(&c).await {
-                                        Ok(_) => {},
-                                        Err(err) => {
-                                            error!("{:?}", err);
-                                        }
-                                    };
-                                }
-                                HandlerHandleOutput::OnPingResponded(c) => {
-                                    trace!("receive BrokerPong");
-
-                                    match handle_broker_pong::handle_broker_pong(c) {
-                                        Ok(_) => {}
-                                        Err(err) => {
-                                            error!("{:?}", err);
-                                        }
-                                    }
-                                }
-                                HandlerHandleOutput::OnConnectResponded(_) => {
-                                    error!("unreachable");
-                                }
-                                HandlerHandleOutput::OnResponded(
-                                    res,
-                                ) => match *res {
-                                    OnResponded::SessionCreateProducer(producer_command, s, res) => {
-                                        match handle_session_create_producer::handle_session_create_producer(producer_command.to_owned(),s, res, &mut self.channel_storage) {
-                                            Ok(_) => {}
-                                            Err(err) => {
-                                                error!("{:?}", err);
-                                            }
-                                        }
-                                    }
-                                    OnResponded::SessionCreateConsumer(subscribe_command,s, res) => {
-                                        match handle_session_create_consumer::handle_session_create_consumer(subscribe_command.to_owned(),s, res, &mut self.channel_storage) {
-                                            Ok(_) => {}
-                                            Err(err) => {
-                                                error!("{:?}", err);
-                                            }
-                                        }
-                                    }
-                                    OnResponded::ProducerSend(s,res) => {
-                                        match handle_producer_send::handle_producer_send(s, res) {
-                                            Ok(_) => {}
-                                                Err(err) => {
-                                                    error!("{:?}", err);
-                                                }
-                                        }
-                                    }
-                                    OnResponded::ConsumerAck(s,res) => {
-                                        match handle_consumer_ack::handle_consumer_ack(s, res) {
-                                            Ok(_) => {}
-                                                Err(err) => {
-                                                    error!("{:?}", err);
-                                                }
-                                        }
-                                    }
-                                },
-                                HandlerHandleOutput::BrokerPushMessage(c) => {
-                                     handle_broker_push_message::handle_broker_push_message(*c, &mut self.pending_messages)
-                                }
-                            }
-                        }
-                        Err(err) => {
-                            error!("{:?}", err);
-                        }
-                    }
-                }
+    async fn grant_consumer_permits(&mut self, consumer_id: ConsumerId, permits: u32) {
+        self.pending_messages
+            .entry(consumer_id.to_owned())
+            .or_default();
+
+        let mut c = FlowCommand::new(permits);
+        c.set_consumer_id(consumer_id.to_owned());
+        match self.connection.write_command(&c).await {
+            Ok(_) => {
+                // Granted again after a reconnection.
+                let n = self.consumer_permits.entry(consumer_id).or_insert(0);
+                *n = n.saturating_add(permits);
+            }
+            Err(err) => {
+                error!("{:?}", err);
             }
-            Ok(None) => {}
-            Err(err) => match err {
-                ReadCommandError::ReadError(err) if err.kind() == IoErrorKind::TimedOut => {}
-                _ => {
+        }
+    }
+
+    // Grants the permits that were not used on the lost connection,
+    // or closes the consumer if the broker refused the subscription.
+    async fn handle_consumer_reconnect(&mut self, consumer_id: ConsumerId, is_accepted: bool) {
+        if is_accepted {
+            let permits = self
+                .consumer_permits
+                .get(&consumer_id)
+                .cloned()
+                .unwrap_or(0);
+            if permits == 0 {
+                return;
+            }
+
+            let mut c = FlowCommand::new(permits);
+            c.set_consumer_id(consumer_id);
+            match self.connection.write_command(&c).await {
+                Ok(_) => {}
+                Err(err) => {
                     error!("{:?}", err);
-                    unimplemented!()
                 }
-            },
+            }
+        } else {
+            self.subscribe_commands.remove(&consumer_id);
+            self.receiving_consumers.remove(&consumer_id);
+            if let Some(r) = self.channel_storage.get_consumer(consumer_id) {
+                handle_connection_lost::close_consumer(r);
+            }
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,303 @@
+use std::{future::Future, io::Error as IoError, str, sync::Arc, time::Duration};
+
+use futures_util::future::{select, BoxFuture, Either, FutureExt as _};
+use pulsar_binary_protocol_spec::{
+    broker_service_url::{ParseError as UrlParseError, Url},
+    types::{TopicName, TopicNameParseError},
+    url::{ParseError as HttpUrlParseError, Url as HttpUrl},
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::{sleep, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+type HttpConnector<S> =
+    Arc<dyn Fn(HttpUrl) -> BoxFuture<'static, Result<S, IoError>> + Send + Sync>;
+
+const MAX_LOOKUP_REDIRECTS: usize = 20;
+const MAX_RESPONSE_SIZE: usize = 1024 * 1024;
+
+// Not sent to a redirected host of another origin.
+const CREDENTIAL_HEADERS: &[&str] = &["Authorization", "Proxy-Authorization", "Cookie"];
+
+// Resolves the brokers through the REST api of an http:// or https:// service url,
+// the connector opens the stream (plain or TLS) to the host of the url.
+pub struct HttpLookup<S> {
+    connector: HttpConnector<S>,
+    service_url: HttpUrl,
+    headers: Vec<(String, String)>,
+    timeout: Option<Duration>,
+}
+
+impl<S> HttpLookup<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    pub fn new<C, Fut>(connector: C, service_url: &str) -> Result<Self, HttpLookupError>
+    where
+        C: Fn(HttpUrl) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<S, IoError>> + Send + 'static,
+    {
+        let service_url: HttpUrl = service_url.parse()?;
+        match service_url.scheme() {
+            "http" | "https" => {}
+            _ => return Err(HttpLookupError::SchemeMismatch),
+        }
+        if service_url.host_str().is_none() {
+            return Err(HttpLookupError::HostMissing);
+        }
+
+        Ok(Self {
+            connector: Arc::new(move |url| connector(url).boxed()),
+            service_url,
+            headers: vec![],
+            timeout: None,
+        })
+    }
+
+    // e.g. ("Authorization", "Bearer <token>")
+    pub fn add_header(&mut self, name: &str, value: &str) -> Result<&mut Self, HttpLookupError> {
+        if !is_header_name(name) || !is_header_value(value) {
+            return Err(HttpLookupError::InvalidHeader(name.to_owned()));
+        }
+
+        self.headers.push((name.to_owned(), value.to_owned()));
+        Ok(self)
+    }
+
+    // Of each request, from the connect to the end of the response.
+    pub fn set_timeout(&mut self, dur: Duration) -> &mut Self {
+        self.timeout = Some(dur);
+        self
+    }
+    fn get_timeout(&self) -> Duration {
+        self.timeout.unwrap_or_else(|| Duration::from_secs(30))
+    }
+
+    // Returns the pulsar+ssl:// url of the broker when the service url is https://.
+    pub async fn lookup_topic(&self, topic: impl AsRef<str>) -> Result<Url, HttpLookupError> {
+        let path = format!("/lookup/v2/topic/{}", topic_path(topic.as_ref())?);
+        let body = self.get(&path).await?;
+
+        let lookup_data: LookupData = serde_json::from_slice(&body)?;
+        let broker_url = match self.service_url.scheme() {
+            "https" => lookup_data.broker_url_tls,
+            _ => lookup_data.broker_url,
+        }
+        .filter(|s| !s.is_empty())
+        .ok_or(HttpLookupError::BrokerServiceUrlMissing)?;
+
+        Ok(broker_url.parse()?)
+    }
+
+    // Returns 0 for a non-partitioned topic.
+    pub async fn get_partitioned_topic_metadata(
+        &self,
+        topic: impl AsRef<str>,
+    ) -> Result<u32, HttpLookupError> {
+        let path = format!("/admin/v2/{}/partitions", topic_path(topic.as_ref())?);
+        let body = self.get(&path).await?;
+
+        let metadata: PartitionedTopicMetadata = serde_json::from_slice(&body)?;
+
+        Ok(metadata.partitions)
+    }
+
+    async fn get(&self, path: &str) -> Result<Vec<u8>, HttpLookupError> {
+        let mut url = self.service_url.join(path)?;
+
+        for _ in 0..MAX_LOOKUP_REDIRECTS {
+            let with_credentials = url.origin() == self.service_url.origin();
+            let response = match select(
+                self.request(&url, with_credentials).boxed(),
+                sleep(self.get_timeout()).boxed(),
+            )
+            .await
+            {
+                Either::Left((res, _)) => res?,
+                Either::Right(_) => return Err(HttpLookupError::TimedOut),
+            };
+
+            match response.status {
+                200..=299 => return Ok(response.body),
+                // The broker that does not own the topic redirects to the one that does.
+                301 | 302 | 303 | 307 | 308 => {
+                    let location = response.location.ok_or(HttpLookupError::InvalidResponse)?;
+                    url = url.join(&location)?;
+                }
+                404 => return Err(HttpLookupError::TopicNotFound),
+                status => {
+                    return Err(HttpLookupError::StatusError(
+                        status,
+                        String::from_utf8_lossy(&response.body).into_owned(),
+                    ))
+                }
+            }
+        }
+
+        Err(HttpLookupError::TooManyRedirects)
+    }
+
+    async fn request(
+        &self,
+        url: &HttpUrl,
+        with_credentials: bool,
+    ) -> Result<HttpResponse, HttpLookupError> {
+        let host = url.host_str().ok_or(HttpLookupError::HostMissing)?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_owned(),
+        };
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_owned(),
+        };
+
+        // HTTP/1.0 keeps the response free of chunked encoding, the server closes after it.
+        let mut request = format!(
+            "GET {} HTTP/1.0\r\nHost: {}\r\nAccept: application/json\r\n",
+            path, host
+        );
+        for (name, value) in &self.headers {
+            if !with_credentials
+                && CREDENTIAL_HEADERS
+                    .iter()
+                    .any(|credential| name.eq_ignore_ascii_case(credential))
+            {
+                continue;
+            }
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str("\r\n");
+
+        let mut stream = (self.connector)(url.to_owned()).await?;
+        stream.write_all(request.as_bytes()).await?;
+        stream.flush().await?;
+
+        let mut buf = vec![];
+        (&mut stream)
+            .take(MAX_RESPONSE_SIZE as u64 + 1)
+            .read_to_end(&mut buf)
+            .await?;
+        if buf.len() > MAX_RESPONSE_SIZE {
+            return Err(HttpLookupError::ResponseTooLarge);
+        }
+
+        HttpResponse::parse(buf).ok_or(HttpLookupError::InvalidResponse)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum HttpLookupError {
+    #[error("HttpUrlParseError {0:?}")]
+    HttpUrlParseError(#[from] HttpUrlParseError),
+    #[error("SchemeMismatch")]
+    SchemeMismatch,
+    #[error("HostMissing")]
+    HostMissing,
+    #[error("InvalidHeader {0:?}")]
+    InvalidHeader(String),
+    #[error("TimedOut")]
+    TimedOut,
+    #[error("ResponseTooLarge")]
+    ResponseTooLarge,
+    #[error("IoError {0:?}")]
+    IoError(#[from] IoError),
+    #[error("InvalidResponse")]
+    InvalidResponse,
+    #[error("TooManyRedirects")]
+    TooManyRedirects,
+    #[error("TopicNotFound")]
+    TopicNotFound,
+    #[error("StatusError {0:?} {1:?}")]
+    StatusError(u16, String),
+    #[error("JsonError {0:?}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("BrokerServiceUrlMissing")]
+    BrokerServiceUrlMissing,
+    #[error("UrlParseError {0:?}")]
+    UrlParseError(#[from] UrlParseError),
+    #[error("TopicNameParseError {0:?}")]
+    TopicNameParseError(#[from] TopicNameParseError),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LookupData {
+    broker_url: Option<String>,
+    broker_url_tls: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PartitionedTopicMetadata {
+    partitions: u32,
+}
+
+// persistent://tenant/namespace/topic to persistent/tenant/namespace/topic
+fn topic_path(topic: &str) -> Result<String, TopicNameParseError> {
+    let topic: TopicName = topic.parse()?;
+
+    Ok(format!(
+        "{}/{}/{}/{}",
+        topic.get_domain().as_str(),
+        topic.get_tenant(),
+        topic.get_namespace(),
+        topic.get_local_name()
+    ))
+}
+
+// The token of RFC 7230.
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+// No CR, LF or other control characters, so that it can not inject a header.
+fn is_header_value(value: &str) -> bool {
+    value.bytes().all(|b| b == b'\t' || !b.is_ascii_control())
+}
+
+struct HttpResponse {
+    status: u16,
+    location: Option<String>,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    fn parse(mut buf: Vec<u8>) -> Option<Self> {
+        let header_end = buf.windows(4).position(|w| w == b"\r\n\r\n")?;
+        let head = str::from_utf8(&buf[..header_end]).ok()?;
+        let mut lines = head.split("\r\n");
+
+        let status_line = lines.next()?;
+        if !status_line.starts_with("HTTP/") {
+            return None;
+        }
+        let status = status_line.split(' ').nth(1)?.parse().ok()?;
+
+        let mut location = None;
+        let mut content_length = None;
+        for line in lines {
+            let (name, value) = line.split_once(':')?;
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("Location") {
+                location = Some(value.to_owned());
+            } else if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = Some(value.parse::<usize>().ok()?);
+            }
+        }
+
+        let mut body = buf.split_off(header_end + 4);
+        if let Some(content_length) = content_length {
+            body.truncate(content_length);
+        }
+
+        Some(Self {
+            status,
+            location,
+            body,
+        })
+    }
+}
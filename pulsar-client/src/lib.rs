@@ -13,6 +13,14 @@ pub mod tokio02_io;
 #[path = "tokio_io.rs"]
 pub mod tokio_io;
 
+#[cfg(feature = "tokio_rustls")]
+pub mod tls;
+
 pub mod consumer;
+pub mod pending_limit;
 pub mod producer;
+pub mod reconnect;
+pub mod service_url_provider;
 pub mod session;
+
+mod sync_wrapper;
@@ -0,0 +1,307 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures_util::{
+    future::{join_all, BoxFuture, FutureExt as _},
+    stream::{Stream, StreamExt as _},
+};
+use log::warn;
+use pulsar_binary_protocol_spec::{
+    types::{Message, TopicName, TopicsOfNamespaceMode},
+    SubscribeCommand,
+};
+use regex::Regex;
+use thiserror::Error;
+
+use crate::{
+    consumer::{AsyncConsumer, AsyncConsumerConfig, AsyncMultiTopicsConsumer, ReceiveMessageError},
+    service_url_provider::ServiceUrlProvider,
+    sync_wrapper::SyncWrapper,
+};
+
+use super::{
+    pool::{ConnectionPool, ConnectionPoolError},
+    sleep, AsyncRead, AsyncWrite,
+};
+
+#[derive(Error, Debug)]
+pub enum PatternConsumerError {
+    #[error("RegexError {0:?}")]
+    RegexError(#[from] regex::Error),
+    #[error("ConnectionPoolError {0:?}")]
+    ConnectionPoolError(#[from] ConnectionPoolError),
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct AsyncPatternConsumerConfig {
+    mode: Option<TopicsOfNamespaceMode>,
+    discovery_interval: Option<Duration>,
+    consumer_config: Option<AsyncConsumerConfig>,
+}
+impl AsyncPatternConsumerConfig {
+    pub fn set_mode(&mut self, mode: TopicsOfNamespaceMode) -> &mut Self {
+        self.mode = Some(mode);
+        self
+    }
+    fn get_mode(&self) -> TopicsOfNamespaceMode {
+        self.mode.unwrap_or(TopicsOfNamespaceMode::Persistent)
+    }
+
+    pub fn set_discovery_interval(&mut self, dur: Duration) -> &mut Self {
+        self.discovery_interval = Some(dur);
+        self
+    }
+    fn get_discovery_interval(&self) -> Duration {
+        self.discovery_interval
+            .unwrap_or_else(|| Duration::from_secs(60))
+    }
+
+    pub fn set_consumer_config(&mut self, config: AsyncConsumerConfig) -> &mut Self {
+        self.consumer_config = Some(config);
+        self
+    }
+    fn get_consumer_config(&self) -> AsyncConsumerConfig {
+        self.consumer_config.to_owned().unwrap_or_default()
+    }
+}
+
+// The new consumers, and every topic of the namespace that matches.
+type Discovered = (Vec<AsyncConsumer>, HashSet<String>);
+
+struct PatternConsumerDiscovery<S> {
+    pool: Arc<ConnectionPool<S>>,
+    service_url_provider: Arc<dyn ServiceUrlProvider>,
+    namespace: String,
+    regex: Regex,
+    subscribe_command: SubscribeCommand,
+    config: AsyncPatternConsumerConfig,
+}
+impl<S> PatternConsumerDiscovery<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    // The partitions of a partitioned topic match by the name of the partitioned topic.
+    fn is_match(&self, topic: &str) -> bool {
+        match topic.parse::<TopicName>() {
+            Ok(topic) => self
+                .regex
+                .is_match(topic.get_partitioned_topic_name().get_local_name()),
+            Err(_) => false,
+        }
+    }
+
+    // Subscribes to the topics that are not known yet, the ones that fail are tried again
+    // on the next discovery.
+    async fn discover(
+        &self,
+        known_topics: HashSet<String>,
+    ) -> Result<Discovered, PatternConsumerError> {
+        let topics = self
+            .pool
+            .get_topics_of_namespace_by_service_url(
+                self.service_url_provider.as_ref(),
+                &self.namespace,
+                self.config.get_mode(),
+            )
+            .await?
+            .into_iter()
+            .filter(|topic| self.is_match(topic))
+            .collect::<HashSet<_>>();
+
+        let consumers = join_all(
+            topics
+                .iter()
+                .filter(|topic| !known_topics.contains(*topic))
+                .map(|topic| {
+                    let mut subscribe_command = self.subscribe_command.to_owned();
+                    subscribe_command.set_topic(topic);
+                    self.pool.create_consumer_by_service_url(
+                        self.service_url_provider.as_ref(),
+                        subscribe_command,
+                        self.config.get_consumer_config(),
+                    )
+                }),
+        )
+        .await
+        .into_iter()
+        .filter_map(|res| match res {
+            Ok(consumer) => Some(consumer),
+            Err(err) => {
+                warn!("subscribe failed, err: {:?}", err);
+                None
+            }
+        })
+        .collect();
+
+        Ok((consumers, topics))
+    }
+}
+
+// Sleeps for the interval, then discovers with the topics known once awake.
+struct PatternConsumerStreamState {
+    sleeping: Option<BoxFuture<'static, ()>>,
+    discovering: Option<BoxFuture<'static, Result<Discovered, PatternConsumerError>>>,
+}
+
+// Subscribes to every topic of the namespace whose local name matches the whole pattern,
+// e.g. events-.* in my-tenant/my-ns. The namespace is discovered again on an interval,
+// the new topics are subscribed and the consumers of the deleted ones are closed.
+// The Stream does not end, it waits for the next discovery when there is no topic.
+pub struct AsyncPatternConsumer<S> {
+    discovery: Arc<PatternConsumerDiscovery<S>>,
+    inner: AsyncMultiTopicsConsumer,
+    stream_state: SyncWrapper<PatternConsumerStreamState>,
+}
+impl<S> AsyncPatternConsumer<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    // The topic of the subscribe command is replaced.
+    pub async fn new(
+        pool: Arc<ConnectionPool<S>>,
+        service_url_provider: Arc<dyn ServiceUrlProvider>,
+        namespace: &str,
+        pattern: &str,
+        subscribe_command: SubscribeCommand,
+        config: impl Into<Option<AsyncPatternConsumerConfig>>,
+    ) -> Result<Self, PatternConsumerError> {
+        let discovery = Arc::new(PatternConsumerDiscovery {
+            pool,
+            service_url_provider,
+            namespace: namespace.to_owned(),
+            regex: Regex::new(&format!("^(?:{})$", pattern))?,
+            subscribe_command,
+            config: config.into().unwrap_or_default(),
+        });
+
+        let (consumers, _) = discovery.discover(HashSet::new()).await?;
+
+        Ok(Self {
+            discovery,
+            inner: AsyncMultiTopicsConsumer::new(consumers),
+            stream_state: SyncWrapper::new(PatternConsumerStreamState {
+                sleeping: None,
+                discovering: None,
+            }),
+        })
+    }
+
+    pub fn get_multi_topics_consumer(&self) -> &AsyncMultiTopicsConsumer {
+        &self.inner
+    }
+
+    pub fn get_topics(&self) -> Vec<&str> {
+        self.inner.get_topics()
+    }
+
+    // Does not wait for the interval.
+    pub async fn rediscover(&mut self) -> Result<(), PatternConsumerError> {
+        let known_topics = self.prepare_discovery();
+        let discovered = self.discovery.discover(known_topics).await?;
+        self.apply_discovery(discovered);
+        Ok(())
+    }
+
+    // The closed consumers are removed, so that their topics are subscribed again.
+    fn prepare_discovery(&mut self) -> HashSet<String> {
+        let closed_topics = self
+            .inner
+            .get_consumers()
+            .iter()
+            .filter(|c| c.is_closed())
+            .map(|c| c.get_topic().to_owned())
+            .collect::<Vec<_>>();
+        for topic in closed_topics {
+            self.inner.remove_consumer(&topic);
+        }
+
+        self.inner
+            .get_topics()
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+
+    fn apply_discovery(&mut self, (consumers, topics): Discovered) {
+        let deleted_topics = self
+            .inner
+            .get_topics()
+            .into_iter()
+            .filter(|topic| !topics.contains(*topic))
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        for topic in deleted_topics {
+            self.inner.remove_consumer(&topic);
+        }
+
+        // Subscribed meanwhile by rediscover.
+        for consumer in consumers {
+            if !self.inner.get_topics().contains(&consumer.get_topic()) {
+                self.inner.add_consumer(consumer);
+            }
+        }
+    }
+}
+
+impl<S> Stream for AsyncPatternConsumer<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    type Item = Result<Message, ReceiveMessageError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            let stream_state = this.stream_state.get_mut();
+            let mut discovering = match stream_state.discovering.take() {
+                Some(discovering) => discovering,
+                None => {
+                    let discovery_interval = this.discovery.config.get_discovery_interval();
+                    let sleeping = stream_state
+                        .sleeping
+                        .get_or_insert_with(|| sleep(discovery_interval).boxed());
+                    match sleeping.poll_unpin(cx) {
+                        Poll::Ready(_) => stream_state.sleeping = None,
+                        Poll::Pending => break,
+                    }
+
+                    // Subscribed or closed meanwhile.
+                    let discovery = this.discovery.to_owned();
+                    let known_topics = this.prepare_discovery();
+                    async move { discovery.discover(known_topics).await }.boxed()
+                }
+            };
+
+            match discovering.poll_unpin(cx) {
+                Poll::Ready(Ok(discovered)) => this.apply_discovery(discovered),
+                Poll::Ready(Err(err)) => warn!("discovery failed, err: {:?}", err),
+                Poll::Pending => {
+                    this.stream_state.get_mut().discovering = Some(discovering);
+                    break;
+                }
+            }
+        }
+
+        match this.inner.poll_next_unpin(cx) {
+            Poll::Ready(Some(item)) => Poll::Ready(Some(item)),
+            Poll::Ready(None) | Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<S> fmt::Debug for AsyncPatternConsumer<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncPatternConsumer")
+            .field("namespace", &self.discovery.namespace)
+            .field("pattern", &self.discovery.regex.as_str())
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use event_listener::Event;
+
+// Counts the messages or bytes of the sends that wait for the broker,
+// the clones share the count, e.g. the memory limit of all producers of a client.
+#[derive(Clone)]
+pub struct PendingLimit(Arc<PendingLimitInner>);
+
+struct PendingLimitInner {
+    max: usize,
+    used: AtomicUsize,
+    released: Event,
+}
+
+impl PendingLimit {
+    pub fn new(max: usize) -> Self {
+        Self(Arc::new(PendingLimitInner {
+            max,
+            used: AtomicUsize::new(0),
+            released: Event::new(),
+        }))
+    }
+
+    pub fn get_max(&self) -> usize {
+        self.0.max
+    }
+
+    pub fn get_used(&self) -> usize {
+        self.0.used.load(Ordering::SeqCst)
+    }
+
+    // One send that is larger than the max is let through when nothing else is pending.
+    pub(crate) fn try_acquire(&self, n: usize) -> bool {
+        let inner = &self.0;
+        let mut used = inner.used.load(Ordering::SeqCst);
+        loop {
+            if used > 0 && used.saturating_add(n) > inner.max {
+                return false;
+            }
+            match inner.used.compare_exchange_weak(
+                used,
+                used.saturating_add(n),
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => return true,
+                Err(v) => used = v,
+            }
+        }
+    }
+
+    pub(crate) async fn acquire(&self, n: usize) {
+        loop {
+            if self.try_acquire(n) {
+                return;
+            }
+
+            let listener = self.0.released.listen();
+            if self.try_acquire(n) {
+                return;
+            }
+            listener.await;
+        }
+    }
+
+    pub(crate) fn release(&self, n: usize) {
+        self.0.used.fetch_sub(n, Ordering::SeqCst);
+        self.0.released.notify(usize::MAX);
+    }
+}
+
+impl fmt::Debug for PendingLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingLimit")
+            .field("max", &self.get_max())
+            .field("used", &self.get_used())
+            .finish()
+    }
+}
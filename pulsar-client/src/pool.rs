@@ -0,0 +1,380 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    io::Error as IoError,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use futures_util::{
+    future::{try_join_all, BoxFuture, FutureExt as _},
+    lock::Mutex as AsyncMutex,
+};
+use log::{error, warn};
+use pulsar_binary_protocol_spec::{
+    broker_service_url::{ParseError as UrlParseError, Scheme, Url},
+    types::{TopicName, TopicNameParseError, TopicsOfNamespaceMode},
+    ConnectCommand, LookupTopicCommand, PartitionedTopicMetadataCommand, SubscribeCommand,
+};
+use thiserror::Error;
+
+use crate::{
+    consumer::{AsyncConsumer, AsyncConsumerConfig, AsyncMultiTopicsConsumer},
+    pending_limit::PendingLimit,
+    reconnect::ReconnectConfig,
+    service_url_provider::ServiceUrlProvider,
+    session::{
+        AsyncSession, RawCreateConsumerError, RawGetPartitionedTopicMetadataError,
+        RawGetTopicsOfNamespaceError, RawLookupTopicError,
+    },
+};
+
+use super::{
+    client::{AsyncClient, RawConnectError},
+    connection::{AsyncConnection, AsyncConnectionConfig},
+    AsyncRead, AsyncWrite,
+};
+
+type Connector<S> = Arc<dyn Fn(Url) -> BoxFuture<'static, Result<S, IoError>> + Send + Sync>;
+
+type Spawner = Box<dyn Fn(BoxFuture<'static, ()>) + Send + Sync>;
+
+// (logical address, physical address, index of the connection to the broker)
+type ConnectionPoolKey = (Url, Url, usize);
+
+type ConnectionPoolSlot = Arc<AsyncMutex<Option<Arc<AsyncSession>>>>;
+
+const MAX_LOOKUP_REDIRECTS: usize = 20;
+
+// Shares the connections to the brokers, the handlers run on the tasks of the spawner.
+pub struct ConnectionPool<S> {
+    connector: Connector<S>,
+    spawner: Spawner,
+    connect_command: ConnectCommand,
+    connection_config: Option<AsyncConnectionConfig>,
+    reconnect_config: Option<ReconnectConfig>,
+    connections_per_broker: usize,
+    memory_limit: Option<PendingLimit>,
+    slots: Mutex<HashMap<ConnectionPoolKey, ConnectionPoolSlot>>,
+    next_index: AtomicUsize,
+}
+
+impl<S> ConnectionPool<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    pub fn new<C, Fut, SP>(connector: C, spawner: SP, connect_command: ConnectCommand) -> Self
+    where
+        C: Fn(Url) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<S, IoError>> + Send + 'static,
+        SP: Fn(BoxFuture<'static, ()>) + Send + Sync + 'static,
+    {
+        Self {
+            connector: Arc::new(move |url| connector(url).boxed()),
+            spawner: Box::new(spawner),
+            connect_command,
+            connection_config: None,
+            reconnect_config: None,
+            connections_per_broker: 1,
+            memory_limit: None,
+            slots: Mutex::new(HashMap::new()),
+            next_index: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn set_connection_config(&mut self, config: AsyncConnectionConfig) -> &mut Self {
+        self.connection_config = Some(config);
+        self
+    }
+
+    // The handlers reconnect to the same physical address, instead of being replaced on the next get.
+    pub fn set_reconnect_config(&mut self, config: ReconnectConfig) -> &mut Self {
+        self.reconnect_config = Some(config);
+        self
+    }
+
+    // The sessions of one broker are handed out in turn.
+    pub fn set_connections_per_broker(&mut self, n: usize) -> &mut Self {
+        self.connections_per_broker = n.max(1);
+        self
+    }
+
+    // The bytes of the pending sends of all producers of all sessions.
+    pub fn set_memory_limit(&mut self, max_bytes: usize) -> &mut Self {
+        self.memory_limit = Some(PendingLimit::new(max_bytes));
+        self
+    }
+
+    pub async fn get_by_url(&self, url: &Url) -> Result<Arc<AsyncSession>, ConnectionPoolError> {
+        self.get(url, url).await
+    }
+
+    // The logical address is the broker that owns the topic, the physical address is the one
+    // that is connected to, they differ when connecting through a proxy.
+    pub async fn get(
+        &self,
+        logical_url: &Url,
+        physical_url: &Url,
+    ) -> Result<Arc<AsyncSession>, ConnectionPoolError> {
+        let index = self.next_index.fetch_add(1, Ordering::Relaxed) % self.connections_per_broker;
+        let key = (logical_url.to_owned(), physical_url.to_owned(), index);
+
+        let slot = {
+            let mut slots = self.slots.lock().expect("poisoned");
+            slots.entry(key).or_default().to_owned()
+        };
+
+        // Connecting to one broker does not block the others.
+        let mut slot = slot.lock().await;
+        if let Some(session) = slot.as_ref() {
+            if !session.is_closed() {
+                return Ok(session.to_owned());
+            }
+        }
+
+        let session = Arc::new(self.connect(logical_url, physical_url).await?);
+        *slot = Some(session.to_owned());
+
+        Ok(session)
+    }
+
+    // Tries the hosts of the service url in random order, until one is connected.
+    pub async fn get_by_service_url(
+        &self,
+        service_url_provider: &dyn ServiceUrlProvider,
+    ) -> Result<(Url, Arc<AsyncSession>), ConnectionPoolError> {
+        let service_url = service_url_provider.get_service_url();
+        let mut urls = service_url.get_urls().to_vec();
+        fastrand::shuffle(&mut urls);
+
+        let mut last_err = None;
+        for url in urls {
+            match self.get_by_url(&url).await {
+                Ok(session) => return Ok((url, session)),
+                Err(err) => {
+                    warn!("connect to {} failed {:?}", url.to_string(), err);
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        Err(last_err.expect("ServiceUrl has one url at least"))
+    }
+
+    pub async fn lookup_topic_by_service_url(
+        &self,
+        service_url_provider: &dyn ServiceUrlProvider,
+        topic: impl AsRef<str>,
+    ) -> Result<(Url, Url), ConnectionPoolError> {
+        let (service_url, _) = self.get_by_service_url(service_url_provider).await?;
+
+        self.lookup_topic(&service_url, topic).await
+    }
+
+    // Looks up the broker that owns the topic through the service url, following the redirects.
+    // Returns the logical and the physical address of the broker.
+    pub async fn lookup_topic(
+        &self,
+        service_url: &Url,
+        topic: impl AsRef<str>,
+    ) -> Result<(Url, Url), ConnectionPoolError> {
+        let mut logical_url = service_url.to_owned();
+        let mut physical_url = service_url.to_owned();
+        let mut authoritative = false;
+
+        for _ in 0..MAX_LOOKUP_REDIRECTS {
+            let session = self.get(&logical_url, &physical_url).await?;
+
+            let mut lookup_topic_command = LookupTopicCommand::new(&topic);
+            lookup_topic_command.set_authoritative(authoritative);
+            let c = session.raw_lookup_topic(lookup_topic_command).await?;
+
+            let broker_url = match service_url.scheme {
+                Scheme::Tcp => c.get_broker_service_url(),
+                Scheme::Tls => c.get_broker_service_url_tls(),
+            }
+            .ok_or(ConnectionPoolError::BrokerServiceUrlMissing)?;
+
+            let broker_url: Url = broker_url.parse()?;
+            let physical_broker_url = if c.is_proxy_through_service_url() {
+                service_url.to_owned()
+            } else {
+                broker_url.to_owned()
+            };
+
+            if !c.is_redirect() {
+                return Ok((broker_url, physical_broker_url));
+            }
+
+            // The proxy does the redirected lookups itself.
+            logical_url = physical_broker_url.to_owned();
+            physical_url = physical_broker_url;
+            authoritative = c.is_authoritative();
+        }
+
+        Err(ConnectionPoolError::TooManyLookupRedirects)
+    }
+
+    // Returns 0 for a non-partitioned topic.
+    pub async fn get_partitioned_topic_metadata_by_service_url(
+        &self,
+        service_url_provider: &dyn ServiceUrlProvider,
+        topic: impl AsRef<str>,
+    ) -> Result<u32, ConnectionPoolError> {
+        let (_, session) = self.get_by_service_url(service_url_provider).await?;
+
+        let c = session
+            .raw_get_partitioned_topic_metadata(PartitionedTopicMetadataCommand::new(topic))
+            .await?;
+
+        Ok(c.get_partitions())
+    }
+
+    // The complete names, with the partitions of the partitioned topics.
+    pub async fn get_topics_of_namespace_by_service_url(
+        &self,
+        service_url_provider: &dyn ServiceUrlProvider,
+        namespace: &str,
+        mode: TopicsOfNamespaceMode,
+    ) -> Result<Vec<String>, ConnectionPoolError> {
+        let (_, session) = self.get_by_service_url(service_url_provider).await?;
+
+        Ok(session.get_topics_of_namespace(namespace, mode).await?)
+    }
+
+    // Subscribes on the broker that owns the topic.
+    pub async fn create_consumer_by_service_url(
+        &self,
+        service_url_provider: &dyn ServiceUrlProvider,
+        subscribe_command: SubscribeCommand,
+        config: AsyncConsumerConfig,
+    ) -> Result<AsyncConsumer, ConnectionPoolError> {
+        let (logical_url, physical_url) = self
+            .lookup_topic_by_service_url(
+                service_url_provider,
+                subscribe_command.get_topic().to_owned(),
+            )
+            .await?;
+        let session = self.get(&logical_url, &physical_url).await?;
+
+        Ok(session
+            .raw_create_consumer_with_config(subscribe_command, config)
+            .await?)
+    }
+
+    // Subscribes to every topic, to every partition of the partitioned ones,
+    // the topic of the subscribe command is replaced.
+    pub async fn create_multi_topics_consumer(
+        &self,
+        service_url_provider: &dyn ServiceUrlProvider,
+        topics: &[impl AsRef<str>],
+        subscribe_command: SubscribeCommand,
+        config: AsyncConsumerConfig,
+    ) -> Result<AsyncMultiTopicsConsumer, ConnectionPoolError> {
+        let topics = try_join_all(topics.iter().map(|topic| async move {
+            let topic: TopicName = topic.as_ref().parse()?;
+            let partitions = self
+                .get_partitioned_topic_metadata_by_service_url(service_url_provider, &topic)
+                .await?;
+
+            Ok::<_, ConnectionPoolError>(if partitions == 0 {
+                vec![topic]
+            } else {
+                (0..partitions).map(|i| topic.get_partition(i)).collect()
+            })
+        }))
+        .await?;
+
+        let consumers = try_join_all(topics.into_iter().flatten().map(|topic| {
+            let mut subscribe_command = subscribe_command.to_owned();
+            subscribe_command.set_topic(&topic);
+            self.create_consumer_by_service_url(
+                service_url_provider,
+                subscribe_command,
+                config.to_owned(),
+            )
+        }))
+        .await?;
+
+        Ok(AsyncMultiTopicsConsumer::new(consumers))
+    }
+
+    async fn connect(
+        &self,
+        logical_url: &Url,
+        physical_url: &Url,
+    ) -> Result<AsyncSession, ConnectionPoolError> {
+        let stream = (self.connector)(physical_url.to_owned()).await?;
+        let connection = AsyncConnection::new(stream, self.connection_config.to_owned());
+
+        let mut connect_command = self.connect_command.to_owned();
+        if logical_url != physical_url {
+            connect_command.set_proxy_to_broker_url(logical_url);
+        }
+
+        let (mut session, mut handler) = AsyncClient::new(connection)
+            .raw_connect(connect_command)
+            .await?;
+
+        if let Some(memory_limit) = &self.memory_limit {
+            session.set_memory_limit(memory_limit.to_owned());
+        }
+
+        if let Some(reconnect_config) = &self.reconnect_config {
+            let connector = self.connector.to_owned();
+            let connection_config = self.connection_config.to_owned();
+            let physical_url = physical_url.to_owned();
+            handler.set_reconnect(
+                move || {
+                    let connecting = connector(physical_url.to_owned());
+                    let connection_config = connection_config.to_owned();
+                    async move { Ok(AsyncConnection::new(connecting.await?, connection_config)) }
+                },
+                reconnect_config.to_owned(),
+            );
+        }
+
+        (self.spawner)(
+            async move {
+                loop {
+                    match handler.handle().await {
+                        Ok(_) => {}
+                        Err((err, _)) => {
+                            error!("{:?}", err);
+                            break;
+                        }
+                    }
+                }
+            }
+            .boxed(),
+        );
+
+        Ok(session)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ConnectionPoolError {
+    #[error("ConnectError {0:?}")]
+    ConnectError(#[from] IoError),
+    #[error("RawConnectError {0:?}")]
+    RawConnectError(#[from] RawConnectError),
+    #[error("RawLookupTopicError {0:?}")]
+    RawLookupTopicError(#[from] RawLookupTopicError),
+    #[error("RawGetPartitionedTopicMetadataError {0:?}")]
+    RawGetPartitionedTopicMetadataError(#[from] RawGetPartitionedTopicMetadataError),
+    #[error("RawGetTopicsOfNamespaceError {0:?}")]
+    RawGetTopicsOfNamespaceError(#[from] RawGetTopicsOfNamespaceError),
+    #[error("RawCreateConsumerError {0:?}")]
+    RawCreateConsumerError(#[from] RawCreateConsumerError),
+    #[error("TopicNameParseError {0:?}")]
+    TopicNameParseError(#[from] TopicNameParseError),
+    #[error("BrokerServiceUrlMissing")]
+    BrokerServiceUrlMissing,
+    #[error("UrlParseError {0:?}")]
+    UrlParseError(#[from] UrlParseError),
+    #[error("TooManyLookupRedirects")]
+    TooManyLookupRedirects,
+}
@@ -1,5 +1,9 @@
 use std::fmt;
 
+use futures_util::lock::Mutex as AsyncMutex;
+
+use crate::{pending_limit::PendingLimit, sync_wrapper::SyncWrapper};
+
 use pulsar_binary_protocol_spec::{
     client_channel::AC_Sender,
     client_channel_messages::ProducerSendHandlerChannelMessage,
@@ -8,6 +12,54 @@ use pulsar_binary_protocol_spec::{
 };
 
 mod raw_send;
+mod send;
+
+pub use raw_send::{RawSendError, SendFuture};
+
+use send::ProducerSinkState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProducerQueueFullPolicy {
+    // The send waits until older sends are responded.
+    Block,
+    // The send fails with ProducerQueueIsFull.
+    Fail,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct AsyncProducerConfig {
+    max_pending_messages: Option<usize>,
+    max_pending_bytes: Option<Option<usize>>,
+    queue_full_policy: Option<ProducerQueueFullPolicy>,
+}
+impl AsyncProducerConfig {
+    // The messages of a batch count one by one.
+    pub fn set_max_pending_messages(&mut self, n: usize) -> &mut Self {
+        self.max_pending_messages = Some(n);
+        self
+    }
+    fn get_max_pending_messages(&self) -> usize {
+        self.max_pending_messages.unwrap_or(1000)
+    }
+
+    // The size of the uncompressed payloads, None disables it.
+    pub fn set_max_pending_bytes(&mut self, n: impl Into<Option<usize>>) -> &mut Self {
+        self.max_pending_bytes = Some(n.into());
+        self
+    }
+    fn get_max_pending_bytes(&self) -> Option<usize> {
+        self.max_pending_bytes.unwrap_or(None)
+    }
+
+    pub fn set_queue_full_policy(&mut self, policy: ProducerQueueFullPolicy) -> &mut Self {
+        self.queue_full_policy = Some(policy);
+        self
+    }
+    fn get_queue_full_policy(&self) -> ProducerQueueFullPolicy {
+        self.queue_full_policy
+            .unwrap_or(ProducerQueueFullPolicy::Block)
+    }
+}
 
 pub struct AsyncProducer {
     sender: AC_Sender<ProducerSendHandlerChannelMessage>,
@@ -15,18 +67,43 @@ pub struct AsyncProducer {
     producer_success_command: ProducerSuccessCommand,
     //
     sequence_id_builder: SequenceIdBuilder,
+    //
+    pending_messages: PendingLimit,
+    pending_bytes: Option<PendingLimit>,
+    // Shared by the producers of the client.
+    memory_limit: Option<PendingLimit>,
+    queue_full_policy: ProducerQueueFullPolicy,
+    //
+    enqueuing: AsyncMutex<()>,
+    sink_state: SyncWrapper<ProducerSinkState>,
 }
 impl AsyncProducer {
     pub(crate) fn new(
         sender: AC_Sender<ProducerSendHandlerChannelMessage>,
         producer_command: ProducerCommand,
         producer_success_command: ProducerSuccessCommand,
+        config: AsyncProducerConfig,
+        memory_limit: Option<PendingLimit>,
     ) -> Self {
+        // Resumes after the last sequence id persisted by the broker, with deduplication.
+        let last_sequence_id = producer_success_command
+            .get_last_sequence_id()
+            .map(u64::from)
+            .max(producer_command.get_initial_sequence_id());
+        let sequence_id_builder =
+            SequenceIdBuilder::new(last_sequence_id.map(|v| v.saturating_add(1)));
+
         Self {
             sender,
             producer_command,
             producer_success_command,
-            sequence_id_builder: SequenceIdBuilder::default(),
+            sequence_id_builder,
+            pending_messages: PendingLimit::new(config.get_max_pending_messages()),
+            pending_bytes: config.get_max_pending_bytes().map(PendingLimit::new),
+            memory_limit,
+            queue_full_policy: config.get_queue_full_policy(),
+            enqueuing: AsyncMutex::new(()),
+            sink_state: SyncWrapper::new(ProducerSinkState::default()),
         }
     }
 
@@ -38,9 +115,22 @@ impl AsyncProducer {
         self.producer_success_command.get_producer_name()
     }
 
+    pub fn get_last_sequence_id(&self) -> Option<SequenceId> {
+        self.producer_success_command.get_last_sequence_id()
+    }
+
     pub fn next_sequence_id(&self) -> SequenceId {
         self.sequence_id_builder.next()
     }
+
+    // True once the connection is lost or the handler is dropped.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    pub fn get_pending_messages(&self) -> usize {
+        self.pending_messages.get_used()
+    }
 }
 
 impl fmt::Debug for AsyncProducer {
@@ -48,6 +138,8 @@ impl fmt::Debug for AsyncProducer {
         f.debug_struct("AsyncProducer")
             .field("producer_command", &self.producer_command)
             .field("producer_success_command", &self.producer_success_command)
+            .field("pending_messages", &self.pending_messages)
+            .field("pending_bytes", &self.pending_bytes)
             .finish()
     }
 }
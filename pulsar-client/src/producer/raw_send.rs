@@ -1,4 +1,12 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
 use pulsar_binary_protocol_spec::{
+    client_channel::{AC_Sender, FC_Receiver},
     client_channel_messages::{
         handler_reply_producer_channel_message::HandlerReplyProducerSendChannelMessage,
         ProducerSendHandlerChannelMessage,
@@ -9,7 +17,9 @@ use pulsar_binary_protocol_spec::{
 };
 use thiserror::Error;
 
-use super::AsyncProducer;
+use crate::pending_limit::PendingLimit;
+
+use super::{AsyncProducer, ProducerQueueFullPolicy};
 
 #[derive(Error, Debug)]
 pub enum RawSendError {
@@ -19,26 +29,125 @@ pub enum RawSendError {
     RespondError(ProducerSendRespondError),
     #[error("ChannelClosed")]
     ChannelClosed,
+    #[error("ProducerQueueIsFull")]
+    ProducerQueueIsFull,
 }
 impl AsyncProducer {
+    // Waits or fails by the queue full policy, once the pending sends reach a limit.
     pub async fn raw_send(
         &self,
         send_command: SendCommand,
     ) -> Result<SendReceiptCommand, RawSendError> {
-        let (sender, receiver) = channel::<HandlerReplyProducerSendChannelMessage>();
-
-        self.sender
-            .send(ProducerSendHandlerChannelMessage::Send(
-                send_command,
-                sender,
-            ))
-            .await
-            .map_err(|_| RawSendError::ProducerChannelClosed)?;
-
-        match receiver.await {
-            Ok(Ok(send_receipt_command)) => Ok(send_receipt_command),
-            Ok(Err(err)) => Err(RawSendError::RespondError(err)),
-            Err(_) => Err(RawSendError::ChannelClosed),
+        let permits = self.acquire_pending_permits(&send_command).await?;
+
+        enqueue(&self.sender, send_command, permits).await?.await
+    }
+
+    async fn acquire_pending_permits(
+        &self,
+        send_command: &SendCommand,
+    ) -> Result<PendingPermits, RawSendError> {
+        let limits = self.get_pending_limits(
+            send_command.get_num_messages(),
+            send_command.get_payload_size(),
+        );
+
+        acquire_pending_permits(limits, self.queue_full_policy).await
+    }
+
+    pub(super) fn get_pending_limits(
+        &self,
+        num_messages: usize,
+        payload_size: usize,
+    ) -> PendingLimits {
+        std::iter::once((self.pending_messages.to_owned(), num_messages))
+            .chain(
+                self.pending_bytes
+                    .iter()
+                    .map(|limit| (limit.to_owned(), payload_size)),
+            )
+            .chain(
+                self.memory_limit
+                    .iter()
+                    .map(|limit| (limit.to_owned(), payload_size)),
+            )
+            .collect()
+    }
+}
+
+pub(super) type PendingLimits = Vec<(PendingLimit, usize)>;
+
+pub(super) async fn acquire_pending_permits(
+    limits: PendingLimits,
+    queue_full_policy: ProducerQueueFullPolicy,
+) -> Result<PendingPermits, RawSendError> {
+    // Always in the same order, so that blocked sends do not wait on each other.
+    let mut permits = PendingPermits(vec![]);
+    for (limit, n) in limits {
+        match queue_full_policy {
+            ProducerQueueFullPolicy::Block => limit.acquire(n).await,
+            ProducerQueueFullPolicy::Fail => {
+                if !limit.try_acquire(n) {
+                    return Err(RawSendError::ProducerQueueIsFull);
+                }
+            }
         }
+        permits.0.push((limit, n));
+    }
+
+    Ok(permits)
+}
+
+// Hands the send to the handler, the returned future resolves once the broker responds.
+pub(super) async fn enqueue(
+    sender: &AC_Sender<ProducerSendHandlerChannelMessage>,
+    send_command: SendCommand,
+    permits: PendingPermits,
+) -> Result<SendFuture, RawSendError> {
+    let (s, r) = channel::<HandlerReplyProducerSendChannelMessage>();
+
+    sender
+        .send(ProducerSendHandlerChannelMessage::Send(
+            send_command,
+            s,
+            Some(Box::new(permits)),
+        ))
+        .await
+        .map_err(|_| RawSendError::ProducerChannelClosed)?;
+
+    Ok(SendFuture { receiver: r })
+}
+
+// Held by the handler, released once the send is responded, failed or timed out.
+pub(super) struct PendingPermits(Vec<(PendingLimit, usize)>);
+
+impl Drop for PendingPermits {
+    fn drop(&mut self) {
+        for (limit, n) in self.0.drain(..) {
+            limit.release(n);
+        }
+    }
+}
+
+pub struct SendFuture {
+    receiver: FC_Receiver<HandlerReplyProducerSendChannelMessage>,
+}
+
+impl Future for SendFuture {
+    type Output = Result<SendReceiptCommand, RawSendError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match Pin::new(&mut self.get_mut().receiver).poll(cx) {
+            Poll::Ready(Ok(Ok(send_receipt_command))) => Poll::Ready(Ok(send_receipt_command)),
+            Poll::Ready(Ok(Err(err))) => Poll::Ready(Err(RawSendError::RespondError(err))),
+            Poll::Ready(Err(_)) => Poll::Ready(Err(RawSendError::ChannelClosed)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl fmt::Debug for SendFuture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendFuture").finish()
     }
 }
@@ -0,0 +1,121 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_util::{
+    future::{BoxFuture, FutureExt as _},
+    sink::Sink,
+    stream::{FuturesUnordered, StreamExt as _},
+};
+use pulsar_binary_protocol_spec::{types::OutgoingMessage, SendReceiptCommand};
+
+use super::{
+    raw_send::{acquire_pending_permits, enqueue, RawSendError, SendFuture},
+    AsyncProducer,
+};
+
+#[derive(Default)]
+pub(super) struct ProducerSinkState {
+    enqueuing: Option<BoxFuture<'static, Result<SendFuture, RawSendError>>>,
+    sending: FuturesUnordered<SendFuture>,
+}
+
+impl AsyncProducer {
+    // Returns once the message is handed to the handler, the messages are published in the
+    // order of the calls, whatever the order their SendFutures are awaited in.
+    // The pending permits are held until the send is responded, failed or timed out,
+    // also when the SendFuture is dropped.
+    pub async fn send_async(&self, message: OutgoingMessage) -> Result<SendFuture, RawSendError> {
+        // Locked first, so that the blocked sends take the permits in the order of the calls,
+        // and the sequence ids are increasing in the order of the enqueuing, for deduplication.
+        let _enqueuing = self.enqueuing.lock().await;
+
+        let payload_size = message.get_payload().len();
+        let limits = self.get_pending_limits(1, payload_size);
+        let permits = acquire_pending_permits(limits, self.queue_full_policy).await?;
+
+        let send_command = message.into_send_command(self.next_sequence_id());
+
+        enqueue(&self.sender, send_command, permits).await
+    }
+
+    pub async fn send(&self, message: OutgoingMessage) -> Result<SendReceiptCommand, RawSendError> {
+        self.send_async(message).await?.await
+    }
+
+    fn poll_enqueued(
+        sink_state: &mut ProducerSinkState,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), RawSendError>> {
+        if let Some(enqueuing) = sink_state.enqueuing.as_mut() {
+            let send_future = match enqueuing.poll_unpin(cx) {
+                Poll::Ready(res) => res,
+                Poll::Pending => return Poll::Pending,
+            };
+            sink_state.enqueuing = None;
+            sink_state.sending.push(send_future?);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+// The permits of the pending limits are the backpressure, the receipts are checked on flush.
+impl Sink<OutgoingMessage> for AsyncProducer {
+    type Error = RawSendError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let sink_state = self.get_mut().sink_state.get_mut();
+
+        // Fails early if a responded send failed.
+        while let Poll::Ready(Some(res)) = sink_state.sending.poll_next_unpin(cx) {
+            res?;
+        }
+
+        Self::poll_enqueued(sink_state, cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, message: OutgoingMessage) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+
+        let payload_size = message.get_payload().len();
+        let limits = this.get_pending_limits(1, payload_size);
+        let queue_full_policy = this.queue_full_policy;
+        let send_command = message.into_send_command(this.next_sequence_id());
+        let sender = this.sender.to_owned();
+
+        let sink_state = this.sink_state.get_mut();
+        debug_assert!(sink_state.enqueuing.is_none());
+        sink_state.enqueuing = Some(
+            async move {
+                let permits = acquire_pending_permits(limits, queue_full_policy).await?;
+                enqueue(&sender, send_command, permits).await
+            }
+            .boxed(),
+        );
+
+        Ok(())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        match self.as_mut().poll_ready(cx) {
+            Poll::Ready(Ok(_)) => {}
+            res => return res,
+        }
+
+        let sink_state = self.get_mut().sink_state.get_mut();
+
+        loop {
+            match sink_state.sending.poll_next_unpin(cx) {
+                Poll::Ready(Some(res)) => res?,
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
+                Poll::Pending => return Poll::Pending,
+            };
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_flush(cx)
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+#[derive(Default, Debug, Clone)]
+pub struct ReconnectConfig {
+    initial_delay: Option<Duration>,
+    max_delay: Option<Duration>,
+    max_retries: Option<usize>,
+}
+impl ReconnectConfig {
+    pub fn set_initial_delay(&mut self, dur: Duration) -> &mut Self {
+        self.initial_delay = Some(dur);
+        self
+    }
+    #[cfg(any(feature = "futures_io", feature = "tokio02_io", feature = "tokio_io",))]
+    fn get_initial_delay(&self) -> Duration {
+        self.initial_delay
+            .unwrap_or_else(|| Duration::from_millis(100))
+    }
+
+    pub fn set_max_delay(&mut self, dur: Duration) -> &mut Self {
+        self.max_delay = Some(dur);
+        self
+    }
+    #[cfg(any(feature = "futures_io", feature = "tokio02_io", feature = "tokio_io",))]
+    fn get_max_delay(&self) -> Duration {
+        self.max_delay.unwrap_or_else(|| Duration::from_secs(60))
+    }
+
+    // Retries forever if not set.
+    pub fn set_max_retries(&mut self, n: usize) -> &mut Self {
+        self.max_retries = Some(n);
+        self
+    }
+
+    // Exponential backoff, with a random jitter of up to half of the delay.
+    #[cfg(any(feature = "futures_io", feature = "tokio02_io", feature = "tokio_io",))]
+    pub(crate) fn get_delay(&self, attempt: usize) -> Duration {
+        let delay = self
+            .get_initial_delay()
+            .checked_mul(1 << attempt.min(16) as u32)
+            .unwrap_or_else(|| self.get_max_delay());
+        let delay = delay.min(self.get_max_delay());
+
+        let half = delay / 2;
+        half + half.mul_f64(fastrand::f64())
+    }
+
+    #[cfg(any(feature = "futures_io", feature = "tokio02_io", feature = "tokio_io",))]
+    pub(crate) fn is_retries_exhausted(&self, attempt: usize) -> bool {
+        match self.max_retries {
+            Some(max_retries) => attempt >= max_retries,
+            None => false,
+        }
+    }
+}
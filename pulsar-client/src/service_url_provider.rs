@@ -0,0 +1,34 @@
+use std::sync::RwLock;
+
+use pulsar_binary_protocol_spec::broker_service_url::ServiceUrl;
+
+// Asked on every connection to the service, so the hosts can be changed at runtime,
+// e.g. for migrating to another cluster.
+pub trait ServiceUrlProvider: Send + Sync {
+    fn get_service_url(&self) -> ServiceUrl;
+}
+
+impl ServiceUrlProvider for ServiceUrl {
+    fn get_service_url(&self) -> ServiceUrl {
+        self.to_owned()
+    }
+}
+
+#[derive(Debug)]
+pub struct SwappableServiceUrlProvider(RwLock<ServiceUrl>);
+impl SwappableServiceUrlProvider {
+    pub fn new(service_url: ServiceUrl) -> Self {
+        Self(RwLock::new(service_url))
+    }
+
+    pub fn swap(&self, service_url: ServiceUrl) -> ServiceUrl {
+        let mut inner = self.0.write().expect("poisoned");
+        std::mem::replace(&mut inner, service_url)
+    }
+}
+
+impl ServiceUrlProvider for SwappableServiceUrlProvider {
+    fn get_service_url(&self) -> ServiceUrl {
+        self.0.read().expect("poisoned").to_owned()
+    }
+}
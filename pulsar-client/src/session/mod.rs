@@ -5,13 +5,25 @@ use pulsar_binary_protocol_spec::{
     ConnectCommand, ConnectedCommand,
 };
 
+use crate::pending_limit::PendingLimit;
+
 mod raw_create_consumer;
 mod raw_create_producer;
+mod raw_get_partitioned_topic_metadata;
+mod raw_get_topics_of_namespace;
+mod raw_lookup_topic;
+
+pub use raw_create_consumer::RawCreateConsumerError;
+pub use raw_create_producer::RawCreateProducerError;
+pub use raw_get_partitioned_topic_metadata::RawGetPartitionedTopicMetadataError;
+pub use raw_get_topics_of_namespace::RawGetTopicsOfNamespaceError;
+pub use raw_lookup_topic::RawLookupTopicError;
 
 pub struct AsyncSession {
     sender: AC_Sender<SessionSendHandlerChannelMessage>,
     connect_command: ConnectCommand,
     connected_command: ConnectedCommand,
+    memory_limit: Option<PendingLimit>,
 }
 impl AsyncSession {
     #[cfg(any(feature = "futures_io", feature = "tokio02_io", feature = "tokio_io",))]
@@ -24,8 +36,20 @@ impl AsyncSession {
             sender,
             connect_command: command_connect,
             connected_command: command_connected,
+            memory_limit: None,
         }
     }
+
+    // Shared by the producers that are created afterwards, limits the bytes of their pending sends.
+    pub fn set_memory_limit(&mut self, memory_limit: PendingLimit) -> &mut Self {
+        self.memory_limit = Some(memory_limit);
+        self
+    }
+
+    // True once the connection is lost or the handler is dropped.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
 }
 
 impl fmt::Debug for AsyncSession {
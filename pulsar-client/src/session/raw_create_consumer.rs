@@ -9,7 +9,7 @@ use pulsar_binary_protocol_spec::{
 };
 use thiserror::Error;
 
-use crate::consumer::AsyncConsumer;
+use crate::consumer::{AsyncConsumer, AsyncConsumerConfig};
 
 use super::AsyncSession;
 
@@ -26,6 +26,15 @@ impl AsyncSession {
     pub async fn raw_create_consumer(
         &self,
         subscribe_command: SubscribeCommand,
+    ) -> Result<AsyncConsumer, RawCreateConsumerError> {
+        self.raw_create_consumer_with_config(subscribe_command, AsyncConsumerConfig::default())
+            .await
+    }
+
+    pub async fn raw_create_consumer_with_config(
+        &self,
+        subscribe_command: SubscribeCommand,
+        config: AsyncConsumerConfig,
     ) -> Result<AsyncConsumer, RawCreateConsumerError> {
         let (sender, receiver) = channel::<HandlerReplySessionCreateConsumerChannelMessage>();
 
@@ -38,9 +47,12 @@ impl AsyncSession {
             .map_err(|_| RawCreateConsumerError::SessionChannelClosed)?;
 
         match receiver.await {
-            Ok(Ok((subscribe_command, success_command, s))) => {
-                Ok(AsyncConsumer::new(s, subscribe_command, success_command))
-            }
+            Ok(Ok((subscribe_command, success_command, s))) => Ok(AsyncConsumer::new(
+                s,
+                subscribe_command,
+                success_command,
+                config,
+            )),
             Ok(Err(err)) => Err(RawCreateConsumerError::RespondError(err)),
             Err(_) => Err(RawCreateConsumerError::ChannelClosed),
         }
@@ -9,7 +9,7 @@ use pulsar_binary_protocol_spec::{
 };
 use thiserror::Error;
 
-use crate::producer::AsyncProducer;
+use crate::producer::{AsyncProducer, AsyncProducerConfig};
 
 use super::AsyncSession;
 
@@ -26,6 +26,15 @@ impl AsyncSession {
     pub async fn raw_create_producer(
         &self,
         producer_command: ProducerCommand,
+    ) -> Result<AsyncProducer, RawCreateProducerError> {
+        self.raw_create_producer_with_config(producer_command, AsyncProducerConfig::default())
+            .await
+    }
+
+    pub async fn raw_create_producer_with_config(
+        &self,
+        producer_command: ProducerCommand,
+        config: AsyncProducerConfig,
     ) -> Result<AsyncProducer, RawCreateProducerError> {
         let (sender, receiver) = channel::<HandlerReplySessionCreateProducerChannelMessage>();
 
@@ -42,6 +51,8 @@ impl AsyncSession {
                 s,
                 producer_command,
                 producer_success_command,
+                config,
+                self.memory_limit.to_owned(),
             )),
             Ok(Err(err)) => Err(RawCreateProducerError::RespondError(err)),
             Err(_) => Err(RawCreateProducerError::ChannelClosed),
@@ -0,0 +1,48 @@
+use pulsar_binary_protocol_spec::{
+    client_channel_messages::{
+        handler_reply_session_channel_message::HandlerReplySessionPartitionedTopicMetadataChannelMessage,
+        SessionSendHandlerChannelMessage,
+    },
+    client_responds::SessionPartitionedTopicMetadataRespondError,
+    futures_channel::oneshot::channel,
+    PartitionedTopicMetadataCommand, PartitionedTopicMetadataResponseCommand,
+};
+use thiserror::Error;
+
+use super::AsyncSession;
+
+#[derive(Error, Debug)]
+pub enum RawGetPartitionedTopicMetadataError {
+    #[error("SessionChannelClosed")]
+    SessionChannelClosed,
+    #[error("RespondError {0:?}")]
+    RespondError(SessionPartitionedTopicMetadataRespondError),
+    #[error("ChannelClosed")]
+    ChannelClosed,
+}
+impl AsyncSession {
+    // Any broker responds, not only the one that owns the topic.
+    pub async fn raw_get_partitioned_topic_metadata(
+        &self,
+        partitioned_topic_metadata_command: PartitionedTopicMetadataCommand,
+    ) -> Result<PartitionedTopicMetadataResponseCommand, RawGetPartitionedTopicMetadataError> {
+        let (sender, receiver) =
+            channel::<HandlerReplySessionPartitionedTopicMetadataChannelMessage>();
+
+        self.sender
+            .send(SessionSendHandlerChannelMessage::PartitionedTopicMetadata(
+                partitioned_topic_metadata_command,
+                sender,
+            ))
+            .await
+            .map_err(|_| RawGetPartitionedTopicMetadataError::SessionChannelClosed)?;
+
+        match receiver.await {
+            Ok(Ok((_, partitioned_topic_metadata_response_command))) => {
+                Ok(partitioned_topic_metadata_response_command)
+            }
+            Ok(Err(err)) => Err(RawGetPartitionedTopicMetadataError::RespondError(err)),
+            Err(_) => Err(RawGetPartitionedTopicMetadataError::ChannelClosed),
+        }
+    }
+}
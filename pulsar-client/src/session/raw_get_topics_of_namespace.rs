@@ -0,0 +1,61 @@
+use pulsar_binary_protocol_spec::{
+    client_channel_messages::{
+        handler_reply_session_channel_message::HandlerReplySessionGetTopicsOfNamespaceChannelMessage,
+        SessionSendHandlerChannelMessage,
+    },
+    client_responds::SessionGetTopicsOfNamespaceRespondError,
+    futures_channel::oneshot::channel,
+    types::TopicsOfNamespaceMode,
+    GetTopicsOfNamespaceCommand, GetTopicsOfNamespaceResponseCommand,
+};
+use thiserror::Error;
+
+use super::AsyncSession;
+
+#[derive(Error, Debug)]
+pub enum RawGetTopicsOfNamespaceError {
+    #[error("SessionChannelClosed")]
+    SessionChannelClosed,
+    #[error("RespondError {0:?}")]
+    RespondError(SessionGetTopicsOfNamespaceRespondError),
+    #[error("ChannelClosed")]
+    ChannelClosed,
+}
+impl AsyncSession {
+    // Any broker responds, not only the one that owns the namespace.
+    pub async fn raw_get_topics_of_namespace(
+        &self,
+        get_topics_of_namespace_command: GetTopicsOfNamespaceCommand,
+    ) -> Result<GetTopicsOfNamespaceResponseCommand, RawGetTopicsOfNamespaceError> {
+        let (sender, receiver) = channel::<HandlerReplySessionGetTopicsOfNamespaceChannelMessage>();
+
+        self.sender
+            .send(SessionSendHandlerChannelMessage::GetTopicsOfNamespace(
+                get_topics_of_namespace_command,
+                sender,
+            ))
+            .await
+            .map_err(|_| RawGetTopicsOfNamespaceError::SessionChannelClosed)?;
+
+        match receiver.await {
+            Ok(Ok((_, get_topics_of_namespace_response_command))) => {
+                Ok(get_topics_of_namespace_response_command)
+            }
+            Ok(Err(err)) => Err(RawGetTopicsOfNamespaceError::RespondError(err)),
+            Err(_) => Err(RawGetTopicsOfNamespaceError::ChannelClosed),
+        }
+    }
+
+    // The namespace is tenant/namespace.
+    pub async fn get_topics_of_namespace(
+        &self,
+        namespace: &str,
+        mode: TopicsOfNamespaceMode,
+    ) -> Result<Vec<String>, RawGetTopicsOfNamespaceError> {
+        let c = self
+            .raw_get_topics_of_namespace(GetTopicsOfNamespaceCommand::new(namespace, mode))
+            .await?;
+
+        Ok(c.get_topics().to_vec())
+    }
+}
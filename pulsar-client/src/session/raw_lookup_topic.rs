@@ -0,0 +1,44 @@
+use pulsar_binary_protocol_spec::{
+    client_channel_messages::{
+        handler_reply_session_channel_message::HandlerReplySessionLookupTopicChannelMessage,
+        SessionSendHandlerChannelMessage,
+    },
+    client_responds::SessionLookupTopicRespondError,
+    futures_channel::oneshot::channel,
+    LookupTopicCommand, LookupTopicResponseCommand,
+};
+use thiserror::Error;
+
+use super::AsyncSession;
+
+#[derive(Error, Debug)]
+pub enum RawLookupTopicError {
+    #[error("SessionChannelClosed")]
+    SessionChannelClosed,
+    #[error("RespondError {0:?}")]
+    RespondError(SessionLookupTopicRespondError),
+    #[error("ChannelClosed")]
+    ChannelClosed,
+}
+impl AsyncSession {
+    pub async fn raw_lookup_topic(
+        &self,
+        lookup_topic_command: LookupTopicCommand,
+    ) -> Result<LookupTopicResponseCommand, RawLookupTopicError> {
+        let (sender, receiver) = channel::<HandlerReplySessionLookupTopicChannelMessage>();
+
+        self.sender
+            .send(SessionSendHandlerChannelMessage::LookupTopic(
+                lookup_topic_command,
+                sender,
+            ))
+            .await
+            .map_err(|_| RawLookupTopicError::SessionChannelClosed)?;
+
+        match receiver.await {
+            Ok(Ok((_, lookup_topic_response_command))) => Ok(lookup_topic_response_command),
+            Ok(Err(err)) => Err(RawLookupTopicError::RespondError(err)),
+            Err(_) => Err(RawLookupTopicError::ChannelClosed),
+        }
+    }
+}
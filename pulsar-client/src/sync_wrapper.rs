@@ -0,0 +1,15 @@
+use std::sync::Mutex;
+
+// Keeps a struct Sync with a field that is Send only, e.g. the BoxFuture of a Stream or a Sink.
+// The field is only reached through &mut self, so the Mutex is never locked.
+pub(crate) struct SyncWrapper<T>(Mutex<T>);
+
+impl<T> SyncWrapper<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self(Mutex::new(value))
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut().expect("never locked")
+    }
+}
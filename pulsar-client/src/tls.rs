@@ -0,0 +1,297 @@
+use std::{
+    io::{BufReader, Error as IoError, ErrorKind as IoErrorKind},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use pulsar_binary_protocol_spec::{
+    broker_service_url::{Scheme, Url},
+    url::{Host, Url as HttpUrl},
+};
+use rustls::{
+    internal::pemfile, Certificate, ClientConfig, RootCertStore, ServerCertVerified,
+    ServerCertVerifier, TLSError,
+};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tokio_rustls::{client::TlsStream, TlsConnector as RustlsConnector};
+use webpki::DNSNameRef;
+
+#[derive(Default, Debug, Clone)]
+pub struct TlsConnectorConfig {
+    ca_certs_pem: Option<Vec<u8>>,
+    client_auth_pem: Option<(Vec<u8>, Vec<u8>)>,
+    hostname_verification: Option<bool>,
+    server_name: Option<String>,
+    enable_sni: Option<bool>,
+}
+impl TlsConnectorConfig {
+    // Trusts the webpki roots if not set.
+    pub fn set_ca_certs_pem(&mut self, pem: &[u8]) -> &mut Self {
+        self.ca_certs_pem = Some(pem.to_owned());
+        self
+    }
+
+    // mTLS, the key is PKCS#8 or RSA.
+    pub fn set_client_auth_pem(&mut self, cert_chain_pem: &[u8], key_pem: &[u8]) -> &mut Self {
+        self.client_auth_pem = Some((cert_chain_pem.to_owned(), key_pem.to_owned()));
+        self
+    }
+
+    // When disabled, the certificate chain is still verified.
+    pub fn set_hostname_verification(&mut self, enabled: bool) -> &mut Self {
+        self.hostname_verification = Some(enabled);
+        self
+    }
+    fn get_hostname_verification(&self) -> bool {
+        self.hostname_verification.unwrap_or(true)
+    }
+
+    // Sent as SNI and verified instead of the host of the url, required for IP addresses.
+    pub fn set_server_name(&mut self, server_name: &str) -> &mut Self {
+        self.server_name = Some(server_name.to_owned());
+        self
+    }
+
+    pub fn set_enable_sni(&mut self, enabled: bool) -> &mut Self {
+        self.enable_sni = Some(enabled);
+        self
+    }
+    fn get_enable_sni(&self) -> bool {
+        self.enable_sni.unwrap_or(true)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum TlsConnectorError {
+    #[error("InvalidCaCerts")]
+    InvalidCaCerts,
+    #[error("InvalidClientCerts")]
+    InvalidClientCerts,
+    #[error("InvalidClientKey")]
+    InvalidClientKey,
+    #[error("TLSError {0:?}")]
+    TLSError(#[from] TLSError),
+}
+
+// Opens a TcpStream for pulsar:// urls and a TLS stream for pulsar+ssl:// urls,
+// can be the connector of the ConnectionPool.
+#[derive(Clone)]
+pub struct TlsConnector {
+    connector: RustlsConnector,
+    server_name: Option<String>,
+}
+
+impl TlsConnector {
+    pub fn new(config: &TlsConnectorConfig) -> Result<Self, TlsConnectorError> {
+        let mut client_config = ClientConfig::new();
+
+        match &config.ca_certs_pem {
+            Some(pem) => {
+                let (n_valid, _) = client_config
+                    .root_store
+                    .add_pem_file(&mut BufReader::new(&pem[..]))
+                    .map_err(|_| TlsConnectorError::InvalidCaCerts)?;
+                if n_valid == 0 {
+                    return Err(TlsConnectorError::InvalidCaCerts);
+                }
+            }
+            None => {
+                client_config
+                    .root_store
+                    .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+            }
+        }
+
+        if let Some((cert_chain_pem, key_pem)) = &config.client_auth_pem {
+            let cert_chain = pemfile::certs(&mut BufReader::new(&cert_chain_pem[..]))
+                .map_err(|_| TlsConnectorError::InvalidClientCerts)?;
+            if cert_chain.is_empty() {
+                return Err(TlsConnectorError::InvalidClientCerts);
+            }
+
+            let mut keys = pemfile::pkcs8_private_keys(&mut BufReader::new(&key_pem[..]))
+                .map_err(|_| TlsConnectorError::InvalidClientKey)?;
+            if keys.is_empty() {
+                keys = pemfile::rsa_private_keys(&mut BufReader::new(&key_pem[..]))
+                    .map_err(|_| TlsConnectorError::InvalidClientKey)?;
+            }
+            let key = keys
+                .into_iter()
+                .next()
+                .ok_or(TlsConnectorError::InvalidClientKey)?;
+
+            client_config.set_single_client_cert(cert_chain, key)?;
+        }
+
+        if !config.get_hostname_verification() {
+            client_config
+                .dangerous()
+                .set_certificate_verifier(Arc::new(NoHostnameVerifier));
+        }
+
+        client_config.enable_sni = config.get_enable_sni();
+
+        Ok(Self {
+            connector: RustlsConnector::from(Arc::new(client_config)),
+            server_name: config.server_name.to_owned(),
+        })
+    }
+
+    pub async fn connect(&self, url: &Url) -> Result<MaybeTlsStream, IoError> {
+        self.connect_host(&url.host, url.port, url.scheme == Scheme::Tls)
+            .await
+    }
+
+    // For the http:// and https:// urls of the HttpLookup.
+    pub async fn connect_http(&self, url: &HttpUrl) -> Result<MaybeTlsStream, IoError> {
+        let host = url
+            .host()
+            .ok_or_else(|| IoError::new(IoErrorKind::InvalidInput, "host is missing"))?
+            .to_owned();
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| IoError::new(IoErrorKind::InvalidInput, "port is missing"))?;
+
+        self.connect_host(&host, port, url.scheme() == "https")
+            .await
+    }
+
+    async fn connect_host(
+        &self,
+        host: &Host,
+        port: u16,
+        tls: bool,
+    ) -> Result<MaybeTlsStream, IoError> {
+        let tcp_stream = match host {
+            Host::Domain(domain) => TcpStream::connect((domain.as_str(), port)).await?,
+            Host::Ipv4(ip) => TcpStream::connect((*ip, port)).await?,
+            Host::Ipv6(ip) => TcpStream::connect((*ip, port)).await?,
+        };
+        tcp_stream.set_nodelay(true)?;
+
+        if !tls {
+            return Ok(MaybeTlsStream::Tcp(tcp_stream));
+        }
+
+        let server_name = match (&self.server_name, host) {
+            (Some(server_name), _) => server_name.as_str(),
+            (None, Host::Domain(domain)) => domain.as_str(),
+            (None, _) => {
+                return Err(IoError::new(
+                    IoErrorKind::InvalidInput,
+                    "server name is required for IP addresses",
+                ))
+            }
+        };
+        let dns_name = DNSNameRef::try_from_ascii_str(server_name)
+            .map_err(|_| IoError::new(IoErrorKind::InvalidInput, "invalid server name"))?;
+
+        let tls_stream = self.connector.connect(dns_name, tcp_stream).await?;
+
+        Ok(MaybeTlsStream::Tls(Box::new(tls_stream)))
+    }
+}
+
+// Verifies the certificate chain against the roots, but not the name of the server.
+struct NoHostnameVerifier;
+
+static SUPPORTED_SIG_ALGS: &[&webpki::SignatureAlgorithm] = &[
+    &webpki::ECDSA_P256_SHA256,
+    &webpki::ECDSA_P256_SHA384,
+    &webpki::ECDSA_P384_SHA256,
+    &webpki::ECDSA_P384_SHA384,
+    &webpki::ED25519,
+    &webpki::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA384_LEGACY_KEY,
+    &webpki::RSA_PSS_2048_8192_SHA512_LEGACY_KEY,
+    &webpki::RSA_PKCS1_2048_8192_SHA256,
+    &webpki::RSA_PKCS1_2048_8192_SHA384,
+    &webpki::RSA_PKCS1_2048_8192_SHA512,
+    &webpki::RSA_PKCS1_3072_8192_SHA384,
+];
+
+impl ServerCertVerifier for NoHostnameVerifier {
+    fn verify_server_cert(
+        &self,
+        roots: &RootCertStore,
+        presented_certs: &[Certificate],
+        _dns_name: DNSNameRef<'_>,
+        _ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        let (end_entity, intermediates) = presented_certs
+            .split_first()
+            .ok_or(TLSError::NoCertificatesPresented)?;
+
+        let cert = webpki::EndEntityCert::from(&end_entity.0).map_err(TLSError::WebPKIError)?;
+        let intermediates = intermediates.iter().map(|c| &c.0[..]).collect::<Vec<_>>();
+        let trust_anchors = roots
+            .roots
+            .iter()
+            .map(|r| r.to_trust_anchor())
+            .collect::<Vec<_>>();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| TLSError::FailedToGetCurrentTime)?;
+
+        cert.verify_is_valid_tls_server_cert(
+            SUPPORTED_SIG_ALGS,
+            &webpki::TLSServerTrustAnchors(&trust_anchors),
+            &intermediates,
+            webpki::Time::from_seconds_since_unix_epoch(now.as_secs()),
+        )
+        .map_err(TLSError::WebPKIError)?;
+
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+pub enum MaybeTlsStream {
+    Tcp(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for MaybeTlsStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), IoError>> {
+        match self.get_mut() {
+            Self::Tcp(s) => Pin::new(s).poll_read(cx, buf),
+            Self::Tls(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for MaybeTlsStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, IoError>> {
+        match self.get_mut() {
+            Self::Tcp(s) => Pin::new(s).poll_write(cx, buf),
+            Self::Tls(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), IoError>> {
+        match self.get_mut() {
+            Self::Tcp(s) => Pin::new(s).poll_flush(cx),
+            Self::Tls(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), IoError>> {
+        match self.get_mut() {
+            Self::Tcp(s) => Pin::new(s).poll_shutdown(cx),
+            Self::Tls(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
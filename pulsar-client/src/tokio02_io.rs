@@ -1,10 +1,14 @@
 use futures_x_io_timeoutable::{
     futures_x_io::{
         tokio02_io::{AsyncRead, AsyncWrite},
-        tokio02_io_util::AsyncWriteExt,
+        tokio02_io_util::{AsyncReadExt, AsyncWriteExt},
     },
     tokio02_io::rw::AsyncReadWithTimeoutExt,
 };
+use tokio02::{
+    io::{split, ReadHalf, WriteHalf},
+    time::delay_for as sleep,
+};
 
 #[path = "connection.rs"]
 pub mod connection;
@@ -14,3 +18,14 @@ pub mod client;
 
 #[path = "handler/mod.rs"]
 pub mod handler;
+
+#[path = "pool.rs"]
+pub mod pool;
+
+#[cfg(feature = "pattern_consumer")]
+#[path = "pattern_consumer.rs"]
+pub mod pattern_consumer;
+
+#[cfg(feature = "http_lookup")]
+#[path = "http_lookup.rs"]
+pub mod http_lookup;
@@ -1,10 +1,14 @@
 use futures_x_io_timeoutable::{
     futures_x_io::{
         tokio_io::{AsyncRead, AsyncWrite},
-        tokio_io_util::AsyncWriteExt,
+        tokio_io_util::{AsyncReadExt, AsyncWriteExt},
     },
     tokio_io::rw::AsyncReadWithTimeoutExt,
 };
+use tokio::{
+    io::{split, ReadHalf, WriteHalf},
+    time::sleep,
+};
 
 #[path = "connection.rs"]
 pub mod connection;
@@ -14,3 +18,14 @@ pub mod client;
 
 #[path = "handler/mod.rs"]
 pub mod handler;
+
+#[path = "pool.rs"]
+pub mod pool;
+
+#[cfg(feature = "pattern_consumer")]
+#[path = "pattern_consumer.rs"]
+pub mod pattern_consumer;
+
+#[cfg(feature = "http_lookup")]
+#[path = "http_lookup.rs"]
+pub mod http_lookup;
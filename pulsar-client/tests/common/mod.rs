@@ -0,0 +1,424 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::future::join;
+use pulsar_client::{
+    consumer::AsyncConsumer,
+    producer::{AsyncProducer, AsyncProducerConfig},
+    session::AsyncSession,
+    spec::{
+        async_channel::{unbounded, Receiver, Sender},
+        command::{
+            Command, CommandWithParsed, PayloadCommand, PayloadCommandPayload, SimpleCommand,
+        },
+        frame::{FrameParseOutput, FrameParser, FrameRenderer},
+        protos::protobuf::pulsar_api::{
+            BaseCommand, BaseCommand_Type as Type, CommandLookupTopicResponse_LookupType,
+            MessageMetadata, SingleMessageMetadata,
+        },
+        ConnectCommand, ProducerCommand, SubscribeCommand,
+    },
+    tokio_io::{
+        client::AsyncClient,
+        connection::{AsyncConnection, AsyncConnectionConfig},
+        handler::{AsyncHandler, HandleError},
+        pool::ConnectionPool,
+    },
+};
+use tokio::{
+    io::{duplex, AsyncReadExt as _, AsyncWriteExt as _, DuplexStream},
+    task::JoinHandle,
+    time::{sleep, timeout},
+};
+
+// The tests fail instead of hanging.
+pub const WAIT: Duration = Duration::from_secs(5);
+
+pub const SERVICE_URL: &str = "pulsar://localhost:6650";
+
+// Plays the broker side of a connection, the tests script what it reads and writes.
+pub struct FakeBroker {
+    stream: DuplexStream,
+    frame_parser: FrameParser,
+    frame_renderer: FrameRenderer,
+    buf: Vec<u8>,
+}
+
+impl FakeBroker {
+    pub fn new(stream: DuplexStream) -> Self {
+        Self {
+            stream,
+            frame_parser: FrameParser::default(),
+            frame_renderer: FrameRenderer::default(),
+            buf: vec![],
+        }
+    }
+
+    // None once the client closed the connection.
+    pub async fn read(&mut self) -> Option<BaseCommand> {
+        self.read_slowly(Duration::default()).await
+    }
+
+    // Pauses before each read of the stream, so that the client writes slowly.
+    pub async fn read_slowly(&mut self, pause: Duration) -> Option<BaseCommand> {
+        loop {
+            if !self.buf.is_empty() {
+                // The parser keeps the partial frame.
+                match self.frame_parser.parse(&self.buf[..]).expect("parse") {
+                    FrameParseOutput::Completed(n, c) => {
+                        self.buf.drain(..n);
+                        return Some(match c {
+                            CommandWithParsed::Simple(c) => c.message,
+                            CommandWithParsed::Payload(c) => c.message,
+                        });
+                    }
+                    FrameParseOutput::Partial(n) => {
+                        self.buf.drain(..n);
+                    }
+                }
+            }
+
+            sleep(pause).await;
+            let mut buf = vec![0; 64 * 1024];
+            let n = self.stream.read(&mut buf).await.ok()?;
+            if n == 0 {
+                return None;
+            }
+            self.buf.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    // The next command that is not a keepalive, it must be of the type.
+    pub async fn expect(&mut self, t: Type) -> BaseCommand {
+        loop {
+            let c = timeout(WAIT, self.read())
+                .await
+                .expect("timeout")
+                .expect("closed");
+            match c.get_field_type() {
+                Type::PING | Type::PONG => continue,
+                _ => {
+                    assert_eq!(c.get_field_type(), t, "{:?}", c);
+                    return c;
+                }
+            }
+        }
+    }
+
+    pub async fn write(&mut self, message: BaseCommand) {
+        self.write_command(Command::Simple(SimpleCommand { message }))
+            .await
+    }
+
+    pub async fn write_message(&mut self, consumer_id: u64, entry_id: u64, payload: &[u8]) {
+        let mut message = command(Type::MESSAGE);
+        let c = message.mut_message();
+        c.set_consumer_id(consumer_id);
+        c.mut_message_id().set_ledgerId(1);
+        c.mut_message_id().set_entryId(entry_id);
+
+        let mut metadata = MessageMetadata::new();
+        metadata.set_producer_name("p".to_owned());
+        metadata.set_sequence_id(entry_id);
+        metadata.set_publish_time(1);
+
+        self.write_command(Command::Payload(
+            PayloadCommand {
+                message,
+                metadata,
+                payload: PayloadCommandPayload::Single(payload.to_vec()),
+            }
+            .into(),
+        ))
+        .await
+    }
+
+    // One entry with the payloads as the messages of a batch.
+    pub async fn write_batch_message(
+        &mut self,
+        consumer_id: u64,
+        entry_id: u64,
+        payloads: &[&[u8]],
+    ) {
+        let mut message = command(Type::MESSAGE);
+        let c = message.mut_message();
+        c.set_consumer_id(consumer_id);
+        c.mut_message_id().set_ledgerId(1);
+        c.mut_message_id().set_entryId(entry_id);
+
+        let mut metadata = MessageMetadata::new();
+        metadata.set_producer_name("p".to_owned());
+        metadata.set_sequence_id(entry_id);
+        metadata.set_publish_time(1);
+        metadata.set_num_messages_in_batch(payloads.len() as i32);
+
+        let payloads = payloads
+            .iter()
+            .map(|payload| {
+                let mut single_message_metadata = SingleMessageMetadata::new();
+                single_message_metadata.set_payload_size(payload.len() as i32);
+                (single_message_metadata, payload.to_vec())
+            })
+            .collect();
+
+        self.write_command(Command::Payload(
+            PayloadCommand {
+                message,
+                metadata,
+                payload: PayloadCommandPayload::Batch(payloads),
+            }
+            .into(),
+        ))
+        .await
+    }
+
+    async fn write_command(&mut self, command: Command) {
+        let mut buf = vec![];
+        self.frame_renderer
+            .render(command, &mut buf)
+            .expect("render");
+        self.stream.write_all(&buf[..]).await.expect("write");
+    }
+
+    pub async fn accept_connect(&mut self) {
+        self.expect(Type::CONNECT).await;
+
+        let mut c = command(Type::CONNECTED);
+        c.mut_connected().set_server_version("fake".to_owned());
+        c.mut_connected().set_protocol_version(17);
+        self.write(c).await;
+    }
+
+    pub async fn accept_producer(&mut self) -> BaseCommand {
+        let producer = self.expect(Type::PRODUCER).await;
+
+        let mut c = command(Type::PRODUCER_SUCCESS);
+        let producer_success = c.mut_producer_success();
+        producer_success.set_request_id(producer.get_producer().get_request_id());
+        producer_success
+            .set_producer_name(format!("p-{}", producer.get_producer().get_producer_id()));
+        self.write(c).await;
+
+        producer
+    }
+
+    pub async fn accept_subscribe(&mut self) -> BaseCommand {
+        let subscribe = self.expect(Type::SUBSCRIBE).await;
+
+        self.write(success(subscribe.get_subscribe().get_request_id()))
+            .await;
+
+        subscribe
+    }
+
+    pub async fn write_send_receipt(&mut self, send: &BaseCommand, entry_id: u64) {
+        let mut c = command(Type::SEND_RECEIPT);
+        let send_receipt = c.mut_send_receipt();
+        send_receipt.set_producer_id(send.get_send().get_producer_id());
+        send_receipt.set_sequence_id(send.get_send().get_sequence_id());
+        send_receipt.mut_message_id().set_ledgerId(1);
+        send_receipt.mut_message_id().set_entryId(entry_id);
+        self.write(c).await;
+    }
+}
+
+pub fn command(t: Type) -> BaseCommand {
+    let mut c = BaseCommand::new();
+    c.set_field_type(t);
+    c
+}
+
+pub fn ping() -> BaseCommand {
+    let mut c = command(Type::PING);
+    c.mut_ping();
+    c
+}
+
+pub fn success(request_id: u64) -> BaseCommand {
+    let mut c = command(Type::SUCCESS);
+    c.mut_success().set_request_id(request_id);
+    c
+}
+
+pub type HandlerTask = JoinHandle<HandleError>;
+
+pub async fn connect(
+    capacity: usize,
+    config: impl Into<Option<AsyncConnectionConfig>>,
+) -> (AsyncSession, AsyncHandler<DuplexStream>, FakeBroker) {
+    let (stream, broker_stream) = duplex(capacity);
+    let mut broker = FakeBroker::new(broker_stream);
+
+    let connection = AsyncConnection::new(stream, config);
+    let (res, _) = join(
+        AsyncClient::new(connection).raw_connect(ConnectCommand::new("test")),
+        broker.accept_connect(),
+    )
+    .await;
+    let (session, handler) = res.expect("raw_connect");
+
+    (session, handler, broker)
+}
+
+// Runs the handler until the connection is closed.
+pub fn spawn_handler(mut handler: AsyncHandler<DuplexStream>) -> HandlerTask {
+    tokio::spawn(async move {
+        loop {
+            if let Err((err, _)) = handler.handle().await {
+                return err;
+            }
+        }
+    })
+}
+
+pub async fn create_producer(
+    session: &AsyncSession,
+    broker: &mut FakeBroker,
+    config: impl Into<Option<AsyncProducerConfig>>,
+) -> AsyncProducer {
+    let (res, _) = join(
+        session.raw_create_producer_with_config(
+            ProducerCommand::new("t"),
+            config.into().unwrap_or_default(),
+        ),
+        broker.accept_producer(),
+    )
+    .await;
+
+    res.expect("raw_create_producer")
+}
+
+pub async fn create_consumer(
+    session: &AsyncSession,
+    broker: &mut FakeBroker,
+    subscribe_command: SubscribeCommand,
+) -> AsyncConsumer {
+    let (res, _) = join(
+        session.raw_create_consumer(subscribe_command),
+        broker.accept_subscribe(),
+    )
+    .await;
+
+    res.expect("raw_create_consumer")
+}
+
+// What the served brokers answer, the tests may change it while serving.
+#[derive(Default)]
+pub struct BrokerTopics {
+    // By the complete name, the topics not in are not partitioned.
+    pub partitions: HashMap<String, u32>,
+    pub topics_of_namespace: Vec<String>,
+}
+
+// Answers the connects, the lookups, the partitioned metadata, the topics of namespace,
+// the subscribes and the consumer closes. Every command read is passed on to the test.
+pub async fn serve(
+    mut broker: FakeBroker,
+    topics: Arc<Mutex<BrokerTopics>>,
+    commands: Sender<BaseCommand>,
+) {
+    while let Some(c) = broker.read().await {
+        let reply = match c.get_field_type() {
+            Type::CONNECT => {
+                let mut reply = command(Type::CONNECTED);
+                reply.mut_connected().set_server_version("fake".to_owned());
+                reply.mut_connected().set_protocol_version(17);
+                Some(reply)
+            }
+            Type::PING => {
+                let mut reply = command(Type::PONG);
+                reply.mut_pong();
+                Some(reply)
+            }
+            Type::LOOKUP => {
+                let mut reply = command(Type::LOOKUP_RESPONSE);
+                let lookup_topic_response = reply.mut_lookupTopicResponse();
+                lookup_topic_response.set_request_id(c.get_lookupTopic().get_request_id());
+                lookup_topic_response.set_brokerServiceUrl(SERVICE_URL.to_owned());
+                lookup_topic_response.set_response(CommandLookupTopicResponse_LookupType::Connect);
+                Some(reply)
+            }
+            Type::PARTITIONED_METADATA => {
+                let partitions = topics
+                    .lock()
+                    .expect("poisoned")
+                    .partitions
+                    .get(c.get_partitionMetadata().get_topic())
+                    .cloned()
+                    .unwrap_or(0);
+
+                let mut reply = command(Type::PARTITIONED_METADATA_RESPONSE);
+                let partition_metadata_response = reply.mut_partitionMetadataResponse();
+                partition_metadata_response
+                    .set_request_id(c.get_partitionMetadata().get_request_id());
+                partition_metadata_response.set_partitions(partitions);
+                Some(reply)
+            }
+            Type::GET_TOPICS_OF_NAMESPACE => {
+                let topics_of_namespace = topics
+                    .lock()
+                    .expect("poisoned")
+                    .topics_of_namespace
+                    .to_owned();
+
+                let mut reply = command(Type::GET_TOPICS_OF_NAMESPACE_RESPONSE);
+                let get_topics_of_namespace_response = reply.mut_getTopicsOfNamespaceResponse();
+                get_topics_of_namespace_response
+                    .set_request_id(c.get_getTopicsOfNamespace().get_request_id());
+                get_topics_of_namespace_response.set_topics(topics_of_namespace.into());
+                Some(reply)
+            }
+            Type::SUBSCRIBE => Some(success(c.get_subscribe().get_request_id())),
+            Type::CLOSE_CONSUMER => Some(success(c.get_close_consumer().get_request_id())),
+            _ => None,
+        };
+
+        let _ = commands.send(c).await;
+        if let Some(reply) = reply {
+            broker.write(reply).await;
+        }
+    }
+}
+
+// Every connection of the pool is served by a new broker.
+pub fn serve_pool(
+    topics: Arc<Mutex<BrokerTopics>>,
+) -> (ConnectionPool<DuplexStream>, Receiver<BaseCommand>) {
+    let (sender, receiver) = unbounded();
+
+    let pool = ConnectionPool::new(
+        move |_| {
+            let (stream, broker_stream) = duplex(64 * 1024);
+            tokio::spawn(serve(
+                FakeBroker::new(broker_stream),
+                topics.to_owned(),
+                sender.to_owned(),
+            ));
+            async move { Ok(stream) }
+        },
+        |f| {
+            tokio::spawn(f);
+        },
+        ConnectCommand::new("test"),
+    );
+
+    (pool, receiver)
+}
+
+// The next command of the type the served brokers read.
+pub async fn expect_served(commands: &Receiver<BaseCommand>, t: Type) -> BaseCommand {
+    loop {
+        let c = timeout(WAIT, commands.recv())
+            .await
+            .expect("timeout")
+            .expect("closed");
+        if c.get_field_type() == t {
+            return c;
+        }
+    }
+}
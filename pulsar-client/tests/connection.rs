@@ -0,0 +1,105 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use futures_util::future::join_all;
+use pulsar_client::spec::{
+    protos::protobuf::pulsar_api::BaseCommand_Type as Type,
+    types::{OutgoingMessage, SubscribeType},
+    SubscribeCommand,
+};
+use tokio::time::timeout;
+
+use common::{connect, create_consumer, create_producer, ping, spawn_handler, WAIT};
+
+#[tokio::test]
+async fn reads_while_a_write_is_pending() {
+    let (session, handler, mut broker) = connect(4 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+    let consumer = create_consumer(
+        &session,
+        &mut broker,
+        SubscribeCommand::new("t", "s", SubscribeType::Exclusive),
+    )
+    .await;
+
+    // The broker does not read, the writer waits for the stream.
+    let mut send_futures = vec![];
+    for _ in 0..4 {
+        let message = OutgoingMessage::new(vec![b'x'; 64 * 1024]);
+        send_futures.push(producer.send_async(message).await.expect("send_async"));
+    }
+
+    broker.write(ping()).await;
+    broker
+        .write_message(consumer.get_consumer_id().into(), 1, b"foo")
+        .await;
+
+    let message_command = timeout(WAIT, consumer.receive_message())
+        .await
+        .expect("timeout")
+        .expect("receive_message");
+    let messages = message_command.to_messages("t").expect("to_messages");
+    assert_eq!(messages[0].get_payload(), b"foo");
+
+    // The PING was answered too, behind the sends.
+    let (mut n_sends, mut is_ponged) = (0, false);
+    while n_sends < 4 || !is_ponged {
+        let c = timeout(WAIT, broker.read())
+            .await
+            .expect("timeout")
+            .expect("closed");
+        match c.get_field_type() {
+            Type::PONG => is_ponged = true,
+            Type::SEND => {
+                broker.write_send_receipt(&c, n_sends).await;
+                n_sends += 1;
+            }
+            t => panic!("{:?}", t),
+        }
+    }
+
+    for send_future in send_futures {
+        timeout(WAIT, send_future)
+            .await
+            .expect("timeout")
+            .expect("send");
+    }
+}
+
+#[tokio::test]
+async fn writes_in_the_order_of_sending() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+
+    let mut send_futures = vec![];
+    for i in 0..100_u8 {
+        send_futures.push(
+            producer
+                .send_async(OutgoingMessage::new(vec![i; 1024]))
+                .await
+                .expect("send_async"),
+        );
+    }
+
+    let mut sequence_ids = vec![];
+    for entry_id in 0..100 {
+        let send = broker.expect(Type::SEND).await;
+        sequence_ids.push(send.get_send().get_sequence_id());
+        broker.write_send_receipt(&send, entry_id).await;
+    }
+    let first = sequence_ids[0];
+    assert_eq!(sequence_ids, (first..first + 100).collect::<Vec<_>>());
+
+    let receipts = timeout(WAIT, join_all(send_futures))
+        .await
+        .expect("timeout");
+    for (entry_id, receipt) in receipts.into_iter().enumerate() {
+        let message_id = receipt.expect("send").get_message_id().expect("message_id");
+        assert_eq!(message_id.get_entry_id(), entry_id as u64);
+    }
+}
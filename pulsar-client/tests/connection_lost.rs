@@ -0,0 +1,88 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use futures_util::{future::join, StreamExt as _};
+use pulsar_client::{
+    producer::RawSendError,
+    session::RawLookupTopicError,
+    spec::{
+        client_responds::{ProducerSendRespondError, SessionLookupTopicRespondError},
+        protos::protobuf::pulsar_api::BaseCommand_Type as Type,
+        types::{OutgoingMessage, SubscribeType},
+        LookupTopicCommand, SubscribeCommand,
+    },
+    tokio_io::handler::HandleError,
+};
+use tokio::time::timeout;
+
+use common::{connect, create_consumer, create_producer, WAIT};
+
+#[tokio::test]
+async fn fails_everything_that_waits_for_the_broker() {
+    let (session, mut handler, mut broker) = connect(64 * 1024, None).await;
+    let handler_task = tokio::spawn(async move {
+        loop {
+            if let Err((err, _)) = handler.handle().await {
+                return (err, handler);
+            }
+        }
+    });
+
+    let producer = create_producer(&session, &mut broker, None).await;
+    let mut consumer = create_consumer(
+        &session,
+        &mut broker,
+        SubscribeCommand::new("t", "s", SubscribeType::Exclusive),
+    )
+    .await;
+
+    let send_future = producer
+        .send_async(OutgoingMessage::new(b"foo"))
+        .await
+        .expect("send_async");
+    broker.expect(Type::SEND).await;
+
+    let (lookup_res, _) = join(
+        session.raw_lookup_topic(LookupTopicCommand::new("t")),
+        async move {
+            broker.expect(Type::LOOKUP).await;
+            drop(broker);
+        },
+    )
+    .await;
+
+    match lookup_res {
+        Err(RawLookupTopicError::RespondError(SessionLookupTopicRespondError::ConnectionLost)) => {}
+        res => panic!("{:?}", res),
+    }
+    match timeout(WAIT, send_future).await.expect("timeout") {
+        Err(RawSendError::RespondError(ProducerSendRespondError::ConnectionLost)) => {}
+        res => panic!("{:?}", res),
+    }
+
+    let (err, mut handler) = timeout(WAIT, handler_task)
+        .await
+        .expect("timeout")
+        .expect("join");
+    match err {
+        HandleError::ConnectionRequireClose(_) => {}
+        err => panic!("{:?}", err),
+    }
+    match handler.handle().await {
+        Err((HandleError::ConnectionClosed, _)) => {}
+        res => panic!("{:?}", res.map_err(|(err, _)| err)),
+    }
+
+    // The producers, the consumers and the session see the disconnect.
+    assert!(producer.is_closed());
+    assert!(consumer.is_closed());
+    assert!(timeout(WAIT, consumer.next())
+        .await
+        .expect("timeout")
+        .is_none());
+    match session.raw_lookup_topic(LookupTopicCommand::new("t")).await {
+        Err(RawLookupTopicError::SessionChannelClosed) => {}
+        res => panic!("{:?}", res),
+    }
+}
@@ -0,0 +1,118 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use std::time::Duration;
+
+use futures_util::StreamExt as _;
+use pulsar_client::spec::{
+    protos::protobuf::pulsar_api::BaseCommand_Type as Type, types::SubscribeType, SubscribeCommand,
+};
+use tokio::time::{sleep, timeout};
+
+use common::{connect, create_consumer, spawn_handler, WAIT};
+
+#[tokio::test]
+async fn grants_the_permits_of_the_taken_messages() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let mut subscribe_command = SubscribeCommand::new("t", "s", SubscribeType::Exclusive);
+    subscribe_command.set_receiver_queue_size(10);
+    let mut consumer = create_consumer(&session, &mut broker, subscribe_command).await;
+    let consumer_id = u64::from(consumer.get_consumer_id());
+
+    let flow = broker.expect(Type::FLOW).await;
+    assert_eq!(flow.get_flow().get_consumer_id(), consumer_id);
+    assert_eq!(flow.get_flow().get_messagePermits(), 10);
+
+    for entry_id in 0..10 {
+        broker
+            .write_message(consumer_id, entry_id, format!("{}", entry_id).as_bytes())
+            .await;
+    }
+
+    for round in 0..2 {
+        for i in 0..5 {
+            let message = timeout(WAIT, consumer.next())
+                .await
+                .expect("timeout")
+                .expect("end")
+                .expect("message");
+            let entry_id = round * 5 + i;
+            assert_eq!(message.get_message_id().get_entry_id(), entry_id);
+            assert_eq!(message.get_payload(), format!("{}", entry_id).as_bytes());
+        }
+
+        // Half of the receiver queue is taken.
+        let flow = broker.expect(Type::FLOW).await;
+        assert_eq!(flow.get_flow().get_messagePermits(), 5);
+    }
+}
+
+#[tokio::test]
+async fn grants_the_permits_of_the_taken_batches() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let mut subscribe_command = SubscribeCommand::new("t", "s", SubscribeType::Exclusive);
+    subscribe_command.set_receiver_queue_size(10);
+    let mut consumer = create_consumer(&session, &mut broker, subscribe_command).await;
+    let consumer_id = u64::from(consumer.get_consumer_id());
+    broker.expect(Type::FLOW).await;
+
+    // The broker spends one permit for each message of a batch.
+    for entry_id in 0..2 {
+        broker
+            .write_batch_message(consumer_id, entry_id, &[b"0", b"1", b"2", b"3", b"4"])
+            .await;
+    }
+
+    for entry_id in 0..2 {
+        for batch_index in 0..5 {
+            let message = timeout(WAIT, consumer.next())
+                .await
+                .expect("timeout")
+                .expect("end")
+                .expect("message");
+            assert_eq!(message.get_message_id().get_entry_id(), entry_id);
+            assert_eq!(
+                message.get_message_id().get_batch_index(),
+                Some(batch_index)
+            );
+        }
+
+        let flow = broker.expect(Type::FLOW).await;
+        assert_eq!(flow.get_flow().get_messagePermits(), 5);
+    }
+}
+
+#[tokio::test]
+async fn waits_for_the_pushed_message() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let mut subscribe_command = SubscribeCommand::new("t", "s", SubscribeType::Exclusive);
+    subscribe_command.set_receiver_queue_size(2);
+    let mut consumer = create_consumer(&session, &mut broker, subscribe_command).await;
+    let consumer_id = u64::from(consumer.get_consumer_id());
+    broker.expect(Type::FLOW).await;
+
+    // Woken up by the handler, nothing is pushed yet.
+    let next = tokio::spawn(async move {
+        consumer
+            .next()
+            .await
+            .map(|res| res.map(|message| message.into_payload()))
+    });
+    sleep(Duration::from_millis(50)).await;
+
+    broker.write_message(consumer_id, 1, b"foo").await;
+    let payload = timeout(WAIT, next)
+        .await
+        .expect("timeout")
+        .expect("join")
+        .expect("end")
+        .expect("message");
+    assert_eq!(payload, b"foo");
+}
@@ -0,0 +1,245 @@
+#![cfg(all(feature = "tokio_io", feature = "http_lookup"))]
+
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::future::pending;
+use pulsar_client::tokio_io::http_lookup::{HttpLookup, HttpLookupError};
+use tokio::{
+    io::{duplex, AsyncReadExt as _, AsyncWriteExt as _, DuplexStream},
+    time::timeout,
+};
+
+use common::WAIT;
+
+const TOPIC: &str = "persistent://public/default/t";
+const LOOKUP_PATH: &str = "/lookup/v2/topic/persistent/public/default/t";
+const LOOKUP_DATA: &str =
+    r#"{"brokerUrl":"pulsar://b1:6650","brokerUrlTls":"pulsar+ssl://b1:6651"}"#;
+
+type Requests = Arc<Mutex<Vec<(String, String)>>>;
+
+// Serves each connection of the lookup with the response routed by the url, None stalls it.
+// Keeps the url and the head of every request.
+fn http_lookup(
+    service_url: &str,
+    route: fn(&str) -> Option<String>,
+) -> (HttpLookup<DuplexStream>, Requests) {
+    let requests: Requests = Arc::new(Mutex::new(vec![]));
+
+    let requests_cloned = requests.to_owned();
+    let lookup = HttpLookup::new(
+        move |url| {
+            let requests = requests_cloned.to_owned();
+            async move {
+                let (stream, mut server) = duplex(64 * 1024);
+                tokio::spawn(async move {
+                    let mut buf = vec![];
+                    while !buf.ends_with(b"\r\n\r\n") {
+                        let mut b = [0; 1];
+                        if server.read(&mut b).await.unwrap_or(0) == 0 {
+                            return;
+                        }
+                        buf.push(b[0]);
+                    }
+                    requests
+                        .lock()
+                        .expect("poisoned")
+                        .push((url.to_string(), String::from_utf8_lossy(&buf).into_owned()));
+
+                    match route(url.as_str()) {
+                        Some(response) => {
+                            let _ = server.write_all(response.as_bytes()).await;
+                        }
+                        None => pending::<()>().await,
+                    }
+                });
+                Ok(stream)
+            }
+        },
+        service_url,
+    )
+    .expect("new");
+
+    (lookup, requests)
+}
+
+fn response(status: &str, headers: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\n\r\n{}",
+        status,
+        headers,
+        body.len(),
+        body
+    )
+}
+
+fn lookup_data(url: &str) -> Option<String> {
+    if url.ends_with(LOOKUP_PATH) {
+        Some(response("200 OK", "", LOOKUP_DATA))
+    } else {
+        Some(response("500 Internal Server Error", "", ""))
+    }
+}
+
+#[tokio::test]
+async fn returns_the_broker_url() {
+    let (mut lookup, requests) = http_lookup("http://a:8080", lookup_data);
+    lookup
+        .add_header("Authorization", "Bearer x")
+        .expect("add_header");
+
+    let url = timeout(WAIT, lookup.lookup_topic(TOPIC))
+        .await
+        .expect("timeout")
+        .expect("lookup_topic");
+    assert_eq!(url.to_string(), "pulsar://b1:6650/");
+
+    let requests = requests.lock().expect("poisoned");
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].0, format!("http://a:8080{}", LOOKUP_PATH));
+    assert!(requests[0]
+        .1
+        .starts_with(&format!("GET {} HTTP/1.0\r\nHost: a:8080\r\n", LOOKUP_PATH)));
+    assert!(requests[0].1.contains("\r\nAuthorization: Bearer x\r\n"));
+}
+
+#[tokio::test]
+async fn returns_the_tls_broker_url_of_https() {
+    let (lookup, _) = http_lookup("https://a:8443", lookup_data);
+
+    let url = timeout(WAIT, lookup.lookup_topic(TOPIC))
+        .await
+        .expect("timeout")
+        .expect("lookup_topic");
+    assert_eq!(url.to_string(), "pulsar+ssl://b1:6651/");
+}
+
+#[tokio::test]
+async fn returns_the_partitions() {
+    let (lookup, requests) = http_lookup("http://a:8080", |_| {
+        Some(response("200 OK", "", r#"{"partitions":3}"#))
+    });
+
+    let partitions = timeout(WAIT, lookup.get_partitioned_topic_metadata(TOPIC))
+        .await
+        .expect("timeout")
+        .expect("get_partitioned_topic_metadata");
+    assert_eq!(partitions, 3);
+
+    assert_eq!(
+        requests.lock().expect("poisoned")[0].0,
+        "http://a:8080/admin/v2/persistent/public/default/t/partitions"
+    );
+}
+
+#[tokio::test]
+async fn follows_the_redirects_without_the_credentials_of_another_origin() {
+    let (mut lookup, requests) = http_lookup("http://a:8080", |url| {
+        if url.starts_with("http://a:8080/lookup/") {
+            Some(response(
+                "307 Temporary Redirect",
+                "Location: /redirected\r\n",
+                "",
+            ))
+        } else if url == "http://a:8080/redirected" {
+            Some(response(
+                "307 Temporary Redirect",
+                &format!("Location: http://b:8080{}\r\n", LOOKUP_PATH),
+                "",
+            ))
+        } else {
+            lookup_data(url)
+        }
+    });
+    lookup
+        .add_header("Authorization", "Bearer x")
+        .expect("add_header")
+        .add_header("X-Trace", "1")
+        .expect("add_header");
+
+    let url = timeout(WAIT, lookup.lookup_topic(TOPIC))
+        .await
+        .expect("timeout")
+        .expect("lookup_topic");
+    assert_eq!(url.to_string(), "pulsar://b1:6650/");
+
+    let requests = requests.lock().expect("poisoned");
+    let urls: Vec<_> = requests.iter().map(|(url, _)| url.as_str()).collect();
+    assert_eq!(
+        urls,
+        vec![
+            format!("http://a:8080{}", LOOKUP_PATH),
+            "http://a:8080/redirected".to_owned(),
+            format!("http://b:8080{}", LOOKUP_PATH),
+        ]
+    );
+    assert!(requests[1].1.contains("\r\nAuthorization: Bearer x\r\n"));
+    assert!(requests[2].1.contains("\r\nHost: b:8080\r\n"));
+    assert!(!requests[2].1.contains("Authorization"));
+    assert!(requests[2].1.contains("\r\nX-Trace: 1\r\n"));
+}
+
+#[tokio::test]
+async fn fails_on_not_found() {
+    let (lookup, _) = http_lookup("http://a:8080", |_| Some(response("404 Not Found", "", "")));
+
+    match timeout(WAIT, lookup.lookup_topic(TOPIC))
+        .await
+        .expect("timeout")
+    {
+        Err(HttpLookupError::TopicNotFound) => {}
+        res => panic!("{:?}", res),
+    }
+}
+
+#[tokio::test]
+async fn rejects_the_headers_that_inject() {
+    let (mut lookup, _) = http_lookup("http://a:8080", lookup_data);
+
+    for (name, value) in &[
+        ("Authorization", "Bearer x\r\nX-Injected: 1"),
+        ("Authorization", "Bearer x\n"),
+        ("X-Injected: 1\r\nAuthorization", "Bearer x"),
+        ("Bad Name", "x"),
+        ("", "x"),
+    ] {
+        match lookup.add_header(name, value) {
+            Err(HttpLookupError::InvalidHeader(_)) => {}
+            res => panic!("{:?} {:?}", (name, value), res.map(|_| ())),
+        }
+    }
+}
+
+#[tokio::test]
+async fn times_out_a_stalled_server() {
+    let (mut lookup, _) = http_lookup("http://a:8080", |_| None);
+    lookup.set_timeout(Duration::from_millis(100));
+
+    match timeout(WAIT, lookup.lookup_topic(TOPIC))
+        .await
+        .expect("timeout")
+    {
+        Err(HttpLookupError::TimedOut) => {}
+        res => panic!("{:?}", res),
+    }
+}
+
+#[tokio::test]
+async fn fails_on_a_too_large_response() {
+    let (lookup, _) = http_lookup("http://a:8080", |_| {
+        Some(response("200 OK", "", &" ".repeat(2 * 1024 * 1024)))
+    });
+
+    match timeout(WAIT, lookup.lookup_topic(TOPIC))
+        .await
+        .expect("timeout")
+    {
+        Err(HttpLookupError::ResponseTooLarge) => {}
+        res => panic!("{:?}", res),
+    }
+}
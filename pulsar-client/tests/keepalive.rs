@@ -0,0 +1,109 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use pulsar_client::{
+    spec::{protos::protobuf::pulsar_api::BaseCommand_Type as Type, types::OutgoingMessage},
+    tokio_io::{
+        connection::{AsyncConnectionConfig, AsyncConnectionTaskError},
+        handler::HandleError,
+    },
+};
+use tokio::time::{sleep, timeout};
+
+use common::{command, connect, create_producer, ping, spawn_handler, WAIT};
+
+fn keepalive_config() -> AsyncConnectionConfig {
+    let mut config = AsyncConnectionConfig::default();
+    config
+        .set_keepalive_interval(Duration::from_millis(100))
+        .set_keepalive_timeout(Duration::from_millis(200));
+    config
+}
+
+#[tokio::test]
+async fn pings_when_idle_and_closes_when_the_broker_stays_silent() {
+    let (_session, handler, mut broker) = connect(64 * 1024, keepalive_config()).await;
+    let handler_task = spawn_handler(handler);
+
+    // Answered, the connection stays open.
+    for _ in 0..3 {
+        let c = timeout(WAIT, broker.read())
+            .await
+            .expect("timeout")
+            .expect("closed");
+        assert_eq!(c.get_field_type(), Type::PING);
+
+        let mut pong = command(Type::PONG);
+        pong.mut_pong();
+        broker.write(pong).await;
+    }
+
+    let silent_at = Instant::now();
+    let err = timeout(WAIT, handler_task)
+        .await
+        .expect("timeout")
+        .expect("join");
+    match err {
+        HandleError::ConnectionRequireClose(AsyncConnectionTaskError::KeepaliveTimeout) => {}
+        err => panic!("{:?}", err),
+    }
+    assert!(silent_at.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn does_not_ping_while_the_broker_is_talking() {
+    let (_session, handler, mut broker) = connect(64 * 1024, keepalive_config()).await;
+    let _handler_task = spawn_handler(handler);
+
+    // Any command counts, the client answers the pings of the broker only.
+    for _ in 0..10 {
+        broker.write(ping()).await;
+
+        let c = timeout(WAIT, broker.read())
+            .await
+            .expect("timeout")
+            .expect("closed");
+        assert_eq!(c.get_field_type(), Type::PONG);
+
+        sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn waits_for_the_ping_queued_behind_a_large_write() {
+    let (session, handler, mut broker) = connect(16 * 1024, keepalive_config()).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+    let send_future = producer
+        .send_async(OutgoingMessage::new(&vec![0; 256 * 1024][..]))
+        .await
+        .expect("send_async");
+
+    // Longer than the keepalive timeout, the PING is queued meanwhile.
+    let started_at = Instant::now();
+    let send = broker
+        .read_slowly(Duration::from_millis(50))
+        .await
+        .expect("closed");
+    assert_eq!(send.get_field_type(), Type::SEND);
+    assert!(started_at.elapsed() >= Duration::from_millis(300));
+
+    let c = timeout(WAIT, broker.read())
+        .await
+        .expect("timeout")
+        .expect("closed");
+    assert_eq!(c.get_field_type(), Type::PING);
+    let mut pong = command(Type::PONG);
+    pong.mut_pong();
+    broker.write(pong).await;
+
+    broker.write_send_receipt(&send, 1).await;
+    timeout(WAIT, send_future)
+        .await
+        .expect("timeout")
+        .expect("send");
+}
@@ -0,0 +1,219 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use futures_util::stream::StreamExt as _;
+use pulsar_client::{
+    consumer::{AsyncConsumerConfig, AsyncMultiTopicsConsumer},
+    spec::{
+        broker_service_url::ServiceUrl, protos::protobuf::pulsar_api::BaseCommand_Type as Type,
+        types::SubscribeType, SubscribeCommand,
+    },
+};
+use tokio::time::timeout;
+
+use common::{
+    command, connect, create_consumer, expect_served, ping, serve_pool, spawn_handler, success,
+    BrokerTopics, SERVICE_URL, WAIT,
+};
+
+#[tokio::test]
+async fn subscribes_to_every_partition() {
+    let topics = Arc::new(Mutex::new(BrokerTopics::default()));
+    topics
+        .lock()
+        .expect("poisoned")
+        .partitions
+        .insert("persistent://public/default/a".to_owned(), 2);
+    let (pool, commands) = serve_pool(topics);
+    let service_url: ServiceUrl = SERVICE_URL.parse().expect("parse");
+
+    let consumer = timeout(
+        WAIT,
+        pool.create_multi_topics_consumer(
+            &service_url,
+            &[
+                "persistent://public/default/a",
+                "persistent://public/default/b",
+            ],
+            SubscribeCommand::new("replaced", "s", SubscribeType::Shared),
+            AsyncConsumerConfig::default(),
+        ),
+    )
+    .await
+    .expect("timeout")
+    .expect("create_multi_topics_consumer");
+
+    assert_eq!(
+        consumer.get_topics(),
+        vec![
+            "persistent://public/default/a-partition-0",
+            "persistent://public/default/a-partition-1",
+            "persistent://public/default/b",
+        ]
+    );
+
+    let mut subscribed = vec![];
+    for _ in 0..3 {
+        let subscribe = expect_served(&commands, Type::SUBSCRIBE).await;
+        assert_eq!(subscribe.get_subscribe().get_subscription(), "s");
+        subscribed.push(subscribe.get_subscribe().get_topic().to_owned());
+    }
+    subscribed.sort();
+    assert_eq!(subscribed, consumer.get_topics());
+}
+
+#[tokio::test]
+async fn polls_the_consumers_in_turn() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let mut consumers = vec![];
+    for topic in &["a", "b"] {
+        let mut consumer = create_consumer(
+            &session,
+            &mut broker,
+            SubscribeCommand::new(topic, "s", SubscribeType::Exclusive),
+        )
+        .await;
+        let consumer_id = u64::from(consumer.get_consumer_id());
+
+        broker
+            .write_batch_message(consumer_id, 1, &[b"0", b"1", b"2"])
+            .await;
+        // The rest of the batch is kept by the consumer.
+        let message = timeout(WAIT, consumer.next())
+            .await
+            .expect("timeout")
+            .expect("end")
+            .expect("message");
+        assert_eq!(message.get_payload(), b"0");
+
+        consumers.push(consumer);
+    }
+
+    let mut consumer = AsyncMultiTopicsConsumer::new(consumers);
+    let mut received = vec![];
+    for _ in 0..4 {
+        let message = timeout(WAIT, consumer.next())
+            .await
+            .expect("timeout")
+            .expect("end")
+            .expect("message");
+        received.push((message.get_topic().to_owned(), message.into_payload()));
+    }
+    assert_eq!(
+        received,
+        vec![
+            ("a".to_owned(), b"1".to_vec()),
+            ("b".to_owned(), b"1".to_vec()),
+            ("a".to_owned(), b"2".to_vec()),
+            ("b".to_owned(), b"2".to_vec()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn acks_the_batch_once_every_message_is_acked() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let consumer = create_consumer(
+        &session,
+        &mut broker,
+        SubscribeCommand::new("a", "s", SubscribeType::Exclusive),
+    )
+    .await;
+    let consumer_id = u64::from(consumer.get_consumer_id());
+    let mut consumer = AsyncMultiTopicsConsumer::new(vec![consumer]);
+
+    broker
+        .write_batch_message(consumer_id, 1, &[b"0", b"1", b"2"])
+        .await;
+    let mut messages = vec![];
+    for _ in 0..3 {
+        messages.push(
+            timeout(WAIT, consumer.next())
+                .await
+                .expect("timeout")
+                .expect("end")
+                .expect("message"),
+        );
+    }
+
+    // Not sent, the broker would ack the whole entry.
+    for message in &[&messages[0], &messages[2]] {
+        timeout(WAIT, consumer.ack(message))
+            .await
+            .expect("timeout")
+            .expect("ack");
+    }
+
+    let (res, _) = tokio::join!(timeout(WAIT, consumer.ack(&messages[1])), async {
+        let ack = broker.expect(Type::ACK).await;
+        let message_ids = ack.get_ack().get_message_id();
+        assert_eq!(message_ids.len(), 1);
+        assert_eq!(message_ids[0].get_entryId(), 1);
+        assert!(!message_ids[0].has_batch_index());
+
+        let mut ack_response = command(Type::ACK_RESPONSE);
+        ack_response.mut_ackResponse().set_consumer_id(consumer_id);
+        ack_response
+            .mut_ackResponse()
+            .set_request_id(ack.get_ack().get_request_id());
+        broker.write(ack_response).await;
+    });
+    res.expect("timeout").expect("ack");
+}
+
+#[tokio::test]
+async fn closes_the_removed_consumer() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let mut consumers = vec![];
+    for topic in &["a", "b"] {
+        consumers.push(
+            create_consumer(
+                &session,
+                &mut broker,
+                SubscribeCommand::new(topic, "s", SubscribeType::Exclusive),
+            )
+            .await,
+        );
+    }
+    let mut consumer = AsyncMultiTopicsConsumer::new(consumers);
+
+    let removed = consumer.remove_consumer("a").expect("remove_consumer");
+    let consumer_id = u64::from(removed.get_consumer_id());
+    drop(removed);
+
+    let close_consumer = broker.expect(Type::CLOSE_CONSUMER).await;
+    assert_eq!(
+        close_consumer.get_close_consumer().get_consumer_id(),
+        consumer_id
+    );
+    broker
+        .write(success(
+            close_consumer.get_close_consumer().get_request_id(),
+        ))
+        .await;
+
+    // Pushed before the close, it is dropped.
+    broker.write_message(consumer_id, 1, b"foo").await;
+    broker.write(ping()).await;
+    loop {
+        let c = timeout(WAIT, broker.read())
+            .await
+            .expect("timeout")
+            .expect("closed");
+        if c.get_field_type() == Type::PONG {
+            break;
+        }
+    }
+
+    assert_eq!(consumer.get_topics(), vec!["b"]);
+    assert!(!consumer.is_closed());
+}
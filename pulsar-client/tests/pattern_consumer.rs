@@ -0,0 +1,184 @@
+#![cfg(all(feature = "tokio_io", feature = "pattern_consumer"))]
+
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::stream::StreamExt as _;
+use pulsar_client::{
+    spec::{
+        async_channel::Receiver,
+        broker_service_url::ServiceUrl,
+        protos::protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type},
+        types::SubscribeType,
+        SubscribeCommand,
+    },
+    tokio_io::pattern_consumer::{AsyncPatternConsumer, AsyncPatternConsumerConfig},
+};
+use tokio::{io::DuplexStream, time::timeout};
+
+use common::{expect_served, serve_pool, BrokerTopics, SERVICE_URL, WAIT};
+
+fn topic(local_name: &str) -> String {
+    format!("persistent://public/default/{}", local_name)
+}
+
+fn broker_topics(local_names: &[&str]) -> Arc<Mutex<BrokerTopics>> {
+    Arc::new(Mutex::new(BrokerTopics {
+        topics_of_namespace: local_names.iter().map(|name| topic(name)).collect(),
+        ..BrokerTopics::default()
+    }))
+}
+
+async fn pattern_consumer(
+    topics: Arc<Mutex<BrokerTopics>>,
+    discovery_interval: Duration,
+) -> (AsyncPatternConsumer<DuplexStream>, Receiver<BaseCommand>) {
+    let (pool, commands) = serve_pool(topics);
+    let service_url: ServiceUrl = SERVICE_URL.parse().expect("parse");
+
+    let mut config = AsyncPatternConsumerConfig::default();
+    config.set_discovery_interval(discovery_interval);
+
+    let consumer = timeout(
+        WAIT,
+        AsyncPatternConsumer::new(
+            Arc::new(pool),
+            Arc::new(service_url),
+            "public/default",
+            "events-.*",
+            SubscribeCommand::new("replaced", "s", SubscribeType::Shared),
+            config,
+        ),
+    )
+    .await
+    .expect("timeout")
+    .expect("new");
+
+    (consumer, commands)
+}
+
+// Polls the Stream, which discovers in the background, until the topics are the expected.
+async fn wait_for_topics(consumer: &mut AsyncPatternConsumer<DuplexStream>, expected: &[String]) {
+    timeout(WAIT, async {
+        loop {
+            let _ = timeout(Duration::from_millis(20), consumer.next()).await;
+
+            let mut topics = consumer.get_topics();
+            topics.sort_unstable();
+            if topics == expected {
+                break;
+            }
+        }
+    })
+    .await
+    .expect("timeout");
+}
+
+#[tokio::test]
+async fn subscribes_to_the_matching_topics() {
+    let topics = broker_topics(&[
+        "events-a",
+        "events-b-partition-0",
+        "events-b-partition-1",
+        "my-events-c",
+        "events",
+        "logs",
+    ]);
+    let (consumer, commands) = pattern_consumer(topics, Duration::from_secs(60)).await;
+
+    // The partitions match by the name of the partitioned topic, the whole name must match.
+    let mut topics = consumer.get_topics();
+    topics.sort_unstable();
+    assert_eq!(
+        topics,
+        vec![
+            topic("events-a"),
+            topic("events-b-partition-0"),
+            topic("events-b-partition-1"),
+        ]
+    );
+
+    let get_topics_of_namespace = expect_served(&commands, Type::GET_TOPICS_OF_NAMESPACE).await;
+    assert_eq!(
+        get_topics_of_namespace
+            .get_getTopicsOfNamespace()
+            .get_namespace(),
+        "public/default"
+    );
+}
+
+#[tokio::test]
+async fn subscribes_the_new_topics_and_closes_the_deleted() {
+    let topics = broker_topics(&["events-a", "events-b"]);
+    let (mut consumer, commands) =
+        pattern_consumer(topics.to_owned(), Duration::from_millis(50)).await;
+
+    topics.lock().expect("poisoned").topics_of_namespace =
+        vec![topic("events-b"), topic("events-c")];
+    wait_for_topics(&mut consumer, &[topic("events-b"), topic("events-c")]).await;
+
+    let mut subscribed = vec![];
+    for _ in 0..3 {
+        let subscribe = expect_served(&commands, Type::SUBSCRIBE).await;
+        subscribed.push(subscribe.get_subscribe().get_topic().to_owned());
+    }
+    subscribed.sort_unstable();
+    assert_eq!(
+        subscribed,
+        vec![topic("events-a"), topic("events-b"), topic("events-c")]
+    );
+
+    // The consumer of events-a.
+    expect_served(&commands, Type::CLOSE_CONSUMER).await;
+}
+
+#[tokio::test]
+async fn knows_the_topics_rediscovered_while_sleeping() {
+    let topics = broker_topics(&["events-a"]);
+    let (mut consumer, commands) =
+        pattern_consumer(topics.to_owned(), Duration::from_millis(200)).await;
+    expect_served(&commands, Type::SUBSCRIBE).await;
+
+    // Starts sleeping.
+    assert!(timeout(Duration::from_millis(20), consumer.next())
+        .await
+        .is_err());
+
+    topics
+        .lock()
+        .expect("poisoned")
+        .topics_of_namespace
+        .push(topic("events-b"));
+    timeout(WAIT, consumer.rediscover())
+        .await
+        .expect("timeout")
+        .expect("rediscover");
+    let subscribe = expect_served(&commands, Type::SUBSCRIBE).await;
+    assert_eq!(subscribe.get_subscribe().get_topic(), topic("events-b"));
+
+    // The discovery of the Stream, after the sleep.
+    timeout(WAIT, async {
+        let mut n = 0;
+        while n < 3 {
+            let _ = timeout(Duration::from_millis(20), consumer.next()).await;
+            while let Ok(c) = commands.try_recv() {
+                match c.get_field_type() {
+                    Type::GET_TOPICS_OF_NAMESPACE => n += 1,
+                    // Not subscribed again, and not closed as a duplicate.
+                    Type::SUBSCRIBE | Type::CLOSE_CONSUMER => panic!("{:?}", c),
+                    _ => {}
+                }
+            }
+        }
+    })
+    .await
+    .expect("timeout");
+
+    let mut topics = consumer.get_topics();
+    topics.sort_unstable();
+    assert_eq!(topics, vec![topic("events-a"), topic("events-b")]);
+}
@@ -0,0 +1,185 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use std::time::Duration;
+
+use pulsar_client::{
+    pending_limit::PendingLimit,
+    producer::{AsyncProducerConfig, ProducerQueueFullPolicy, RawSendError},
+    spec::{protos::protobuf::pulsar_api::BaseCommand_Type as Type, types::OutgoingMessage},
+};
+use tokio::time::timeout;
+
+use common::{connect, create_producer, spawn_handler, WAIT};
+
+fn producer_config(policy: ProducerQueueFullPolicy) -> AsyncProducerConfig {
+    let mut config = AsyncProducerConfig::default();
+    config
+        .set_max_pending_messages(2)
+        .set_queue_full_policy(policy);
+    config
+}
+
+#[tokio::test]
+async fn block_waits_for_a_receipt() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(
+        &session,
+        &mut broker,
+        producer_config(ProducerQueueFullPolicy::Block),
+    )
+    .await;
+
+    let first = producer
+        .send_async(OutgoingMessage::new(b"1"))
+        .await
+        .expect("send_async");
+    let _second = producer
+        .send_async(OutgoingMessage::new(b"2"))
+        .await
+        .expect("send_async");
+    assert_eq!(producer.get_pending_messages(), 2);
+
+    let third = producer.send_async(OutgoingMessage::new(b"3"));
+    tokio::pin!(third);
+    assert!(timeout(Duration::from_millis(100), &mut third)
+        .await
+        .is_err());
+
+    let send = broker.expect(Type::SEND).await;
+    broker.write_send_receipt(&send, 1).await;
+    timeout(WAIT, first).await.expect("timeout").expect("send");
+
+    timeout(WAIT, third)
+        .await
+        .expect("timeout")
+        .expect("send_async");
+    assert_eq!(producer.get_pending_messages(), 2);
+}
+
+#[tokio::test]
+async fn fail_returns_queue_is_full() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(
+        &session,
+        &mut broker,
+        producer_config(ProducerQueueFullPolicy::Fail),
+    )
+    .await;
+
+    let mut send_futures = vec![];
+    for payload in &[b"1", b"2"] {
+        send_futures.push(
+            producer
+                .send_async(OutgoingMessage::new(payload))
+                .await
+                .expect("send_async"),
+        );
+    }
+    match producer.send_async(OutgoingMessage::new(b"3")).await {
+        Err(RawSendError::ProducerQueueIsFull) => {}
+        res => panic!("{:?}", res),
+    }
+
+    for entry_id in 0..2 {
+        let send = broker.expect(Type::SEND).await;
+        broker.write_send_receipt(&send, entry_id).await;
+    }
+    for send_future in send_futures {
+        timeout(WAIT, send_future)
+            .await
+            .expect("timeout")
+            .expect("send");
+    }
+    assert_eq!(producer.get_pending_messages(), 0);
+
+    producer
+        .send_async(OutgoingMessage::new(b"3"))
+        .await
+        .expect("send_async");
+}
+
+#[tokio::test]
+async fn dropped_send_futures_keep_their_permits() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(
+        &session,
+        &mut broker,
+        producer_config(ProducerQueueFullPolicy::Fail),
+    )
+    .await;
+
+    // Fire and forget.
+    for payload in &[b"1", b"2"] {
+        drop(
+            producer
+                .send_async(OutgoingMessage::new(payload))
+                .await
+                .expect("send_async"),
+        );
+    }
+    assert_eq!(producer.get_pending_messages(), 2);
+    match producer.send_async(OutgoingMessage::new(b"3")).await {
+        Err(RawSendError::ProducerQueueIsFull) => {}
+        res => panic!("{:?}", res),
+    }
+
+    // Released by the handler once responded.
+    for entry_id in 0..2 {
+        let send = broker.expect(Type::SEND).await;
+        broker.write_send_receipt(&send, entry_id).await;
+    }
+    timeout(WAIT, async {
+        while producer.get_pending_messages() > 0 {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("timeout");
+}
+
+#[tokio::test]
+async fn memory_limit_is_shared_by_the_producers() {
+    let (mut session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let memory_limit = PendingLimit::new(8);
+    session.set_memory_limit(memory_limit.to_owned());
+
+    let config = producer_config(ProducerQueueFullPolicy::Fail);
+    let first_producer = create_producer(&session, &mut broker, config.to_owned()).await;
+    let second_producer = create_producer(&session, &mut broker, config).await;
+
+    let send_future = first_producer
+        .send_async(OutgoingMessage::new(b"12345"))
+        .await
+        .expect("send_async");
+    assert_eq!(memory_limit.get_used(), 5);
+    match second_producer
+        .send_async(OutgoingMessage::new(b"12345"))
+        .await
+    {
+        Err(RawSendError::ProducerQueueIsFull) => {}
+        res => panic!("{:?}", res),
+    }
+
+    let send = broker.expect(Type::SEND).await;
+    broker.write_send_receipt(&send, 1).await;
+    timeout(WAIT, send_future)
+        .await
+        .expect("timeout")
+        .expect("send");
+    assert_eq!(memory_limit.get_used(), 0);
+
+    second_producer
+        .send_async(OutgoingMessage::new(b"12345"))
+        .await
+        .expect("send_async");
+}
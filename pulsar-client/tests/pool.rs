@@ -0,0 +1,204 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use pulsar_client::{
+    spec::{
+        async_channel::{unbounded, Receiver},
+        broker_service_url::Url,
+        protos::protobuf::pulsar_api::{BaseCommand, BaseCommand_Type as Type},
+        ConnectCommand,
+    },
+    tokio_io::pool::ConnectionPool,
+};
+use tokio::{
+    io::{duplex, DuplexStream},
+    task::JoinHandle,
+    time::{sleep, timeout},
+};
+
+use common::{expect_served, serve, BrokerTopics, FakeBroker, WAIT};
+
+// The physical address of each connection, and the task of its broker.
+type Connections = Arc<Mutex<Vec<(Url, JoinHandle<()>)>>>;
+
+// Every connection of the pool is served by a new broker, the test can abort it.
+fn pool(
+    connections_per_broker: usize,
+) -> (
+    ConnectionPool<DuplexStream>,
+    Connections,
+    Receiver<BaseCommand>,
+) {
+    let topics = Arc::new(Mutex::new(BrokerTopics::default()));
+    let connections: Connections = Arc::new(Mutex::new(vec![]));
+    let (sender, receiver) = unbounded();
+
+    let connections_cloned = connections.to_owned();
+    let mut pool = ConnectionPool::new(
+        move |url| {
+            let (stream, broker_stream) = duplex(64 * 1024);
+            let broker_task = tokio::spawn(serve(
+                FakeBroker::new(broker_stream),
+                topics.to_owned(),
+                sender.to_owned(),
+            ));
+            connections_cloned
+                .lock()
+                .expect("poisoned")
+                .push((url, broker_task));
+            async move { Ok(stream) }
+        },
+        |f| {
+            tokio::spawn(f);
+        },
+        ConnectCommand::new("test"),
+    );
+    pool.set_connections_per_broker(connections_per_broker);
+
+    (pool, connections, receiver)
+}
+
+fn url(s: &str) -> Url {
+    s.parse().expect("parse")
+}
+
+fn connected_urls(connections: &Connections) -> Vec<Url> {
+    connections
+        .lock()
+        .expect("poisoned")
+        .iter()
+        .map(|(url, _)| url.to_owned())
+        .collect()
+}
+
+#[tokio::test]
+async fn shares_the_session_of_a_broker() {
+    let (pool, connections, _commands) = pool(1);
+    let a = url("pulsar://a:6650");
+    let b = url("pulsar://b:6650");
+
+    let session_a = timeout(WAIT, pool.get_by_url(&a))
+        .await
+        .expect("timeout")
+        .expect("get_by_url");
+    let session = timeout(WAIT, pool.get_by_url(&a))
+        .await
+        .expect("timeout")
+        .expect("get_by_url");
+    assert!(Arc::ptr_eq(&session_a, &session));
+
+    let session_b = timeout(WAIT, pool.get_by_url(&b))
+        .await
+        .expect("timeout")
+        .expect("get_by_url");
+    assert!(!Arc::ptr_eq(&session_a, &session_b));
+
+    assert_eq!(connected_urls(&connections), vec![a, b]);
+}
+
+#[tokio::test]
+async fn keys_by_the_logical_and_the_physical_address() {
+    let (pool, connections, commands) = pool(1);
+    let proxy = url("pulsar://proxy:6650");
+    let broker_1 = url("pulsar://broker-1:6650");
+    let broker_2 = url("pulsar://broker-2:6650");
+
+    let session_1 = timeout(WAIT, pool.get(&broker_1, &proxy))
+        .await
+        .expect("timeout")
+        .expect("get");
+    let connect = expect_served(&commands, Type::CONNECT).await;
+    assert_eq!(
+        connect.get_connect().get_proxy_to_broker_url(),
+        "broker-1:6650"
+    );
+
+    let session_2 = timeout(WAIT, pool.get(&broker_2, &proxy))
+        .await
+        .expect("timeout")
+        .expect("get");
+    let connect = expect_served(&commands, Type::CONNECT).await;
+    assert_eq!(
+        connect.get_connect().get_proxy_to_broker_url(),
+        "broker-2:6650"
+    );
+
+    // Not proxied.
+    let session_proxy = timeout(WAIT, pool.get(&proxy, &proxy))
+        .await
+        .expect("timeout")
+        .expect("get");
+    let connect = expect_served(&commands, Type::CONNECT).await;
+    assert!(!connect.get_connect().has_proxy_to_broker_url());
+
+    assert!(!Arc::ptr_eq(&session_1, &session_2));
+    assert!(!Arc::ptr_eq(&session_1, &session_proxy));
+    assert!(!Arc::ptr_eq(&session_2, &session_proxy));
+    assert_eq!(
+        connected_urls(&connections),
+        vec![proxy.to_owned(), proxy.to_owned(), proxy.to_owned()]
+    );
+
+    let session = timeout(WAIT, pool.get(&broker_1, &proxy))
+        .await
+        .expect("timeout")
+        .expect("get");
+    assert!(Arc::ptr_eq(&session_1, &session));
+    assert_eq!(connections.lock().expect("poisoned").len(), 3);
+}
+
+#[tokio::test]
+async fn hands_out_the_sessions_of_a_broker_in_turn() {
+    let (pool, connections, _commands) = pool(2);
+    let a = url("pulsar://a:6650");
+
+    let mut sessions = vec![];
+    for _ in 0..4 {
+        sessions.push(
+            timeout(WAIT, pool.get_by_url(&a))
+                .await
+                .expect("timeout")
+                .expect("get_by_url"),
+        );
+    }
+
+    assert!(!Arc::ptr_eq(&sessions[0], &sessions[1]));
+    assert!(Arc::ptr_eq(&sessions[0], &sessions[2]));
+    assert!(Arc::ptr_eq(&sessions[1], &sessions[3]));
+    assert_eq!(connections.lock().expect("poisoned").len(), 2);
+}
+
+#[tokio::test]
+async fn replaces_a_closed_session() {
+    let (pool, connections, _commands) = pool(1);
+    let a = url("pulsar://a:6650");
+
+    let session = timeout(WAIT, pool.get_by_url(&a))
+        .await
+        .expect("timeout")
+        .expect("get_by_url");
+
+    // The broker is gone, the handler ends.
+    connections.lock().expect("poisoned")[0].1.abort();
+    timeout(WAIT, async {
+        while !session.is_closed() {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("timeout");
+
+    let replaced = timeout(WAIT, pool.get_by_url(&a))
+        .await
+        .expect("timeout")
+        .expect("get_by_url");
+    assert!(!Arc::ptr_eq(&session, &replaced));
+    assert!(!replaced.is_closed());
+    assert_eq!(connected_urls(&connections), vec![a.to_owned(), a]);
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use std::time::Duration;
+
+use futures_util::{
+    future::join,
+    sink::SinkExt as _,
+    stream::{self, StreamExt as _},
+};
+use pulsar_client::{
+    producer::{AsyncProducerConfig, ProducerQueueFullPolicy, RawSendError},
+    spec::{
+        client_responds::ProducerSendRespondError,
+        protos::protobuf::pulsar_api::BaseCommand_Type as Type, types::OutgoingMessage,
+    },
+};
+use tokio::time::timeout;
+
+use common::{connect, create_producer, spawn_handler, success, WAIT};
+
+fn producer_config(max_pending_messages: usize) -> AsyncProducerConfig {
+    let mut config = AsyncProducerConfig::default();
+    config
+        .set_max_pending_messages(max_pending_messages)
+        .set_queue_full_policy(ProducerQueueFullPolicy::Block);
+    config
+}
+
+#[tokio::test]
+async fn send_returns_the_receipt() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+
+    let (res, _) = join(producer.send(OutgoingMessage::new(b"foo")), async {
+        let send = broker.expect(Type::SEND).await;
+        broker.write_send_receipt(&send, 7).await;
+    })
+    .await;
+    let receipt = res.expect("send");
+    assert_eq!(u64::from(receipt.get_sequence_id()), 1);
+    assert_eq!(
+        receipt.get_message_id().expect("message_id").get_entry_id(),
+        7
+    );
+    assert_eq!(producer.get_pending_messages(), 0);
+}
+
+#[tokio::test]
+async fn blocked_send_asyncs_keep_the_order_of_the_calls() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, producer_config(1)).await;
+
+    let first = producer
+        .send_async(OutgoingMessage::new(b"1"))
+        .await
+        .expect("send_async");
+
+    // Both wait for the permit of the first.
+    let sends = async {
+        let (second, third) = join(
+            producer.send_async(OutgoingMessage::new(b"2")),
+            producer.send_async(OutgoingMessage::new(b"3")),
+        )
+        .await;
+        let second = second.expect("send_async").await.expect("send");
+        let third = third.expect("send_async").await.expect("send");
+        (second, third)
+    };
+    let broker_side = async {
+        for entry_id in 0..3 {
+            let send = broker.expect(Type::SEND).await;
+            assert_eq!(send.get_send().get_sequence_id(), entry_id + 1);
+            broker.write_send_receipt(&send, entry_id).await;
+        }
+    };
+    let ((second, third), _) = timeout(WAIT, join(sends, broker_side))
+        .await
+        .expect("timeout");
+
+    first.await.expect("send");
+    assert_eq!(u64::from(second.get_sequence_id()), 2);
+    assert_eq!(u64::from(third.get_sequence_id()), 3);
+}
+
+#[tokio::test]
+async fn sink_is_back_pressured_by_the_pending_limits() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let mut producer = create_producer(&session, &mut broker, producer_config(2)).await;
+
+    let sends = async {
+        let mut messages =
+            stream::iter(0..5).map(|i| Ok(OutgoingMessage::new(format!("{}", i).into_bytes())));
+        producer.send_all(&mut messages).await.expect("send_all");
+        producer.get_pending_messages()
+    };
+    let broker_side = async {
+        let mut pending = vec![];
+        for entry_id in 0..5 {
+            if pending.len() == 2 {
+                // Nothing more until a receipt.
+                assert!(timeout(Duration::from_millis(100), broker.read())
+                    .await
+                    .is_err());
+                let send = pending.remove(0);
+                broker.write_send_receipt(&send, entry_id - 2).await;
+            }
+
+            let send = broker.expect(Type::SEND).await;
+            assert_eq!(send.get_send().get_sequence_id(), entry_id + 1);
+            pending.push(send);
+        }
+        for (entry_id, send) in (3..5).zip(pending) {
+            broker.write_send_receipt(&send, entry_id).await;
+        }
+    };
+    let (pending_messages, _) = timeout(WAIT, join(sends, broker_side))
+        .await
+        .expect("timeout");
+
+    // Flushed, all the receipts are checked.
+    assert_eq!(pending_messages, 0);
+}
+
+#[tokio::test]
+async fn closes_the_dropped_producer() {
+    let (session, handler, mut broker) = connect(64 * 1024, None).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+    let producer_id = u64::from(producer.get_producer_id());
+    let send_future = producer
+        .send_async(OutgoingMessage::new(b"foo"))
+        .await
+        .expect("send_async");
+    broker.expect(Type::SEND).await;
+    drop(producer);
+
+    let close_producer = broker.expect(Type::CLOSE_PRODUCER).await;
+    assert_eq!(
+        close_producer.get_close_producer().get_producer_id(),
+        producer_id
+    );
+    broker
+        .write(success(
+            close_producer.get_close_producer().get_request_id(),
+        ))
+        .await;
+
+    // Not responded before the close.
+    match timeout(WAIT, send_future).await.expect("timeout") {
+        Err(RawSendError::RespondError(ProducerSendRespondError::ConnectionLost)) => {}
+        res => panic!("{:?}", res),
+    }
+
+    // The name can be used again.
+    create_producer(&session, &mut broker, None).await;
+}
@@ -0,0 +1,281 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use std::{
+    io::{Error as IoError, ErrorKind as IoErrorKind},
+    time::{Duration, Instant},
+};
+
+use pulsar_client::{
+    producer::RawSendError,
+    reconnect::ReconnectConfig,
+    session::RawLookupTopicError,
+    spec::{
+        async_channel::{unbounded, Receiver},
+        client_responds::{ProducerSendRespondError, SessionLookupTopicRespondError},
+        protos::protobuf::pulsar_api::BaseCommand_Type as Type,
+        types::{OutgoingMessage, SubscribeType},
+        LookupTopicCommand, SubscribeCommand,
+    },
+    tokio_io::{
+        connection::{AsyncConnection, AsyncConnectionConfig},
+        handler::{AsyncHandler, HandleError},
+    },
+};
+use tokio::{
+    io::{duplex, DuplexStream},
+    time::timeout,
+};
+
+use common::{connect, create_consumer, create_producer, spawn_handler, success, FakeBroker, WAIT};
+
+fn reconnect_config() -> ReconnectConfig {
+    let mut config = ReconnectConfig::default();
+    config
+        .set_initial_delay(Duration::from_millis(10))
+        .set_max_delay(Duration::from_millis(50));
+    config
+}
+
+// Every reconnection is accepted by a new broker.
+fn reconnect_to_brokers(
+    handler: &mut AsyncHandler<DuplexStream>,
+    config: AsyncConnectionConfig,
+) -> Receiver<FakeBroker> {
+    let (sender, receiver) = unbounded();
+
+    handler.set_reconnect(
+        move || {
+            let (stream, broker_stream) = duplex(64 * 1024);
+            let sender = sender.to_owned();
+            let config = config.to_owned();
+            async move {
+                sender
+                    .send(FakeBroker::new(broker_stream))
+                    .await
+                    .map_err(|_| IoError::new(IoErrorKind::ConnectionRefused, "no broker"))?;
+                Ok(AsyncConnection::new(stream, config))
+            }
+        },
+        reconnect_config(),
+    );
+
+    receiver
+}
+
+async fn next_broker(brokers: &Receiver<FakeBroker>) -> FakeBroker {
+    let mut broker = timeout(WAIT, brokers.recv())
+        .await
+        .expect("timeout")
+        .expect("closed");
+    broker.accept_connect().await;
+    broker
+}
+
+#[tokio::test]
+async fn resends_the_pending_sends_after_reconnecting() {
+    let (session, mut handler, mut broker) = connect(64 * 1024, None).await;
+    let brokers = reconnect_to_brokers(&mut handler, AsyncConnectionConfig::default());
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+    let send_future = producer
+        .send_async(OutgoingMessage::new(b"foo"))
+        .await
+        .expect("send_async");
+    let send = broker.expect(Type::SEND).await;
+    drop(broker);
+
+    let mut broker = next_broker(&brokers).await;
+    let producer_command = broker.accept_producer().await;
+    // With the name the broker assigned, and a new epoch.
+    assert_eq!(producer_command.get_producer().get_producer_name(), "p-1");
+    assert_eq!(producer_command.get_producer().get_epoch(), 1);
+
+    let resent = broker.expect(Type::SEND).await;
+    assert_eq!(
+        resent.get_send().get_sequence_id(),
+        send.get_send().get_sequence_id()
+    );
+    broker.write_send_receipt(&resent, 1).await;
+
+    timeout(WAIT, send_future)
+        .await
+        .expect("timeout")
+        .expect("send");
+    assert!(!producer.is_closed());
+}
+
+#[tokio::test]
+async fn reconnects_on_an_out_of_order_receipt() {
+    let (session, mut handler, mut broker) = connect(64 * 1024, None).await;
+    let brokers = reconnect_to_brokers(&mut handler, AsyncConnectionConfig::default());
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+    let mut send_futures = vec![];
+    let mut sends = vec![];
+    for payload in &[b"1", b"2"] {
+        send_futures.push(
+            producer
+                .send_async(OutgoingMessage::new(*payload))
+                .await
+                .expect("send_async"),
+        );
+        sends.push(broker.expect(Type::SEND).await);
+    }
+
+    broker.write_send_receipt(&sends[1], 2).await;
+    // The connection is closed by the client.
+    timeout(WAIT, async { while broker.read().await.is_some() {} })
+        .await
+        .expect("timeout");
+
+    let mut broker = next_broker(&brokers).await;
+    broker.accept_producer().await;
+    for send in &sends {
+        let resent = broker.expect(Type::SEND).await;
+        assert_eq!(
+            resent.get_send().get_sequence_id(),
+            send.get_send().get_sequence_id()
+        );
+        broker.write_send_receipt(&resent, 1).await;
+    }
+
+    for send_future in send_futures {
+        timeout(WAIT, send_future)
+            .await
+            .expect("timeout")
+            .expect("send");
+    }
+    assert!(!producer.is_closed());
+}
+
+#[tokio::test]
+async fn times_out_while_reconnecting() {
+    let mut config = AsyncConnectionConfig::default();
+    config.set_send_timeout(Duration::from_millis(200));
+    let (session, mut handler, mut broker) = connect(64 * 1024, config).await;
+    // The broker stays down.
+    handler.set_reconnect(
+        || async { Err(IoError::new(IoErrorKind::ConnectionRefused, "down")) },
+        reconnect_config(),
+    );
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+    drop(broker);
+    while !matches!(
+        session.raw_lookup_topic(LookupTopicCommand::new("t")).await,
+        Err(RawLookupTopicError::RespondError(
+            SessionLookupTopicRespondError::ConnectionLost
+        ))
+    ) {}
+
+    let started_at = Instant::now();
+    let send_future = producer
+        .send_async(OutgoingMessage::new(b"foo"))
+        .await
+        .expect("send_async");
+    match timeout(WAIT, send_future).await.expect("timeout") {
+        Err(RawSendError::RespondError(ProducerSendRespondError::Timeout)) => {}
+        res => panic!("{:?}", res),
+    }
+    assert!(started_at.elapsed() >= Duration::from_millis(200));
+    assert!(!producer.is_closed());
+}
+
+#[tokio::test]
+async fn closes_once_the_retries_are_exhausted() {
+    let (session, mut handler, mut broker) = connect(64 * 1024, None).await;
+    let mut config = reconnect_config();
+    config.set_max_retries(2);
+    handler.set_reconnect(
+        || async { Err(IoError::new(IoErrorKind::ConnectionRefused, "down")) },
+        config,
+    );
+    let handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+    let send_future = producer
+        .send_async(OutgoingMessage::new(b"foo"))
+        .await
+        .expect("send_async");
+    broker.expect(Type::SEND).await;
+    drop(broker);
+
+    match timeout(WAIT, handler_task)
+        .await
+        .expect("timeout")
+        .expect("join")
+    {
+        HandleError::ConnectionRequireClose(_) => {}
+        err => panic!("{:?}", err),
+    }
+    match send_future.await {
+        Err(RawSendError::RespondError(ProducerSendRespondError::ConnectionLost)) => {}
+        res => panic!("{:?}", res),
+    }
+    assert!(producer.is_closed());
+}
+
+#[tokio::test]
+async fn reconnects_when_the_reregistration_times_out() {
+    let mut config = AsyncConnectionConfig::default();
+    config.set_operation_timeout(Duration::from_millis(200));
+    let (session, mut handler, mut broker) = connect(64 * 1024, config.to_owned()).await;
+    let brokers = reconnect_to_brokers(&mut handler, config);
+    let _handler_task = spawn_handler(handler);
+
+    let _producer = create_producer(&session, &mut broker, None).await;
+    drop(broker);
+
+    // Never answers.
+    let mut broker = next_broker(&brokers).await;
+    let producer_command = broker.expect(Type::PRODUCER).await;
+    assert_eq!(producer_command.get_producer().get_epoch(), 1);
+    assert!(timeout(WAIT, broker.read())
+        .await
+        .expect("timeout")
+        .is_none());
+
+    let mut broker = next_broker(&brokers).await;
+    let producer_command = broker.accept_producer().await;
+    assert_eq!(producer_command.get_producer().get_epoch(), 2);
+}
+
+#[tokio::test]
+async fn drops_the_queued_messages_on_reconnecting() {
+    let (session, mut handler, mut broker) = connect(64 * 1024, None).await;
+    let brokers = reconnect_to_brokers(&mut handler, AsyncConnectionConfig::default());
+    let _handler_task = spawn_handler(handler);
+
+    let mut subscribe_command = SubscribeCommand::new("t", "s", SubscribeType::Exclusive);
+    subscribe_command.set_receiver_queue_size(10);
+    let consumer = create_consumer(&session, &mut broker, subscribe_command).await;
+    let consumer_id = u64::from(consumer.get_consumer_id());
+    broker.expect(Type::FLOW).await;
+
+    for entry_id in 0..3 {
+        broker.write_message(consumer_id, entry_id, b"foo").await;
+    }
+    // One is taken, the others are queued.
+    timeout(WAIT, consumer.receive_message())
+        .await
+        .expect("timeout")
+        .expect("receive_message");
+    drop(broker);
+
+    let mut broker = next_broker(&brokers).await;
+    let subscribe = broker.expect(Type::SUBSCRIBE).await;
+    assert_eq!(subscribe.get_subscribe().get_consumer_id(), consumer_id);
+    broker
+        .write(success(subscribe.get_subscribe().get_request_id()))
+        .await;
+
+    // The unused permits, and the permits of the dropped messages.
+    let flow = broker.expect(Type::FLOW).await;
+    assert_eq!(flow.get_flow().get_messagePermits(), 9);
+    assert!(consumer.get_message().await.expect("get_message").is_none());
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "tokio_io")]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use futures_util::future::join;
+use pulsar_client::{
+    producer::RawSendError,
+    session::{RawCreateProducerError, RawLookupTopicError},
+    spec::{
+        client_responds::{
+            ProducerSendRespondError, SessionCreateProducerRespondError,
+            SessionLookupTopicRespondError,
+        },
+        protos::protobuf::pulsar_api::BaseCommand_Type as Type,
+        types::OutgoingMessage,
+        LookupTopicCommand, ProducerCommand,
+    },
+    tokio_io::connection::AsyncConnectionConfig,
+};
+use tokio::time::timeout;
+
+use common::{command, connect, create_producer, spawn_handler, WAIT};
+
+fn timeout_config() -> AsyncConnectionConfig {
+    let mut config = AsyncConnectionConfig::default();
+    config
+        .set_operation_timeout(Duration::from_millis(200))
+        .set_send_timeout(Duration::from_millis(300));
+    config
+}
+
+#[tokio::test]
+async fn sweeps_the_expired_requests() {
+    let (session, handler, mut broker) = connect(64 * 1024, timeout_config()).await;
+    let _handler_task = spawn_handler(handler);
+
+    let started_at = Instant::now();
+    let (res, producer) = join(
+        session.raw_create_producer(ProducerCommand::new("t")),
+        broker.expect(Type::PRODUCER),
+    )
+    .await;
+    match res {
+        Err(RawCreateProducerError::RespondError(SessionCreateProducerRespondError::Timeout)) => {}
+        res => panic!("{:?}", res),
+    }
+    assert!(started_at.elapsed() >= Duration::from_millis(200));
+
+    // Responded too late, it is ignored and the connection keeps working.
+    let mut c = command(Type::PRODUCER_SUCCESS);
+    c.mut_producer_success()
+        .set_request_id(producer.get_producer().get_request_id());
+    c.mut_producer_success().set_producer_name("p".to_owned());
+    broker.write(c).await;
+
+    let (res, lookup) = join(
+        session.raw_lookup_topic(LookupTopicCommand::new("t")),
+        broker.expect(Type::LOOKUP),
+    )
+    .await;
+    match res {
+        Err(RawLookupTopicError::RespondError(SessionLookupTopicRespondError::Timeout)) => {}
+        res => panic!("{:?}", res),
+    }
+
+    let mut c = command(Type::LOOKUP_RESPONSE);
+    c.mut_lookupTopicResponse()
+        .set_request_id(lookup.get_lookupTopic().get_request_id());
+    broker.write(c).await;
+
+    create_producer(&session, &mut broker, None).await;
+}
+
+#[tokio::test]
+async fn sweeps_the_expired_sends() {
+    let (session, handler, mut broker) = connect(64 * 1024, timeout_config()).await;
+    let _handler_task = spawn_handler(handler);
+
+    let producer = create_producer(&session, &mut broker, None).await;
+
+    let started_at = Instant::now();
+    let first = producer
+        .send_async(OutgoingMessage::new(b"foo"))
+        .await
+        .expect("send_async");
+    let first_send = broker.expect(Type::SEND).await;
+
+    // The later send is not expired yet.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let second = producer
+        .send_async(OutgoingMessage::new(b"bar"))
+        .await
+        .expect("send_async");
+    let second_send = broker.expect(Type::SEND).await;
+
+    match timeout(WAIT, first).await.expect("timeout") {
+        Err(RawSendError::RespondError(ProducerSendRespondError::Timeout)) => {}
+        res => panic!("{:?}", res),
+    }
+    assert!(started_at.elapsed() >= Duration::from_millis(300));
+
+    broker.write_send_receipt(&first_send, 1).await;
+    broker.write_send_receipt(&second_send, 2).await;
+
+    let receipt = timeout(WAIT, second).await.expect("timeout").expect("send");
+    assert_eq!(
+        receipt.get_message_id().expect("message_id").get_entry_id(),
+        2
+    );
+}
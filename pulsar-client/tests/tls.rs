@@ -0,0 +1,176 @@
+#![cfg(feature = "tokio_rustls")]
+
+/*
+The fixtures in tests/tls, a CA that signs the certificate of localhost, and an untrusted CA.
+
+openssl req -x509 -newkey ec -pkeyopt ec_paramgen_curve:P-256 -nodes -days 36500 -subj "/CN=pulsar-rs test ca" \
+    -addext "basicConstraints=critical,CA:TRUE" -addext "keyUsage=critical,keyCertSign" \
+    -keyout /tmp/ca.key.pem -out ca.cert.pem
+openssl req -x509 -newkey ec -pkeyopt ec_paramgen_curve:P-256 -nodes -days 36500 -subj "/CN=pulsar-rs test untrusted_ca" \
+    -addext "basicConstraints=critical,CA:TRUE" -addext "keyUsage=critical,keyCertSign" \
+    -keyout /tmp/untrusted_ca.key.pem -out untrusted_ca.cert.pem
+openssl req -newkey ec -pkeyopt ec_paramgen_curve:P-256 -nodes -subj "/CN=localhost" \
+    -keyout server.key.pem -out /tmp/server.csr.pem
+printf "basicConstraints=critical,CA:FALSE\nsubjectAltName=DNS:localhost\nextendedKeyUsage=serverAuth\n" > /tmp/server.ext
+openssl x509 -req -in /tmp/server.csr.pem -CA ca.cert.pem -CAkey /tmp/ca.key.pem -CAcreateserial \
+    -days 36500 -extfile /tmp/server.ext -out server.cert.pem
+*/
+
+mod common;
+
+use std::{
+    io::{BufReader, Error as IoError, ErrorKind as IoErrorKind},
+    sync::Arc,
+};
+
+use pulsar_client::{
+    spec::broker_service_url::Url,
+    tls::{MaybeTlsStream, TlsConnector, TlsConnectorConfig},
+};
+use rustls::{internal::pemfile, NoClientAuth, ServerConfig};
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::TcpListener,
+    time::timeout,
+};
+use tokio_rustls::TlsAcceptor;
+
+use common::WAIT;
+
+const CA_CERT: &[u8] = include_bytes!("tls/ca.cert.pem");
+const UNTRUSTED_CA_CERT: &[u8] = include_bytes!("tls/untrusted_ca.cert.pem");
+const SERVER_CERT: &[u8] = include_bytes!("tls/server.cert.pem");
+const SERVER_KEY: &[u8] = include_bytes!("tls/server.key.pem");
+
+// Accepts the TLS connections of localhost, echoes what it reads.
+async fn listen() -> u16 {
+    let certs = pemfile::certs(&mut BufReader::new(SERVER_CERT)).expect("certs");
+    let mut keys =
+        pemfile::pkcs8_private_keys(&mut BufReader::new(SERVER_KEY)).expect("pkcs8_private_keys");
+    let mut server_config = ServerConfig::new(NoClientAuth::new());
+    server_config
+        .set_single_cert(certs, keys.remove(0))
+        .expect("set_single_cert");
+    let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let port = listener.local_addr().expect("local_addr").port();
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(x) => x,
+                Err(_) => return,
+            };
+            let acceptor = acceptor.to_owned();
+            tokio::spawn(async move {
+                // Fails when the client rejects the certificate.
+                let mut stream = match acceptor.accept(stream).await {
+                    Ok(stream) => stream,
+                    Err(_) => return,
+                };
+                let mut buf = [0; 4];
+                if stream.read_exact(&mut buf).await.is_ok() {
+                    let _ = stream.write_all(&buf).await;
+                    let _ = stream.flush().await;
+                }
+            });
+        }
+    });
+
+    port
+}
+
+fn tls_url(host: &str, port: u16) -> Url {
+    format!("pulsar+ssl://{}:{}", host, port)
+        .parse()
+        .expect("parse")
+}
+
+async fn ping(connector: &TlsConnector, url: &Url) -> Result<(), IoError> {
+    let mut stream = timeout(WAIT, connector.connect(url))
+        .await
+        .expect("timeout")?;
+    assert!(matches!(stream, MaybeTlsStream::Tls(_)));
+
+    stream.write_all(b"ping").await?;
+    stream.flush().await?;
+    let mut buf = [0; 4];
+    timeout(WAIT, stream.read_exact(&mut buf))
+        .await
+        .expect("timeout")?;
+    assert_eq!(&buf, b"ping");
+
+    Ok(())
+}
+
+// The handshake fails on the certificate.
+fn assert_rejected(res: Result<(), IoError>) {
+    assert_eq!(res.expect_err("ping").kind(), IoErrorKind::InvalidData);
+}
+
+#[tokio::test]
+async fn connects_to_the_server_signed_by_the_ca() {
+    let port = listen().await;
+
+    let mut config = TlsConnectorConfig::default();
+    config.set_ca_certs_pem(CA_CERT);
+    let connector = TlsConnector::new(&config).expect("new");
+
+    ping(&connector, &tls_url("localhost", port))
+        .await
+        .expect("ping");
+}
+
+#[tokio::test]
+async fn fails_on_an_untrusted_certificate() {
+    let port = listen().await;
+
+    let mut config = TlsConnectorConfig::default();
+    config.set_ca_certs_pem(UNTRUSTED_CA_CERT);
+    let connector = TlsConnector::new(&config).expect("new");
+    assert_rejected(ping(&connector, &tls_url("localhost", port)).await);
+
+    // Also without the hostname verification, the chain is verified.
+    config.set_hostname_verification(false);
+    let connector = TlsConnector::new(&config).expect("new");
+    assert_rejected(ping(&connector, &tls_url("localhost", port)).await);
+
+    // The webpki roots.
+    let connector = TlsConnector::new(&TlsConnectorConfig::default()).expect("new");
+    assert_rejected(ping(&connector, &tls_url("localhost", port)).await);
+}
+
+#[tokio::test]
+async fn verifies_the_server_name() {
+    let port = listen().await;
+    let url = tls_url("127.0.0.1", port);
+
+    let mut config = TlsConnectorConfig::default();
+    config.set_ca_certs_pem(CA_CERT);
+
+    // Required for an IP address.
+    let connector = TlsConnector::new(&config).expect("new");
+    let err = ping(&connector, &url).await.expect_err("ping");
+    assert_eq!(err.kind(), IoErrorKind::InvalidInput);
+
+    config.set_server_name("localhost");
+    let connector = TlsConnector::new(&config).expect("new");
+    ping(&connector, &url).await.expect("ping");
+
+    config.set_server_name("example.com");
+    let connector = TlsConnector::new(&config).expect("new");
+    assert_rejected(ping(&connector, &url).await);
+
+    config.set_hostname_verification(false);
+    let connector = TlsConnector::new(&config).expect("new");
+    ping(&connector, &url).await.expect("ping");
+}
+
+#[tokio::test]
+async fn rejects_the_invalid_ca_certs() {
+    let mut config = TlsConnectorConfig::default();
+    config.set_ca_certs_pem(b"foo");
+
+    assert!(TlsConnector::new(&config).is_err());
+}